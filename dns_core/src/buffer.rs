@@ -1,18 +1,89 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::{EncodeError, ParseError},
+    name::{MAX_NAME_LEN, Name},
+};
+
 pub const MAX_PACKET_SIZE: usize = 4096;
 
-pub struct BytePacketBuffer {
-    pub buffer: [u8; MAX_PACKET_SIZE],
+/// Largest message that fits the 16-bit TCP length prefix.
+pub const MAX_MESSAGE_SIZE: usize = 65535;
+
+// Compression pointers carry a 14-bit offset.
+const MAX_POINTER_OFFSET: usize = 0x3FFF;
+
+/// Backing memory for a `BytePacketBuffer`.
+pub trait Storage {
+    fn bytes(&self) -> &[u8];
+
+    /// Mutable access to at least `len` bytes, growing the storage if it
+    /// can. Returns `None` when `len` bytes do not fit.
+    fn bytes_mut(&mut self, len: usize) -> Option<&mut [u8]>;
+}
+
+impl<const N: usize> Storage for [u8; N] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn bytes_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        (len <= N).then_some(self)
+    }
+}
+
+/// Grows on demand up to `MAX_MESSAGE_SIZE`; the vector's length is the
+/// furthest position written.
+impl Storage for Vec<u8> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn bytes_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        if len > MAX_MESSAGE_SIZE {
+            return None;
+        }
+        if len > self.len() {
+            self.resize(len, 0);
+        }
+        Some(self)
+    }
+}
+
+impl Storage for &mut [u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn bytes_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        (len <= self.len()).then_some(&mut **self)
+    }
+}
+
+/// Read-only; every write fails.
+impl Storage for &[u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn bytes_mut(&mut self, _len: usize) -> Option<&mut [u8]> {
+        None
+    }
+}
+
+pub struct BytePacketBuffer<S: Storage = [u8; MAX_PACKET_SIZE]> {
+    pub buffer: S,
     pub position: usize,
     size: usize,
+    compression: bool,
+    // Wire form of each name suffix -> offset where it was first written.
+    // Keyed by exact bytes so compression never rewrites the caller's case.
+    names: HashMap<Vec<u8>, usize>,
 }
 
 impl Default for BytePacketBuffer {
     fn default() -> Self {
-        BytePacketBuffer {
-            buffer: [0; MAX_PACKET_SIZE],
-            position: 0,
-            size: MAX_PACKET_SIZE,
-        }
+        BytePacketBuffer::with_storage([0; MAX_PACKET_SIZE])
     }
 }
 
@@ -20,14 +91,53 @@ impl BytePacketBuffer {
     pub fn new() -> BytePacketBuffer {
         Self::default()
     }
+}
+
+impl BytePacketBuffer<Vec<u8>> {
+    /// An empty buffer that grows as it is written, up to 65535 bytes.
+    pub fn growable() -> BytePacketBuffer<Vec<u8>> {
+        BytePacketBuffer::with_storage(Vec::new())
+    }
+}
+
+impl<'a> BytePacketBuffer<&'a [u8]> {
+    /// A read-only buffer over a received message.
+    pub fn from_bytes(bytes: &'a [u8]) -> BytePacketBuffer<&'a [u8]> {
+        BytePacketBuffer::with_storage(bytes)
+    }
+}
+
+impl<S: Storage> BytePacketBuffer<S> {
+    pub fn with_storage(buffer: S) -> BytePacketBuffer<S> {
+        BytePacketBuffer {
+            buffer,
+            position: 0,
+            size: MAX_MESSAGE_SIZE,
+            compression: true,
+            names: HashMap::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.buffer
+    }
+
+    // Readable length: the size limit capped by what the storage holds.
+    fn limit(&self) -> usize {
+        self.size.min(self.buffer.bytes().len())
+    }
 
-    pub fn set(&mut self, pos: usize, val: u8) -> Result<(), Box<dyn std::error::Error>> {
-        self.buffer[pos] = val;
+    pub fn set(&mut self, pos: usize, val: u8) -> Result<(), EncodeError> {
+        let bytes = pos
+            .checked_add(1)
+            .and_then(|len| self.buffer.bytes_mut(len))
+            .ok_or(EncodeError::BufferFull { offset: pos })?;
+        bytes[pos] = val;
 
         Ok(())
     }
 
-    pub fn set_u16(&mut self, pos: usize, val: u16) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn set_u16(&mut self, pos: usize, val: u16) -> Result<(), EncodeError> {
         self.set(pos, (val >> 8) as u8)?;
         self.set(pos + 1, (val & 0xFF) as u8)?;
 
@@ -39,76 +149,121 @@ impl BytePacketBuffer {
     }
 
     pub fn set_size(&mut self, size: usize) {
-        self.size = size.min(MAX_PACKET_SIZE);
+        self.size = size.min(MAX_MESSAGE_SIZE);
     }
 
-    fn write(&mut self, val: u8) -> Result<(), Box<dyn std::error::Error>> {
-        if self.position >= MAX_PACKET_SIZE {
-            return Err("End of buffer reached".into());
-        }
+    /// Enables or disables name compression for `write_qname`. Compression is
+    /// on by default; turn it off when producing canonical (DNSSEC) wire form.
+    pub fn set_compression(&mut self, enabled: bool) {
+        self.compression = enabled;
+    }
+
+    pub fn compression(&self) -> bool {
+        self.compression
+    }
 
-        self.buffer[self.position] = val;
+    /// Forgets every name written so far, so later names cannot point at them.
+    pub fn clear_names(&mut self) {
+        self.names.clear();
+    }
+
+    fn write(&mut self, val: u8) -> Result<(), EncodeError> {
+        self.set(self.position, val)?;
         self.position += 1;
         Ok(())
     }
 
-    pub fn write_u8(&mut self, val: u8) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn write_u8(&mut self, val: u8) -> Result<(), EncodeError> {
         self.write(val)
     }
 
-    pub fn write_u16(&mut self, val: u16) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn write_u16(&mut self, val: u16) -> Result<(), EncodeError> {
         self.write((val >> 8) as u8)?;
         self.write((val & 0xff) as u8)
     }
 
-    pub fn write_u32(&mut self, val: u32) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn write_u32(&mut self, val: u32) -> Result<(), EncodeError> {
         self.write(((val >> 24) & 0xff) as u8)?;
         self.write(((val >> 16) & 0xff) as u8)?;
         self.write(((val >> 8) & 0xff) as u8)?;
         self.write((val & 0xff) as u8)
     }
 
-    pub fn read(&mut self) -> Result<u8, Box<dyn std::error::Error>> {
-        if self.position >= self.size {
-            return Err("End of buffer reached".into());
-        }
-
-        let res = self.buffer[self.position];
+    pub fn read(&mut self) -> Result<u8, ParseError> {
+        let res = self.get(self.position)?;
 
         self.position += 1;
 
         Ok(res)
     }
 
-    pub fn get(&self, pos: usize) -> Result<u8, Box<dyn std::error::Error>> {
-        if pos >= self.size {
-            return Err("End of buffer reached".into());
+    pub fn get(&self, pos: usize) -> Result<u8, ParseError> {
+        if pos >= self.limit() {
+            return Err(ParseError::EndOfBuffer { offset: pos });
         }
 
-        Ok(self.buffer[pos])
+        Ok(self.buffer.bytes()[pos])
     }
 
-    pub fn step(&mut self, steps: usize) {
-        self.position += steps;
+    /// Advances the position, failing if that would move past the end.
+    pub fn step(&mut self, steps: usize) -> Result<(), ParseError> {
+        let pos = self
+            .position
+            .checked_add(steps)
+            .ok_or(ParseError::EndOfBuffer { offset: usize::MAX })?;
+        self.seek(pos)
     }
 
-    pub fn seek(&mut self, pos: usize) {
-        self.position = pos
+    /// Moves to `pos`, which may be at most one past the last readable byte.
+    pub fn seek(&mut self, pos: usize) -> Result<(), ParseError> {
+        if pos > self.limit() {
+            return Err(ParseError::EndOfBuffer { offset: pos });
+        }
+        self.position = pos;
+        Ok(())
     }
 
-    pub fn get_range(&self, start: usize, end: usize) -> Result<&[u8], Box<dyn std::error::Error>> {
-        let len = start + end;
-        if len > self.size {
-            return Err("End of buffer reached".into());
+    /// The bytes in `start..end`.
+    pub fn get_range(&self, start: usize, end: usize) -> Result<&[u8], ParseError> {
+        if start > end || end > self.limit() {
+            return Err(ParseError::EndOfBuffer {
+                offset: end.min(self.limit()),
+            });
         }
-        Ok(&self.buffer[start..len])
+        Ok(&self.buffer.bytes()[start..end])
     }
 
-    pub fn read_u16(&mut self) -> Result<u16, Box<dyn std::error::Error>> {
+    /// Reads the next `len` bytes and advances past them.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&[u8], ParseError> {
+        let start = self.position;
+        let end = start
+            .checked_add(len)
+            .ok_or(ParseError::EndOfBuffer { offset: usize::MAX })?;
+        if end > self.limit() {
+            return Err(ParseError::EndOfBuffer {
+                offset: self.limit(),
+            });
+        }
+        self.position = end;
+        Ok(&self.buffer.bytes()[start..end])
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        let start = self.position;
+        let storage = start
+            .checked_add(bytes.len())
+            .and_then(|end| self.buffer.bytes_mut(end))
+            .ok_or(EncodeError::BufferFull { offset: start })?;
+        storage[start..start + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+        Ok(())
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, ParseError> {
         Ok(((self.read()? as u16) << 8) | (self.read()? as u16))
     }
 
-    pub fn read_u32(&mut self) -> Result<u32, Box<dyn std::error::Error>> {
+    pub fn read_u32(&mut self) -> Result<u32, ParseError> {
         let first_byte = self.read()? as u32;
         let second_byte = self.read()? as u32;
         let third_byte = self.read()? as u32;
@@ -117,79 +272,147 @@ impl BytePacketBuffer {
         Ok((first_byte << 24) | (second_byte << 16) | (third_byte << 8) | fourth_byte)
     }
 
-    pub fn write_qname(&mut self, domain: &str) -> Result<(), Box<dyn std::error::Error>> {
-        for label in domain.split(".") {
-            let len = label.len();
+    /// Reads a length-prefixed <character-string> (RFC 1035 section 3.3).
+    pub fn read_character_string(&mut self) -> Result<&[u8], ParseError> {
+        let len = self.read()? as usize;
+        self.read_bytes(len)
+    }
 
-            if len > 0x3f {
-                return Err("Single label exceeds 63 characters of length".into());
-            }
+    pub fn write_character_string(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        if bytes.len() > u8::MAX as usize {
+            return Err(EncodeError::StringTooLong {
+                offset: self.position,
+                len: bytes.len(),
+            });
+        }
+        self.write_u8(bytes.len() as u8)?;
+        self.write_bytes(bytes)
+    }
+
+    /// Writes `name`, replacing any suffix already present in the buffer
+    /// with a compression pointer when compression is enabled.
+    pub fn write_qname(&mut self, name: &Name) -> Result<(), EncodeError> {
+        self.write_name(name, self.compression)
+    }
+
+    /// Writes `name` as a full label sequence. Used for RDATA names that
+    /// must not be compressed (RFC 3597 section 4).
+    pub fn write_qname_uncompressed(&mut self, name: &Name) -> Result<(), EncodeError> {
+        self.write_name(name, false)
+    }
 
-            self.write_u8(len as u8)?;
+    fn write_name(&mut self, name: &Name, compress: bool) -> Result<(), EncodeError> {
+        let mut suffix = name.clone();
 
-            for char_byte in label.as_bytes() {
-                self.write_u8(*char_byte)?;
+        while let Some(parent) = suffix.parent() {
+            let key = suffix.to_wire();
+            match self.names.get(&key) {
+                Some(&offset) if compress => {
+                    return self.write_u16(0xC000 | offset as u16);
+                }
+                Some(_) => {}
+                None if self.position <= MAX_POINTER_OFFSET => {
+                    self.names.insert(key, self.position);
+                }
+                None => {}
             }
+
+            let label = suffix.labels().next().unwrap_or_default();
+            self.write_u8(label.len() as u8)?;
+            self.write_bytes(label)?;
+
+            suffix = parent;
         }
 
         self.write_u8(0)
     }
 
-    pub fn read_qname(&mut self, out_str: &mut String) -> Result<(), Box<dyn std::error::Error>> {
-        let mut pos = self.pos();
+    pub fn read_qname(&mut self, out: &mut Name) -> Result<(), ParseError> {
+        let start = self.pos();
+        let mut name = Name::root();
 
-        let mut jumped = false;
-        let max_jumps = 50;
-        let mut jumps_performed = 0;
+        let end = walk_name(&self.buffer.bytes()[..self.limit()], start, |label| {
+            name.push_label(label)
+                .map_err(|_| ParseError::NameTooLong { offset: start })
+        })?;
 
-        let mut delimiter = "";
+        self.position = end;
+        *out = name;
+        Ok(())
+    }
+}
 
-        loop {
-            if jumps_performed > max_jumps {
-                return Err("Name reading jump limit reached".into());
+/// Validates the possibly compressed name at `start` in `message`, handing
+/// each label to `on_label` in order. Returns the position just past the
+/// name's in-place encoding. Shared by the owned and borrowed parsers.
+pub(crate) fn walk_name<'a>(
+    message: &'a [u8],
+    start: usize,
+    mut on_label: impl FnMut(&'a [u8]) -> Result<(), ParseError>,
+) -> Result<usize, ParseError> {
+    let mut pos = start;
+
+    let mut end = None;
+    let max_jumps = 50;
+    let mut jumps_performed = 0;
+    let mut wire_len = 0;
+
+    loop {
+        if jumps_performed > max_jumps {
+            return Err(ParseError::CompressionLoop { offset: pos });
+        }
+        let length_byte = *message
+            .get(pos)
+            .ok_or(ParseError::EndOfBuffer { offset: pos })?;
+
+        match length_byte & 0xC0 {
+            0xC0 => {
+                let byte_2nd = *message
+                    .get(pos + 1)
+                    .ok_or(ParseError::EndOfBuffer { offset: pos + 1 })?;
+                end.get_or_insert(pos + 2);
+
+                pos = (((length_byte as usize) & 0x3F) << 8) | (byte_2nd as usize);
+                jumps_performed += 1;
             }
-            let length_byte = self.get(pos)?;
-
-            if (length_byte & 0xC0) == 0xC0 {
-                if !jumped {
-                    self.seek(pos + 2);
+            0x00 => {
+                wire_len += length_byte as usize + 1;
+                if wire_len > MAX_NAME_LEN {
+                    return Err(ParseError::NameTooLong { offset: start });
                 }
 
-                let byte_2nd = self.get(pos + 1)?;
-                let offset = (((length_byte as u16) ^ 0xC0) << 8) | (byte_2nd as u16);
-                pos = offset as usize;
-
-                jumped = true;
-                jumps_performed += 1;
-                continue;
-            } else {
                 pos += 1;
 
                 if length_byte == 0 {
-                    break;
+                    return Ok(end.unwrap_or(pos));
                 }
 
-                out_str.push_str(delimiter);
-                let str_buffer = self.get_range(pos, length_byte as usize)?;
-                out_str.push_str(&String::from_utf8_lossy(str_buffer).to_lowercase());
-
-                delimiter = ".";
+                let label = message.get(pos..pos + length_byte as usize).ok_or(
+                    ParseError::EndOfBuffer {
+                        offset: message.len(),
+                    },
+                )?;
+                on_label(label)?;
 
                 pos += length_byte as usize;
             }
+            _ => {
+                return Err(ParseError::InvalidLabelType {
+                    offset: pos,
+                    byte: length_byte,
+                });
+            }
         }
-
-        if !jumped {
-            self.seek(pos);
-        }
-
-        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::buffer::MAX_PACKET_SIZE;
+    use crate::{
+        buffer::{MAX_MESSAGE_SIZE, MAX_PACKET_SIZE},
+        error::{EncodeError, ParseError},
+        name::Name,
+    };
 
     use super::BytePacketBuffer;
 
@@ -205,12 +428,148 @@ mod tests {
         // The 513th byte should error because the buffer is full
         assert!(buffer.write_u8(0).is_err());
 
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
         assert_eq!(0, buffer.pos());
         assert_eq!(0, buffer.read().unwrap());
 
-        buffer.step(10);
+        buffer.step(10).unwrap();
         assert_eq!(11, buffer.pos());
         assert_eq!(10, buffer.get(10).unwrap());
     }
+
+    #[test]
+    fn read_qname_reports_compression_loops() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_u8(0xC0).unwrap();
+        buffer.write_u8(0x02).unwrap();
+        buffer.write_u8(0xC0).unwrap();
+        buffer.write_u8(0x00).unwrap();
+        buffer.seek(0).unwrap();
+
+        let mut out = Name::root();
+        assert!(matches!(
+            buffer.read_qname(&mut out),
+            Err(ParseError::CompressionLoop { .. })
+        ));
+    }
+
+    #[test]
+    fn write_qname_compresses_repeated_suffixes() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"example.com".parse().unwrap()).unwrap();
+        let second = buffer.pos();
+        buffer
+            .write_qname(&"www.example.com".parse().unwrap())
+            .unwrap();
+
+        // "www" label followed by a pointer back to offset 0
+        assert_eq!(second + 6, buffer.pos());
+        assert_eq!(0xC0, buffer.get(second + 4).unwrap());
+        assert_eq!(0x00, buffer.get(second + 5).unwrap());
+
+        buffer.seek(second).unwrap();
+        let mut out = Name::root();
+        buffer.read_qname(&mut out).unwrap();
+        assert_eq!("www.example.com.", out.to_string());
+    }
+
+    #[test]
+    fn uncompressed_names_are_written_in_full() {
+        let name: Name = "example.com".parse().unwrap();
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&name).unwrap();
+        let second = buffer.pos();
+        buffer.write_qname_uncompressed(&name).unwrap();
+        assert_eq!(2 * second, buffer.pos());
+
+        let mut disabled = BytePacketBuffer::new();
+        disabled.set_compression(false);
+        disabled.write_qname(&name).unwrap();
+        disabled.write_qname(&name).unwrap();
+        assert_eq!(2 * second, disabled.pos());
+    }
+
+    #[test]
+    fn names_keep_their_case_through_compression_and_decoding() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"example.com".parse().unwrap()).unwrap();
+        let second = buffer.pos();
+        buffer
+            .write_qname(&"wWw.ExAmPlE.com".parse().unwrap())
+            .unwrap();
+
+        // Only the identically cased "com" suffix is shared
+        assert_eq!(second + 4 + 8 + 2, buffer.pos());
+
+        buffer.seek(second).unwrap();
+        let mut out = Name::root();
+        buffer.read_qname(&mut out).unwrap();
+        assert_eq!("wWw.ExAmPlE.com.", out.to_string());
+    }
+
+    #[test]
+    fn growable_buffer_extends_up_to_max_message_size() {
+        let mut buffer = BytePacketBuffer::growable();
+        assert!(buffer.read().is_err());
+
+        for i in 0..MAX_MESSAGE_SIZE {
+            buffer.write_u8(i as u8).unwrap();
+        }
+        assert_eq!(
+            Err(EncodeError::BufferFull {
+                offset: MAX_MESSAGE_SIZE
+            }),
+            buffer.write_u8(0)
+        );
+
+        buffer.seek(MAX_PACKET_SIZE).unwrap();
+        assert_eq!((MAX_PACKET_SIZE & 0xFF) as u8, buffer.read().unwrap());
+        assert_eq!(MAX_MESSAGE_SIZE, buffer.into_inner().len());
+    }
+
+    #[test]
+    fn read_only_buffers_reject_writes() {
+        let bytes = [0x12, 0x34];
+        let mut buffer = BytePacketBuffer::from_bytes(&bytes);
+        assert_eq!(0x1234, buffer.read_u16().unwrap());
+        assert!(buffer.read().is_err());
+        assert!(buffer.write_u8(0).is_err());
+    }
+
+    #[test]
+    fn positional_access_fails_instead_of_panicking() {
+        let mut storage = [0u8; 4];
+        let mut buffer = BytePacketBuffer::with_storage(&mut storage[..]);
+
+        assert!(buffer.set(4, 1).is_err());
+        assert!(buffer.set(usize::MAX, 1).is_err());
+        assert!(buffer.set_u16(3, 1).is_err());
+        assert!(buffer.seek(5).is_err());
+        assert!(buffer.step(usize::MAX).is_err());
+        assert_eq!(0, buffer.pos());
+
+        buffer.seek(4).unwrap();
+        assert!(buffer.step(1).is_err());
+        assert!(buffer.get(4).is_err());
+        assert!(buffer.read_bytes(1).is_err());
+        assert!(buffer.write_bytes(&[1]).is_err());
+
+        assert!(buffer.get_range(3, 2).is_err());
+        assert!(buffer.get_range(2, 5).is_err());
+        assert_eq!(&[0, 0], buffer.get_range(2, 4).unwrap());
+    }
+
+    #[test]
+    fn bulk_reads_and_writes() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_bytes(b"hello").unwrap();
+        buffer.write_u8(b'!').unwrap();
+        assert_eq!(6, buffer.pos());
+
+        buffer.seek(0).unwrap();
+        assert_eq!(b"hell", buffer.read_bytes(4).unwrap());
+        assert_eq!(b"o!", buffer.read_bytes(2).unwrap());
+        assert_eq!(6, buffer.pos());
+        assert_eq!(b"ello", buffer.get_range(1, 5).unwrap());
+    }
 }
@@ -0,0 +1,506 @@
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    error::{EncodeError, ParseError},
+    name::Name,
+    types::QueryType,
+};
+
+/// Payload size advertised by `Edns::new`, small enough to avoid IP
+/// fragmentation on common paths.
+pub const DEFAULT_PAYLOAD_SIZE: u16 = 1232;
+
+const DO_FLAG: u16 = 0x8000;
+
+/// An option carried in the OPT record. Options whose data does not match
+/// their format are kept as `UNKNOWN` so they still round-trip.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EdnsOption {
+    UNKNOWN(u16, Vec<u8>),
+    /// Name server identifier (RFC 5001); empty in requests.
+    NSID(Vec<u8>), // 3
+    /// Client subnet (RFC 7871). Only the first `source_prefix` bits of
+    /// `addr` are sent.
+    ECS {
+        source_prefix: u8,
+        scope_prefix: u8,
+        addr: IpAddr,
+    }, // 8
+    /// DNS cookie (RFC 7873); `server` is empty or 8 to 32 bytes.
+    COOKIE {
+        client: [u8; 8],
+        server: Vec<u8>,
+    }, // 10
+    /// TCP keepalive timeout in units of 100 ms (RFC 7828); `None` in
+    /// requests.
+    KEEPALIVE(Option<u16>), // 11
+    /// Number of padding bytes (RFC 7830).
+    PADDING(u16), // 12
+    /// Extended DNS error (RFC 8914).
+    EDE {
+        info_code: u16,
+        extra_text: String,
+    }, // 15
+}
+
+impl EdnsOption {
+    pub fn code(&self) -> u16 {
+        match self {
+            EdnsOption::UNKNOWN(code, _) => *code,
+            EdnsOption::NSID(_) => 3,
+            EdnsOption::ECS { .. } => 8,
+            EdnsOption::COOKIE { .. } => 10,
+            EdnsOption::KEEPALIVE(_) => 11,
+            EdnsOption::PADDING(_) => 12,
+            EdnsOption::EDE { .. } => 15,
+        }
+    }
+
+    fn read<S: Storage>(
+        buffer: &mut BytePacketBuffer<S>,
+        code: u16,
+        len: usize,
+    ) -> Result<EdnsOption, ParseError> {
+        let data = buffer.read_bytes(len)?;
+        Ok(EdnsOption::decode(code, data)
+            .unwrap_or_else(|| EdnsOption::UNKNOWN(code, data.to_vec())))
+    }
+
+    fn decode(code: u16, data: &[u8]) -> Option<EdnsOption> {
+        let option = match code {
+            3 => EdnsOption::NSID(data.to_vec()),
+            8 => {
+                let (&[f0, f1, source_prefix, scope_prefix], addr) = data.split_first_chunk()?;
+                let addr = match u16::from_be_bytes([f0, f1]) {
+                    1 => IpAddr::V4(Ipv4Addr::from(prefix_octets::<4>(addr, source_prefix)?)),
+                    2 => IpAddr::V6(Ipv6Addr::from(prefix_octets::<16>(addr, source_prefix)?)),
+                    _ => return None,
+                };
+                EdnsOption::ECS {
+                    source_prefix,
+                    scope_prefix,
+                    addr,
+                }
+            }
+            10 => {
+                let (client, server) = data.split_first_chunk::<8>()?;
+                if !server.is_empty() && !(8..=32).contains(&server.len()) {
+                    return None;
+                }
+                EdnsOption::COOKIE {
+                    client: *client,
+                    server: server.to_vec(),
+                }
+            }
+            11 => match data {
+                [] => EdnsOption::KEEPALIVE(None),
+                &[hi, lo] => EdnsOption::KEEPALIVE(Some(u16::from_be_bytes([hi, lo]))),
+                _ => return None,
+            },
+            12 => EdnsOption::PADDING(data.len() as u16),
+            15 => {
+                let (info_code, text) = data.split_first_chunk::<2>()?;
+                EdnsOption::EDE {
+                    info_code: u16::from_be_bytes(*info_code),
+                    extra_text: String::from_utf8(text.to_vec()).ok()?,
+                }
+            }
+            _ => return None,
+        };
+        Some(option)
+    }
+
+    fn write<S: Storage>(&self, buffer: &mut BytePacketBuffer<S>) -> Result<(), EncodeError> {
+        buffer.write_u16(self.code())?;
+        let len_pos = buffer.pos();
+        buffer.write_u16(0)?;
+        let data_start = buffer.pos();
+
+        match self {
+            EdnsOption::UNKNOWN(_, data) | EdnsOption::NSID(data) => buffer.write_bytes(data)?,
+            EdnsOption::ECS {
+                source_prefix,
+                scope_prefix,
+                addr,
+            } => {
+                let (family, octets) = match addr {
+                    IpAddr::V4(addr) => (1, addr.octets().to_vec()),
+                    IpAddr::V6(addr) => (2, addr.octets().to_vec()),
+                };
+                let source_prefix = (*source_prefix as usize).min(octets.len() * 8);
+                buffer.write_u16(family)?;
+                buffer.write_u8(source_prefix as u8)?;
+                buffer.write_u8(*scope_prefix)?;
+
+                // Address bits beyond the prefix must be zero.
+                let mut prefix = octets[..source_prefix.div_ceil(8)].to_vec();
+                if let Some(last) = prefix.last_mut() {
+                    *last &= 0xffu8 << ((8 - source_prefix % 8) % 8);
+                }
+                buffer.write_bytes(&prefix)?;
+            }
+            EdnsOption::COOKIE { client, server } => {
+                buffer.write_bytes(client)?;
+                buffer.write_bytes(server)?;
+            }
+            EdnsOption::KEEPALIVE(timeout) => {
+                if let Some(timeout) = timeout {
+                    buffer.write_u16(*timeout)?;
+                }
+            }
+            EdnsOption::PADDING(len) => buffer.write_bytes(&vec![0; *len as usize])?,
+            EdnsOption::EDE {
+                info_code,
+                extra_text,
+            } => {
+                buffer.write_u16(*info_code)?;
+                buffer.write_bytes(extra_text.as_bytes())?;
+            }
+        }
+
+        let len = buffer.pos() - data_start;
+        if len > u16::MAX as usize {
+            return Err(EncodeError::RdataTooLong {
+                offset: data_start,
+                len,
+            });
+        }
+        buffer.set_u16(len_pos, len as u16)
+    }
+}
+
+// Widens the truncated ECS address back to a full address. The data must
+// hold exactly as many bytes as the prefix needs.
+fn prefix_octets<const N: usize>(data: &[u8], prefix: u8) -> Option<[u8; N]> {
+    let prefix = prefix as usize;
+    if prefix > N * 8 || data.len() != prefix.div_ceil(8) {
+        return None;
+    }
+    let mut octets = [0u8; N];
+    octets[..data.len()].copy_from_slice(data);
+    Some(octets)
+}
+
+impl fmt::Display for EdnsOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdnsOption::UNKNOWN(code, data) => {
+                write!(f, "OPT{code}: ")?;
+                write_hex(f, data)
+            }
+            EdnsOption::NSID(data) => {
+                f.write_str("NSID: ")?;
+                write_hex(f, data)?;
+                write!(f, " (\"{}\")", String::from_utf8_lossy(data))
+            }
+            EdnsOption::ECS {
+                source_prefix,
+                scope_prefix,
+                addr,
+            } => write!(f, "CLIENT-SUBNET: {addr}/{source_prefix}/{scope_prefix}"),
+            EdnsOption::COOKIE { client, server } => {
+                f.write_str("COOKIE: ")?;
+                write_hex(f, client)?;
+                write_hex(f, server)
+            }
+            EdnsOption::KEEPALIVE(None) => f.write_str("TCP-KEEPALIVE"),
+            EdnsOption::KEEPALIVE(Some(timeout)) => {
+                write!(f, "TCP-KEEPALIVE: {}.{} secs", timeout / 10, timeout % 10)
+            }
+            EdnsOption::PADDING(len) => write!(f, "PADDING: {len} bytes"),
+            EdnsOption::EDE {
+                info_code,
+                extra_text,
+            } => {
+                write!(f, "EDE: {info_code}")?;
+                if !extra_text.is_empty() {
+                    write!(f, " ({extra_text})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+    for byte in data {
+        write!(f, "{byte:02x}")?;
+    }
+    Ok(())
+}
+
+/// The EDNS(0) OPT pseudo-record (RFC 6891), lifted out of the additional
+/// section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edns {
+    pub payload_size: u16,
+    /// Upper eight bits of the 12-bit RCODE, see `DnsPacket::rescode`.
+    pub ext_rcode: u8,
+    pub version: u8,
+    pub dnssec_ok: bool,
+    /// Remaining flag bits, kept so unknown flags round-trip.
+    pub z: u16,
+    pub options: Vec<EdnsOption>,
+}
+
+impl Default for Edns {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Edns {
+    pub fn new() -> Edns {
+        Edns {
+            payload_size: DEFAULT_PAYLOAD_SIZE,
+            ext_rcode: 0,
+            version: 0,
+            dnssec_ok: false,
+            z: 0,
+            options: Vec::new(),
+        }
+    }
+
+    /// The largest UDP response the sender accepts. Values below 512 are
+    /// treated as 512.
+    pub fn max_payload(&self) -> usize {
+        self.payload_size.max(512) as usize
+    }
+
+    /// Reads a whole OPT record, starting at its owner name.
+    pub fn read<S: Storage>(buffer: &mut BytePacketBuffer<S>) -> Result<Edns, ParseError> {
+        let mut owner = Name::root();
+        buffer.read_qname(&mut owner)?;
+        let _ = buffer.read_u16()?; // type
+
+        let payload_size = buffer.read_u16()?;
+        let ttl = buffer.read_u32()?;
+        let len = buffer.read_u16()? as usize;
+        let rdata_start = buffer.pos();
+
+        let mut options = Vec::new();
+        while buffer.pos() - rdata_start < len {
+            let code = buffer.read_u16()?;
+            let option_len = buffer.read_u16()? as usize;
+            let consumed = buffer.pos() - rdata_start + option_len;
+            if consumed > len {
+                return Err(ParseError::RdataLengthMismatch {
+                    offset: rdata_start,
+                    expected: len,
+                    actual: consumed,
+                });
+            }
+            options.push(EdnsOption::read(buffer, code, option_len)?);
+        }
+
+        let consumed = buffer.pos() - rdata_start;
+        if consumed != len {
+            return Err(ParseError::RdataLengthMismatch {
+                offset: rdata_start,
+                expected: len,
+                actual: consumed,
+            });
+        }
+
+        let flags = ttl as u16;
+        Ok(Edns {
+            payload_size,
+            ext_rcode: (ttl >> 24) as u8,
+            version: (ttl >> 16) as u8,
+            dnssec_ok: flags & DO_FLAG != 0,
+            z: flags & !DO_FLAG,
+            options,
+        })
+    }
+
+    pub fn write<S: Storage>(
+        &self,
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<usize, EncodeError> {
+        let start_pos = buffer.pos();
+
+        buffer.write_u8(0)?;
+        buffer.write_u16(QueryType::OPT.to_num())?;
+        buffer.write_u16(self.payload_size)?;
+
+        let flags = (self.z & !DO_FLAG) | if self.dnssec_ok { DO_FLAG } else { 0 };
+        buffer.write_u32(
+            ((self.ext_rcode as u32) << 24) | ((self.version as u32) << 16) | flags as u32,
+        )?;
+
+        let rdlength_pos = buffer.pos();
+        buffer.write_u16(0)?;
+        let rdata_start = buffer.pos();
+
+        for option in &self.options {
+            option.write(buffer)?;
+        }
+
+        let rdata_len = buffer.pos() - rdata_start;
+        if rdata_len > u16::MAX as usize {
+            return Err(EncodeError::RdataTooLong {
+                offset: rdata_start,
+                len: rdata_len,
+            });
+        }
+        buffer.set_u16(rdlength_pos, rdata_len as u16)?;
+
+        Ok(buffer.pos() - start_pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edns, EdnsOption};
+    use crate::{buffer::BytePacketBuffer, error::ParseError};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn roundtrip(options: Vec<EdnsOption>) -> Vec<EdnsOption> {
+        let mut edns = Edns::new();
+        edns.options = options;
+        let mut buffer = BytePacketBuffer::new();
+        edns.write(&mut buffer).unwrap();
+        buffer.seek(0).unwrap();
+        Edns::read(&mut buffer).unwrap().options
+    }
+
+    #[test]
+    fn opt_record_roundtrip() {
+        let edns = Edns {
+            payload_size: 4096,
+            ext_rcode: 1,
+            version: 0,
+            dnssec_ok: true,
+            z: 0x0001,
+            options: vec![EdnsOption::UNKNOWN(65001, vec![1, 2, 3])],
+        };
+
+        let mut buffer = BytePacketBuffer::new();
+        let written = edns.write(&mut buffer).unwrap();
+        assert_eq!(11 + 7, written);
+        assert_eq!(
+            &[0, 0, 41, 0x10, 0, 1, 0, 0x80, 1, 0, 7],
+            buffer.get_range(0, 11).unwrap()
+        );
+
+        buffer.seek(0).unwrap();
+        assert_eq!(edns, Edns::read(&mut buffer).unwrap());
+        assert_eq!(written, buffer.pos());
+    }
+
+    #[test]
+    fn option_overrunning_rdata_is_rejected() {
+        let message = [0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 5, 0, 10, 0, 2, 0xAA];
+        let mut buffer = BytePacketBuffer::from_bytes(&message);
+        assert_eq!(
+            Err(ParseError::RdataLengthMismatch {
+                offset: 11,
+                expected: 5,
+                actual: 6
+            }),
+            Edns::read(&mut buffer)
+        );
+    }
+
+    #[test]
+    fn small_payload_sizes_mean_512() {
+        let mut edns = Edns::new();
+        edns.payload_size = 100;
+        assert_eq!(512, edns.max_payload());
+    }
+
+    #[test]
+    fn typed_options_roundtrip() {
+        let options = vec![
+            EdnsOption::NSID(b"ams-1".to_vec()),
+            EdnsOption::ECS {
+                source_prefix: 24,
+                scope_prefix: 0,
+                addr: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 0)),
+            },
+            EdnsOption::ECS {
+                source_prefix: 56,
+                scope_prefix: 48,
+                addr: "2001:db8:1:200::".parse().unwrap(),
+            },
+            EdnsOption::COOKIE {
+                client: [1, 2, 3, 4, 5, 6, 7, 8],
+                server: vec![9; 16],
+            },
+            EdnsOption::KEEPALIVE(None),
+            EdnsOption::KEEPALIVE(Some(600)),
+            EdnsOption::PADDING(32),
+            EdnsOption::EDE {
+                info_code: 18,
+                extra_text: "prohibited".to_string(),
+            },
+            EdnsOption::UNKNOWN(65001, vec![1]),
+        ];
+        assert_eq!(options, roundtrip(options.clone()));
+    }
+
+    #[test]
+    fn client_subnet_sends_only_the_prefix() {
+        let mut edns = Edns::new();
+        edns.options = vec![EdnsOption::ECS {
+            source_prefix: 20,
+            scope_prefix: 0,
+            addr: IpAddr::V4(Ipv4Addr::new(198, 51, 100, 77)),
+        }];
+        let mut buffer = BytePacketBuffer::new();
+        let written = edns.write(&mut buffer).unwrap();
+        assert_eq!(
+            &[0, 8, 0, 7, 0, 1, 20, 0, 198, 51, 96],
+            buffer.get_range(11, written).unwrap()
+        );
+
+        let masked = EdnsOption::ECS {
+            source_prefix: 20,
+            scope_prefix: 0,
+            addr: IpAddr::V4(Ipv4Addr::new(198, 51, 96, 0)),
+        };
+        assert_eq!(vec![masked], roundtrip(edns.options));
+    }
+
+    #[test]
+    fn malformed_options_are_kept_raw() {
+        let options = vec![
+            // ECS with more address bytes than the prefix needs
+            EdnsOption::UNKNOWN(8, vec![0, 1, 8, 0, 10, 0]),
+            // client cookie too short
+            EdnsOption::UNKNOWN(10, vec![1, 2, 3]),
+            EdnsOption::UNKNOWN(11, vec![0]),
+            // extra text that is not UTF-8
+            EdnsOption::UNKNOWN(15, vec![0, 18, 0xff]),
+        ];
+        assert_eq!(options, roundtrip(options.clone()));
+
+        let v6 = EdnsOption::UNKNOWN(8, vec![0, 2, 129, 0]);
+        assert_eq!(vec![v6.clone()], roundtrip(vec![v6]));
+    }
+
+    #[test]
+    fn options_display_like_dig() {
+        assert_eq!(
+            "NSID: 616d73 (\"ams\")",
+            EdnsOption::NSID(b"ams".to_vec()).to_string()
+        );
+        assert_eq!(
+            "TCP-KEEPALIVE: 60.0 secs",
+            EdnsOption::KEEPALIVE(Some(600)).to_string()
+        );
+        assert_eq!(
+            "CLIENT-SUBNET: 192.0.2.0/24/0",
+            EdnsOption::ECS {
+                source_prefix: 24,
+                scope_prefix: 0,
+                addr: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 0)),
+            }
+            .to_string()
+        );
+    }
+}
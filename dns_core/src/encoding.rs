@@ -0,0 +1,229 @@
+//! Text encodings used by presentation formats.
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE32HEX: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+/// Standard base64 (RFC 4648 section 4) with padding.
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &byte)| acc | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(bits >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes padded base64. Returns `None` on any character outside the
+/// alphabet or misplaced padding.
+pub fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+    if text.len() % 4 != 0 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    for (index, chunk) in text.chunks(4).enumerate() {
+        let last = index == text.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+
+        let mut bits = 0u32;
+        for (i, &c) in chunk[..4 - padding].iter().enumerate() {
+            let value = BASE64.iter().position(|&b| b == c)? as u32;
+            bits |= value << (18 - 6 * i);
+        }
+        out.extend_from_slice(&bits.to_be_bytes()[1..4 - padding]);
+    }
+    Some(out)
+}
+
+/// Lowercase hexadecimal.
+pub fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decodes hexadecimal of either case.
+pub fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    text.chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+/// Base32 with the extended hex alphabet (RFC 4648 section 7), without
+/// padding, as used for NSEC3 hashed owner names.
+pub fn base32hex_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut bits = 0u16;
+    let mut pending = 0;
+    for &byte in data {
+        bits = (bits << 8) | byte as u16;
+        pending += 8;
+        while pending >= 5 {
+            pending -= 5;
+            out.push(BASE32HEX[(bits >> pending) as usize & 0x1f] as char);
+        }
+    }
+    if pending > 0 {
+        out.push(BASE32HEX[(bits << (5 - pending)) as usize & 0x1f] as char);
+    }
+    out
+}
+
+/// Decodes unpadded base32hex of either case. Returns `None` for characters
+/// outside the alphabet or a length that cannot come from whole octets.
+pub fn base32hex_decode(text: &str) -> Option<Vec<u8>> {
+    if matches!(text.len() % 8, 1 | 3 | 6) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let mut bits = 0u16;
+    let mut pending = 0;
+    for c in text.bytes() {
+        let value = BASE32HEX
+            .iter()
+            .position(|&b| b == c.to_ascii_uppercase())?;
+        bits = (bits << 5) | value as u16;
+        pending += 5;
+        if pending >= 8 {
+            pending -= 8;
+            out.push((bits >> pending) as u8);
+        }
+    }
+    // Leftover bits are padding and must be zero.
+    if bits & ((1 << pending) - 1) != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+/// Escapes a <character-string> for presentation format (RFC 1035
+/// section 5.1) without surrounding quotes.
+pub(crate) fn escape_bytes(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len());
+    for &byte in data {
+        match byte {
+            b'"' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            0x20..=0x7E => out.push(byte as char),
+            _ => out.push_str(&format!("\\{byte:03}")),
+        }
+    }
+    out
+}
+
+/// Resolves `\X` and `\DDD` escapes. Returns `None` for a dangling
+/// backslash or a decimal escape above 255.
+pub(crate) fn unescape_bytes(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            out.push(byte);
+            continue;
+        }
+        let escaped = bytes.next()?;
+        if escaped.is_ascii_digit() {
+            let mut value = (escaped - b'0') as u16;
+            for _ in 0..2 {
+                let digit = bytes.next().filter(u8::is_ascii_digit)?;
+                value = value * 10 + (digit - b'0') as u16;
+            }
+            out.push(u8::try_from(value).ok()?);
+        } else {
+            out.push(escaped);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        base32hex_decode, base32hex_encode, base64_decode, base64_encode, escape_bytes, hex_decode,
+        hex_encode, unescape_bytes,
+    };
+
+    #[test]
+    fn base64_matches_rfc_4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encoded, base64_encode(plain.as_bytes()));
+            assert_eq!(Some(plain.as_bytes().to_vec()), base64_decode(encoded));
+        }
+
+        for invalid in ["Zg=", "Z===", "Zg==Zg==", "Zm9*"] {
+            assert_eq!(None, base64_decode(invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn base32hex_matches_rfc_4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "CO"),
+            ("fo", "CPNG"),
+            ("foo", "CPNMU"),
+            ("foob", "CPNMUOG"),
+            ("fooba", "CPNMUOJ1"),
+            ("foobar", "CPNMUOJ1E8"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encoded, base32hex_encode(plain.as_bytes()));
+            assert_eq!(Some(plain.as_bytes().to_vec()), base32hex_decode(encoded));
+        }
+        assert_eq!(Some(b"foob".to_vec()), base32hex_decode("cpnmuog"));
+
+        for invalid in ["C", "CPN", "CPNMUO", "CW", "CP"] {
+            assert_eq!(None, base32hex_decode(invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn hex_roundtrip() {
+        assert_eq!("00ff7a", hex_encode(&[0, 255, 0x7a]));
+        assert_eq!(Some(vec![0, 255, 0x7a]), hex_decode("00FF7a"));
+        assert_eq!(None, hex_decode("abc"));
+        assert_eq!(None, hex_decode("zz"));
+    }
+
+    #[test]
+    fn character_string_escapes() {
+        assert_eq!(r#"a\"b\\c\009"#, escape_bytes(b"a\"b\\c\t"));
+        assert_eq!(
+            Some(b"a\"b\\c\t".to_vec()),
+            unescape_bytes(r#"a\"b\\c\009"#)
+        );
+        assert_eq!(None, unescape_bytes("a\\"));
+        assert_eq!(None, unescape_bytes("\\256"));
+    }
+}
@@ -0,0 +1,346 @@
+use std::{fmt, io, path::PathBuf};
+
+use crate::types::Opcode;
+
+/// Message section a question or record belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Section {
+    Question,
+    Answer,
+    Authority,
+    Additional,
+}
+
+impl Section {
+    /// Name of the section under `opcode`. UPDATE messages (RFC 2136) call
+    /// the sections zone, prerequisite, update and additional.
+    pub fn name(&self, opcode: Opcode) -> &'static str {
+        match (opcode, self) {
+            (Opcode::UPDATE, Section::Question) => "zone",
+            (Opcode::UPDATE, Section::Answer) => "prerequisite",
+            (Opcode::UPDATE, Section::Authority) => "update",
+            (_, Section::Question) => "question",
+            (_, Section::Answer) => "answer",
+            (_, Section::Authority) => "authority",
+            (_, Section::Additional) => "additional",
+        }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name(Opcode::QUERY))
+    }
+}
+
+/// Failure while decoding wire-format data. Offsets are absolute positions
+/// in the message buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    EndOfBuffer {
+        offset: usize,
+    },
+    InvalidLabelType {
+        offset: usize,
+        byte: u8,
+    },
+    NameTooLong {
+        offset: usize,
+    },
+    CompressionLoop {
+        offset: usize,
+    },
+    RdataLengthMismatch {
+        offset: usize,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidRdata {
+        offset: usize,
+        reason: &'static str,
+    },
+    InSection {
+        section: Section,
+        index: usize,
+        source: Box<ParseError>,
+    },
+}
+
+impl ParseError {
+    pub fn offset(&self) -> usize {
+        match self {
+            ParseError::EndOfBuffer { offset }
+            | ParseError::InvalidLabelType { offset, .. }
+            | ParseError::NameTooLong { offset }
+            | ParseError::CompressionLoop { offset }
+            | ParseError::RdataLengthMismatch { offset, .. }
+            | ParseError::InvalidRdata { offset, .. } => *offset,
+            ParseError::InSection { source, .. } => source.offset(),
+        }
+    }
+
+    /// Section and index of the question or record that failed, if the
+    /// error was raised while reading a whole packet.
+    pub fn location(&self) -> Option<(Section, usize)> {
+        match self {
+            ParseError::InSection { section, index, .. } => Some((*section, *index)),
+            _ => None,
+        }
+    }
+
+    /// The underlying error with any section context stripped.
+    pub fn kind(&self) -> &ParseError {
+        match self {
+            ParseError::InSection { source, .. } => source.kind(),
+            other => other,
+        }
+    }
+
+    pub fn in_section(self, section: Section, index: usize) -> ParseError {
+        ParseError::InSection {
+            section,
+            index,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EndOfBuffer { offset } => {
+                write!(f, "end of buffer reached at offset {offset}")
+            }
+            ParseError::InvalidLabelType { offset, byte } => {
+                write!(f, "invalid label type 0x{byte:02x} at offset {offset}")
+            }
+            ParseError::NameTooLong { offset } => {
+                write!(f, "name at offset {offset} exceeds 255 bytes")
+            }
+            ParseError::CompressionLoop { offset } => {
+                write!(f, "name compression loop at offset {offset}")
+            }
+            ParseError::RdataLengthMismatch {
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "rdata at offset {offset} is {expected} bytes but {actual} were decoded"
+            ),
+            ParseError::InvalidRdata { offset, reason } => {
+                write!(f, "invalid rdata at offset {offset}: {reason}")
+            }
+            ParseError::InSection {
+                section,
+                index,
+                source,
+            } => write!(f, "{section} #{index}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Failure while encoding wire-format data. Offsets are the buffer position
+/// the write was attempted at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    BufferFull {
+        offset: usize,
+    },
+    RdataTooLong {
+        offset: usize,
+        len: usize,
+    },
+    InvalidOpcode {
+        offset: usize,
+        opcode: u8,
+    },
+    StringTooLong {
+        offset: usize,
+        len: usize,
+    },
+    InvalidRdata {
+        offset: usize,
+        reason: &'static str,
+    },
+    InSection {
+        section: Section,
+        index: usize,
+        source: Box<EncodeError>,
+    },
+}
+
+impl EncodeError {
+    pub fn offset(&self) -> usize {
+        match self {
+            EncodeError::BufferFull { offset }
+            | EncodeError::RdataTooLong { offset, .. }
+            | EncodeError::InvalidOpcode { offset, .. }
+            | EncodeError::StringTooLong { offset, .. }
+            | EncodeError::InvalidRdata { offset, .. } => *offset,
+            EncodeError::InSection { source, .. } => source.offset(),
+        }
+    }
+
+    pub fn location(&self) -> Option<(Section, usize)> {
+        match self {
+            EncodeError::InSection { section, index, .. } => Some((*section, *index)),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> &EncodeError {
+        match self {
+            EncodeError::InSection { source, .. } => source.kind(),
+            other => other,
+        }
+    }
+
+    pub fn in_section(self, section: Section, index: usize) -> EncodeError {
+        EncodeError::InSection {
+            section,
+            index,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::BufferFull { offset } => {
+                write!(f, "buffer full at offset {offset}")
+            }
+            EncodeError::RdataTooLong { offset, len } => {
+                write!(
+                    f,
+                    "rdata of {len} bytes at offset {offset} exceeds 65535 bytes"
+                )
+            }
+            EncodeError::InvalidOpcode { offset, opcode } => {
+                write!(
+                    f,
+                    "opcode {opcode} at offset {offset} does not fit in 4 bits"
+                )
+            }
+            EncodeError::StringTooLong { offset, len } => {
+                write!(
+                    f,
+                    "character string of {len} bytes at offset {offset} exceeds 255 bytes"
+                )
+            }
+            EncodeError::InvalidRdata { offset, reason } => {
+                write!(f, "invalid rdata at offset {offset}: {reason}")
+            }
+            EncodeError::InSection {
+                section,
+                index,
+                source,
+            } => write!(f, "{section} #{index}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Failure while building a domain name from labels or presentation format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NameError {
+    EmptyLabel,
+    LabelTooLong { len: usize },
+    NameTooLong { len: usize },
+    InvalidEscape,
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::EmptyLabel => write!(f, "empty label"),
+            NameError::LabelTooLong { len } => {
+                write!(f, "label of {len} bytes exceeds 63 bytes")
+            }
+            NameError::NameTooLong { len } => {
+                write!(f, "name of {len} bytes exceeds 255 bytes")
+            }
+            NameError::InvalidEscape => write!(f, "invalid escape sequence"),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+/// Failure while parsing presentation-format text such as mnemonics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PresentationError {
+    UnknownMnemonic { text: String },
+    Invalid { field: &'static str, text: String },
+    Missing { field: &'static str },
+}
+
+impl fmt::Display for PresentationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresentationError::UnknownMnemonic { text } => {
+                write!(f, "unknown mnemonic '{text}'")
+            }
+            PresentationError::Invalid { field, text } => {
+                write!(f, "invalid {field} '{text}'")
+            }
+            PresentationError::Missing { field } => write!(f, "missing {field}"),
+        }
+    }
+}
+
+impl std::error::Error for PresentationError {}
+
+/// Failure while loading a zone file. `line` and `column` are 1-based and
+/// refer to `file`, or to the text passed in when it is `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZoneError {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub kind: ZoneErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ZoneErrorKind {
+    Syntax(PresentationError),
+    /// The first record has no owner to inherit.
+    MissingOwner,
+    /// No TTL was given and neither `$TTL` nor an earlier record set one.
+    MissingTtl,
+    /// A file could not be read. For the top-level file the line and column
+    /// are 0.
+    Io {
+        path: PathBuf,
+        kind: io::ErrorKind,
+    },
+    IncludeTooDeep,
+    /// `$GENERATE` directives, counted across included files, would produce
+    /// more records than the loader allows.
+    TooManyGenerated,
+}
+
+impl fmt::Display for ZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ZoneErrorKind::Syntax(err) => write!(f, "{err}"),
+            ZoneErrorKind::MissingOwner => write!(f, "no previous owner to inherit"),
+            ZoneErrorKind::MissingTtl => write!(f, "no TTL given and no default set"),
+            ZoneErrorKind::Io { path, kind } => {
+                write!(f, "cannot read {}: {kind}", path.display())
+            }
+            ZoneErrorKind::IncludeTooDeep => write!(f, "$INCLUDE nested too deeply"),
+            ZoneErrorKind::TooManyGenerated => write!(f, "$GENERATE produces too many records"),
+        }
+    }
+}
+
+impl std::error::Error for ZoneError {}
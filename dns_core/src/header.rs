@@ -1,4 +1,10 @@
-use crate::{buffer::BytePacketBuffer, types::ResultCode};
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    error::{EncodeError, ParseError},
+    types::{Opcode, ResultCode},
+};
+
+pub const HEADER_LEN: usize = 12;
 
 #[derive(Clone, Debug)]
 pub struct DnsHeader {
@@ -7,10 +13,10 @@ pub struct DnsHeader {
     pub recursion_desired: bool,    // 1 bit
     pub truncated_message: bool,    // 1 bit
     pub authoritative_answer: bool, // 1 bit
-    pub opcode: u8,                 // 4 bits
+    pub opcode: Opcode,             // 4 bits
     pub response: bool,             // 1 bit
 
-    pub rescode: ResultCode,       // 4 bits
+    pub rescode: ResultCode,       // 4 bits, see DnsPacket::rescode
     pub checking_disabled: bool,   // 1 bit
     pub authed_data: bool,         // 1 bit
     pub z: bool,                   // 1 bit
@@ -36,7 +42,7 @@ impl DnsHeader {
             recursion_desired: false,
             truncated_message: false,
             authoritative_answer: false,
-            opcode: 0,
+            opcode: Opcode::QUERY,
             response: false,
 
             rescode: ResultCode::NOERROR,
@@ -61,48 +67,58 @@ impl DnsHeader {
     //      D                      E
     //      E
 
-    pub fn read(
-        &mut self,
-        buffer: &mut BytePacketBuffer,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.id = buffer.read_u16()?;
+    pub fn read<S: Storage>(&mut self, buffer: &mut BytePacketBuffer<S>) -> Result<(), ParseError> {
+        self.decode(buffer.read_bytes(HEADER_LEN)?);
+
+        Ok(())
+    }
+
+    // `bytes` must hold at least HEADER_LEN bytes.
+    pub(crate) fn decode(&mut self, bytes: &[u8]) {
+        let a = bytes[2];
+        let b = bytes[3];
 
-        let a = buffer.read()?;
-        let b = buffer.read()?;
+        self.id = u16::from_be_bytes([bytes[0], bytes[1]]);
 
         self.recursion_desired = (a & (1 << 0)) > 0;
         self.truncated_message = (a & (1 << 1)) > 0;
         self.authoritative_answer = (a & (1 << 2)) > 0;
-        self.opcode = (a >> 3) & 0x0f;
+        self.opcode = Opcode::from_num((a >> 3) & 0x0f);
         self.response = (a & (1 << 7)) > 0;
 
-        self.rescode = ResultCode::from_num(b & 0x0f);
+        self.rescode = ResultCode::from_num((b & 0x0f) as u16);
         self.checking_disabled = (b & (1 << 4)) > 0;
         self.authed_data = (b & (1 << 5)) > 0;
         self.z = (b & (1 << 6)) > 0;
         self.recursion_available = (b & (1 << 7)) > 0;
 
-        self.questions = buffer.read_u16()?;
-        self.answers = buffer.read_u16()?;
-        self.authoritative_entries = buffer.read_u16()?;
-        self.resource_entries = buffer.read_u16()?;
-
-        Ok(())
+        self.questions = u16::from_be_bytes([bytes[4], bytes[5]]);
+        self.answers = u16::from_be_bytes([bytes[6], bytes[7]]);
+        self.authoritative_entries = u16::from_be_bytes([bytes[8], bytes[9]]);
+        self.resource_entries = u16::from_be_bytes([bytes[10], bytes[11]]);
     }
 
-    pub fn write(&self, buffer: &mut BytePacketBuffer) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn write<S: Storage>(&self, buffer: &mut BytePacketBuffer<S>) -> Result<(), EncodeError> {
         buffer.write_u16(self.id)?;
 
+        let opcode = self.opcode.to_num();
+        if opcode > 0x0f {
+            return Err(EncodeError::InvalidOpcode {
+                offset: buffer.pos(),
+                opcode,
+            });
+        }
+
         buffer.write_u8(
             (self.recursion_desired as u8)
                 | ((self.truncated_message as u8) << 1)
                 | ((self.authoritative_answer as u8) << 2)
-                | (self.opcode << 3)
+                | (opcode << 3)
                 | ((self.response as u8) << 7),
         )?;
 
         buffer.write_u8(
-            (self.rescode as u8)
+            (self.rescode.to_num() & 0x0f) as u8
                 | ((self.recursion_available as u8) << 7)
                 | ((self.z as u8) << 6)
                 | ((self.authed_data as u8) << 5)
@@ -119,7 +135,11 @@ impl DnsHeader {
 #[cfg(test)]
 mod tests {
     use super::DnsHeader;
-    use crate::{buffer::BytePacketBuffer, types::ResultCode};
+    use crate::{
+        buffer::BytePacketBuffer,
+        error::EncodeError,
+        types::{Opcode, ResultCode},
+    };
 
     #[test]
     fn header_roundtrip_preserves_flags() {
@@ -128,7 +148,7 @@ mod tests {
         header.recursion_desired = true;
         header.truncated_message = true;
         header.authoritative_answer = true;
-        header.opcode = 5;
+        header.opcode = Opcode::UPDATE;
         header.response = true;
         header.rescode = ResultCode::SERVFAIL;
         header.checking_disabled = true;
@@ -142,7 +162,7 @@ mod tests {
 
         let mut buffer = BytePacketBuffer::new();
         header.write(&mut buffer).unwrap();
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
 
         let mut parsed = DnsHeader::new();
         parsed.read(&mut buffer).unwrap();
@@ -164,7 +184,31 @@ mod tests {
     }
 
     #[test]
-    fn unknown_result_codes_default_to_noerror() {
-        assert_eq!(ResultCode::NOERROR, ResultCode::from_num(42));
+    fn unassigned_result_codes_are_kept() {
+        let mut header = DnsHeader::new();
+        header.rescode = ResultCode::UNKNOWN(12);
+
+        let mut buffer = BytePacketBuffer::new();
+        header.write(&mut buffer).unwrap();
+        buffer.seek(0).unwrap();
+
+        let mut parsed = DnsHeader::new();
+        parsed.read(&mut buffer).unwrap();
+        assert_eq!(ResultCode::UNKNOWN(12), parsed.rescode);
+    }
+
+    #[test]
+    fn opcode_must_fit_in_four_bits() {
+        let mut header = DnsHeader::new();
+        header.opcode = Opcode::UNKNOWN(16);
+
+        let mut buffer = BytePacketBuffer::new();
+        assert_eq!(
+            Err(EncodeError::InvalidOpcode {
+                offset: 2,
+                opcode: 16
+            }),
+            header.write(&mut buffer)
+        );
     }
 }
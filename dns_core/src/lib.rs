@@ -1,6 +1,14 @@
 pub mod buffer;
+pub mod edns;
+pub mod encoding;
+pub mod error;
 pub mod header;
+pub mod name;
 pub mod packet;
+pub mod packet_ref;
+mod presentation;
 pub mod question;
+pub mod rdata;
 pub mod record;
 pub mod types;
+pub mod zone;
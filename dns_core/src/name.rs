@@ -0,0 +1,354 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::error::NameError;
+
+pub const MAX_LABEL_LEN: usize = 63;
+pub const MAX_NAME_LEN: usize = 255;
+
+/// An absolute domain name stored as uncompressed wire labels.
+///
+/// Comparison, hashing and ordering ignore ASCII case. Ordering follows the
+/// DNSSEC canonical order (RFC 4034 section 6.1): labels are compared from
+/// the root down.
+#[derive(Clone, Default)]
+pub struct Name {
+    // Length-prefixed labels without the terminating root label.
+    wire: Vec<u8>,
+}
+
+impl Name {
+    pub fn root() -> Name {
+        Name { wire: Vec::new() }
+    }
+
+    pub fn from_labels<I, L>(labels: I) -> Result<Name, NameError>
+    where
+        I: IntoIterator<Item = L>,
+        L: AsRef<[u8]>,
+    {
+        let mut name = Name::root();
+        for label in labels {
+            name.push_label(label.as_ref())?;
+        }
+        Ok(name)
+    }
+
+    /// Appends `label` below the root, i.e. as the new rightmost label.
+    pub fn push_label(&mut self, label: &[u8]) -> Result<(), NameError> {
+        if label.is_empty() {
+            return Err(NameError::EmptyLabel);
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(NameError::LabelTooLong { len: label.len() });
+        }
+        let len = self.wire_len() + label.len() + 1;
+        if len > MAX_NAME_LEN {
+            return Err(NameError::NameTooLong { len });
+        }
+
+        self.wire.push(label.len() as u8);
+        self.wire.extend_from_slice(label);
+        Ok(())
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.wire.is_empty()
+    }
+
+    /// Length of the uncompressed wire form, including the root label.
+    pub fn wire_len(&self) -> usize {
+        self.wire.len() + 1
+    }
+
+    pub fn label_count(&self) -> usize {
+        self.labels().count()
+    }
+
+    pub fn labels(&self) -> Labels<'_> {
+        Labels { wire: &self.wire }
+    }
+
+    /// The name with its leftmost label removed, or `None` for the root.
+    pub fn parent(&self) -> Option<Name> {
+        let first = *self.wire.first()? as usize;
+        Some(Name {
+            wire: self.wire[first + 1..].to_vec(),
+        })
+    }
+
+    /// True when `self` equals `other` or lies below it.
+    pub fn is_subdomain_of(&self, other: &Name) -> bool {
+        let skip = match self.label_count().checked_sub(other.label_count()) {
+            Some(skip) => skip,
+            None => return false,
+        };
+
+        let mut offset = 0;
+        for _ in 0..skip {
+            offset += self.wire[offset] as usize + 1;
+        }
+        self.wire[offset..].eq_ignore_ascii_case(&other.wire)
+    }
+
+    /// Copy of the name with every label folded to ASCII lowercase.
+    pub fn to_lowercase(&self) -> Name {
+        Name {
+            wire: self.wire.to_ascii_lowercase(),
+        }
+    }
+
+    /// Byte-for-byte comparison, e.g. for DNS 0x20 checks where the answer
+    /// must echo the exact case that was sent.
+    pub fn eq_exact(&self, other: &Name) -> bool {
+        self.wire == other.wire
+    }
+
+    /// Uncompressed wire form including the root label.
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut wire = self.wire.clone();
+        wire.push(0);
+        wire
+    }
+}
+
+pub struct Labels<'a> {
+    wire: &'a [u8],
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let (&len, rest) = self.wire.split_first()?;
+        let (label, rest) = rest.split_at(len as usize);
+        self.wire = rest;
+        Some(label)
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.wire.eq_ignore_ascii_case(&other.wire)
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Length bytes never exceed 63, so lowercasing leaves them untouched.
+        state.write_usize(self.wire.len());
+        for byte in &self.wire {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+    }
+}
+
+impl Ord for Name {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ours: Vec<&[u8]> = self.labels().collect();
+        let theirs: Vec<&[u8]> = other.labels().collect();
+
+        for (a, b) in ours.iter().rev().zip(theirs.iter().rev()) {
+            let a = a.iter().map(u8::to_ascii_lowercase);
+            let b = b.iter().map(u8::to_ascii_lowercase);
+            match a.cmp(b) {
+                Ordering::Equal => {}
+                unequal => return unequal,
+            }
+        }
+        ours.len().cmp(&theirs.len())
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+
+        for label in self.labels() {
+            fmt_label(f, label)?;
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes one label in presentation format, escaping special and
+/// non-printable bytes.
+pub(crate) fn fmt_label(f: &mut fmt::Formatter<'_>, label: &[u8]) -> fmt::Result {
+    for &byte in label {
+        match byte {
+            b'.' | b'\\' | b'"' | b'(' | b')' | b';' | b'@' | b'$' => {
+                write!(f, "\\{}", byte as char)?
+            }
+            0x21..=0x7E => write!(f, "{}", byte as char)?,
+            _ => write!(f, "\\{byte:03}")?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Name(\"{self}\")")
+    }
+}
+
+impl FromStr for Name {
+    type Err = NameError;
+
+    /// Parses presentation format. `\.`-style and `\DDD` escapes are honoured;
+    /// the trailing dot is optional and the result is always absolute.
+    fn from_str(s: &str) -> Result<Name, NameError> {
+        if s == "." {
+            return Ok(Name::root());
+        }
+
+        let mut name = Name::root();
+        let mut label = Vec::new();
+        let mut bytes = s.bytes();
+
+        while let Some(byte) = bytes.next() {
+            match byte {
+                b'.' => {
+                    name.push_label(&label)?;
+                    label.clear();
+                }
+                b'\\' => {
+                    let escaped = bytes.next().ok_or(NameError::InvalidEscape)?;
+                    if escaped.is_ascii_digit() {
+                        let mut value = (escaped - b'0') as u16;
+                        for _ in 0..2 {
+                            match bytes.next() {
+                                Some(digit) if digit.is_ascii_digit() => {
+                                    value = value * 10 + (digit - b'0') as u16;
+                                }
+                                _ => return Err(NameError::InvalidEscape),
+                            }
+                        }
+                        let value = u8::try_from(value).map_err(|_| NameError::InvalidEscape)?;
+                        label.push(value);
+                    } else {
+                        label.push(escaped);
+                    }
+                }
+                _ => label.push(byte),
+            }
+        }
+
+        if !label.is_empty() {
+            name.push_label(&label)?;
+        } else if name.is_root() {
+            return Err(NameError::EmptyLabel);
+        }
+
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Name;
+    use crate::error::NameError;
+
+    #[test]
+    fn presentation_roundtrip_with_escapes() {
+        let name: Name = r"a\.b.c\032d.\\\255.example".parse().unwrap();
+        let labels: Vec<&[u8]> = name.labels().collect();
+        assert_eq!(vec![&b"a.b"[..], b"c d", b"\\\xff", b"example"], labels);
+        assert_eq!(r"a\.b.c\032d.\\\255.example.", name.to_string());
+        assert_eq!(name, name.to_string().parse().unwrap());
+
+        assert_eq!(".", Name::root().to_string());
+        assert_eq!(Name::root(), ".".parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        assert_eq!(Err(NameError::EmptyLabel), "a..b".parse::<Name>());
+        assert_eq!(Err(NameError::EmptyLabel), "".parse::<Name>());
+        assert_eq!(
+            Err(NameError::LabelTooLong { len: 64 }),
+            "a".repeat(64).parse::<Name>()
+        );
+        let long = vec!["a".repeat(63); 4].join(".");
+        assert_eq!(
+            Err(NameError::NameTooLong { len: 257 }),
+            long.parse::<Name>()
+        );
+        assert_eq!(Err(NameError::InvalidEscape), r"a\25".parse::<Name>());
+        assert_eq!(Err(NameError::InvalidEscape), r"a\256".parse::<Name>());
+    }
+
+    #[test]
+    fn comparison_ignores_case_and_uses_canonical_order() {
+        use std::collections::HashSet;
+
+        let upper: Name = "WWW.Example.COM".parse().unwrap();
+        let lower: Name = "www.example.com.".parse().unwrap();
+        assert_eq!(upper, lower);
+        assert_eq!(1, HashSet::from([upper, lower]).len());
+
+        // RFC 4034 section 6.1 example ordering
+        let ordered: Vec<Name> = [
+            "example",
+            "a.example",
+            "yljkjljk.a.example",
+            "Z.a.example",
+            "zABC.a.EXAMPLE",
+            "z.example",
+            r"\001.z.example",
+            "*.z.example",
+            r"\200.z.example",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        let mut sorted = ordered.clone();
+        sorted.reverse();
+        sorted.sort();
+        assert_eq!(ordered, sorted);
+    }
+
+    #[test]
+    fn case_is_kept_until_normalized() {
+        let mixed: Name = "ExAmple.COM".parse().unwrap();
+        assert_eq!("ExAmple.COM.", mixed.to_string());
+
+        let lower = mixed.to_lowercase();
+        assert_eq!("example.com.", lower.to_string());
+        assert_eq!(mixed, lower);
+        assert!(!mixed.eq_exact(&lower));
+        assert!(lower.eq_exact(&"example.com".parse().unwrap()));
+    }
+
+    #[test]
+    fn hierarchy_helpers() {
+        let name: Name = "www.Example.com".parse().unwrap();
+        let zone: Name = "example.COM".parse().unwrap();
+
+        assert!(name.is_subdomain_of(&zone));
+        assert!(zone.is_subdomain_of(&zone));
+        assert!(!zone.is_subdomain_of(&name));
+        assert!(name.is_subdomain_of(&Name::root()));
+        assert!(!name.is_subdomain_of(&"ample.com".parse().unwrap()));
+
+        assert_eq!(Some(zone.clone()), name.parent());
+        assert_eq!(None, Name::root().parent());
+        assert_eq!(3, name.label_count());
+        assert_eq!(17, name.wire_len());
+    }
+}
@@ -1,6 +1,12 @@
 use crate::{
-    buffer::BytePacketBuffer, header::DnsHeader, question::DnsQuestion, record::DnsRecord,
-    types::QueryType,
+    buffer::{BytePacketBuffer, Storage},
+    edns::Edns,
+    error::{EncodeError, ParseError, Section},
+    header::DnsHeader,
+    name::Name,
+    question::DnsQuestion,
+    record::DnsRecord,
+    types::{QueryType, ResultCode},
 };
 
 #[derive(Clone, Debug)]
@@ -10,6 +16,8 @@ pub struct DnsPacket {
     pub answers: Vec<DnsRecord>,
     pub authorities: Vec<DnsRecord>,
     pub resources: Vec<DnsRecord>,
+    /// The OPT record, kept out of `resources`.
+    pub edns: Option<Edns>,
 }
 
 impl Default for DnsPacket {
@@ -26,60 +34,129 @@ impl DnsPacket {
             answers: Vec::new(),
             authorities: Vec::new(),
             resources: Vec::new(),
+            edns: None,
         }
     }
 
-    pub fn from_buffer(
-        buffer: &mut BytePacketBuffer,
-    ) -> Result<DnsPacket, Box<dyn std::error::Error>> {
+    pub fn from_buffer<S: Storage>(
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<DnsPacket, ParseError> {
         let mut p = DnsPacket::new();
 
         p.header.read(buffer)?;
 
-        for _ in 0..p.header.questions {
-            let mut question = DnsQuestion::new("".to_string(), QueryType::UNKNOWN(0));
-            question.read(buffer)?;
+        for i in 0..p.header.questions as usize {
+            let mut question = DnsQuestion::new(Name::root(), QueryType::UNKNOWN(0));
+            question
+                .read(buffer)
+                .map_err(|e| e.in_section(Section::Question, i))?;
             p.questions.push(question);
         }
 
-        for _ in 0..p.header.answers {
-            p.answers.push(DnsRecord::read(buffer)?);
+        for i in 0..p.header.answers as usize {
+            let rec = DnsRecord::read(buffer).map_err(|e| e.in_section(Section::Answer, i))?;
+            p.answers.push(rec);
         }
 
-        for _ in 0..p.header.authoritative_entries {
-            let rec = DnsRecord::read(buffer)?;
+        for i in 0..p.header.authoritative_entries as usize {
+            let rec = DnsRecord::read(buffer).map_err(|e| e.in_section(Section::Authority, i))?;
             p.authorities.push(rec);
         }
-        for _ in 0..p.header.resource_entries {
-            let rec = DnsRecord::read(buffer)?;
-            p.resources.push(rec);
+        for i in 0..p.header.resource_entries as usize {
+            let start = buffer.pos();
+            let rec = DnsRecord::read(buffer).map_err(|e| e.in_section(Section::Additional, i))?;
+            match rec {
+                DnsRecord::UNKNOWN {
+                    qtype: QueryType::OPT,
+                    ..
+                } if p.edns.is_none() => {
+                    buffer.seek(start)?;
+                    let edns =
+                        Edns::read(buffer).map_err(|e| e.in_section(Section::Additional, i))?;
+                    p.edns = Some(edns);
+                }
+                rec => p.resources.push(rec),
+            }
         }
 
         Ok(p)
     }
 
-    pub fn write(
+    /// Parses a complete message of any length.
+    pub fn from_bytes(bytes: &[u8]) -> Result<DnsPacket, ParseError> {
+        DnsPacket::from_buffer(&mut BytePacketBuffer::from_bytes(bytes))
+    }
+
+    /// Serialises the packet into a new vector of up to 65535 bytes.
+    pub fn to_bytes(&mut self) -> Result<Vec<u8>, EncodeError> {
+        let mut buffer = BytePacketBuffer::growable();
+        self.write(&mut buffer)?;
+        Ok(buffer.into_inner())
+    }
+
+    /// Name of `section` for this packet's opcode, e.g. "prerequisite" for
+    /// the answer section of an UPDATE.
+    pub fn section_name(&self, section: Section) -> &'static str {
+        section.name(self.header.opcode)
+    }
+
+    /// The 12-bit extended RCODE: the four header bits combined with the
+    /// upper eight bits carried in the OPT record TTL (RFC 6891).
+    pub fn rescode(&self) -> ResultCode {
+        let upper = self.edns.as_ref().map_or(0, |edns| edns.ext_rcode) as u16;
+        ResultCode::from_num((upper << 4) | (self.header.rescode.to_num() & 0x0f))
+    }
+
+    /// Splits `rescode` between the header and the OPT record, adding EDNS
+    /// when the code does not fit in four bits.
+    pub fn set_rescode(&mut self, rescode: ResultCode) {
+        let num = rescode.to_num() & 0x0fff;
+        self.header.rescode = ResultCode::from_num(num & 0x0f);
+
+        let upper = (num >> 4) as u8;
+        match &mut self.edns {
+            Some(edns) => edns.ext_rcode = upper,
+            None if upper != 0 => {
+                let mut edns = Edns::new();
+                edns.ext_rcode = upper;
+                self.edns = Some(edns);
+            }
+            None => {}
+        }
+    }
+
+    pub fn write<S: Storage>(
         &mut self,
-        buffer: &mut BytePacketBuffer,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<(), EncodeError> {
         self.header.questions = self.questions.len() as u16;
         self.header.answers = self.answers.len() as u16;
         self.header.authoritative_entries = self.authorities.len() as u16;
-        self.header.resource_entries = self.resources.len() as u16;
+        self.header.resource_entries = (self.resources.len() + self.edns.is_some() as usize) as u16;
 
+        buffer.clear_names();
         self.header.write(buffer)?;
 
-        for question in &self.questions {
-            question.write(buffer)?;
+        for (i, question) in self.questions.iter().enumerate() {
+            question
+                .write(buffer)
+                .map_err(|e| e.in_section(Section::Question, i))?;
         }
-        for rec in &self.answers {
-            rec.write(buffer)?;
+        for (i, rec) in self.answers.iter().enumerate() {
+            rec.write(buffer)
+                .map_err(|e| e.in_section(Section::Answer, i))?;
         }
-        for rec in &self.authorities {
-            rec.write(buffer)?;
+        for (i, rec) in self.authorities.iter().enumerate() {
+            rec.write(buffer)
+                .map_err(|e| e.in_section(Section::Authority, i))?;
         }
-        for rec in &self.resources {
-            rec.write(buffer)?;
+        for (i, rec) in self.resources.iter().enumerate() {
+            rec.write(buffer)
+                .map_err(|e| e.in_section(Section::Additional, i))?;
+        }
+        if let Some(edns) = &self.edns {
+            edns.write(buffer)
+                .map_err(|e| e.in_section(Section::Additional, self.resources.len()))?;
         }
 
         Ok(())
@@ -90,10 +167,12 @@ impl DnsPacket {
 mod tests {
     use super::DnsPacket;
     use crate::{
-        buffer::BytePacketBuffer,
+        buffer::{BytePacketBuffer, MAX_PACKET_SIZE},
+        edns::Edns,
+        error::{EncodeError, ParseError, Section},
         question::DnsQuestion,
         record::DnsRecord,
-        types::{QueryType, ResultCode},
+        types::{DnsClass, Opcode, QueryType, ResultCode},
     };
     use std::net::Ipv4Addr;
 
@@ -104,12 +183,13 @@ mod tests {
         packet.header.recursion_desired = true;
         packet.header.rescode = ResultCode::NOERROR;
 
-        packet
-            .questions
-            .push(DnsQuestion::new("example.org".into(), QueryType::A));
+        packet.questions.push(DnsQuestion::new(
+            "example.org".parse().unwrap(),
+            QueryType::A,
+        ));
         packet.answers.push(DnsRecord::A {
-            domain: "example.org".into(),
-            class: 1,
+            domain: "example.org".parse().unwrap(),
+            class: DnsClass::IN,
             ttl: 123,
             addr: Ipv4Addr::new(192, 0, 2, 123),
         });
@@ -123,11 +203,201 @@ mod tests {
         assert_eq!(0, packet.header.authoritative_entries);
         assert_eq!(0, packet.header.resource_entries);
 
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
         let parsed = DnsPacket::from_buffer(&mut buffer).unwrap();
 
         assert_eq!(packet.header.id, parsed.header.id);
         assert_eq!(packet.questions, parsed.questions);
         assert_eq!(packet.answers, parsed.answers);
     }
+
+    #[test]
+    fn parse_errors_report_section_and_index() {
+        let mut packet = DnsPacket::new();
+        packet.questions.push(DnsQuestion::new(
+            "example.org".parse().unwrap(),
+            QueryType::A,
+        ));
+        for last in 1..=2 {
+            packet.answers.push(DnsRecord::A {
+                domain: "example.org".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 60,
+                addr: Ipv4Addr::new(192, 0, 2, last),
+            });
+        }
+
+        let mut buffer = BytePacketBuffer::new();
+        packet.write(&mut buffer).unwrap();
+        let truncated_at = buffer.pos() - 2;
+        buffer.set_size(truncated_at);
+
+        buffer.seek(0).unwrap();
+        let err = DnsPacket::from_buffer(&mut buffer).unwrap_err();
+        assert_eq!(Some((Section::Answer, 1)), err.location());
+        assert_eq!(
+            &ParseError::EndOfBuffer {
+                offset: truncated_at
+            },
+            err.kind()
+        );
+    }
+
+    #[test]
+    fn write_compresses_repeated_owner_and_rdata_names() {
+        let mut packet = DnsPacket::new();
+        packet.questions.push(DnsQuestion::new(
+            "example.org".parse().unwrap(),
+            QueryType::A,
+        ));
+        for last in 0..10 {
+            packet.answers.push(DnsRecord::A {
+                domain: "example.org".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 60,
+                addr: Ipv4Addr::new(192, 0, 2, last),
+            });
+        }
+        packet.authorities.push(DnsRecord::NS {
+            domain: "example.org".parse().unwrap(),
+            class: DnsClass::IN,
+            host: "ns1.example.org".parse().unwrap(),
+            ttl: 60,
+        });
+        packet.authorities.push(DnsRecord::SOA {
+            domain: "example.org".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 60,
+            mname: "ns1.example.org".parse().unwrap(),
+            rname: "hostmaster.example.org".parse().unwrap(),
+            serial: 1,
+            refresh: 2,
+            retry: 3,
+            expire: 4,
+            minimum: 5,
+        });
+
+        let mut compressed = BytePacketBuffer::new();
+        packet.write(&mut compressed).unwrap();
+
+        let mut uncompressed = BytePacketBuffer::new();
+        uncompressed.set_compression(false);
+        packet.write(&mut uncompressed).unwrap();
+
+        // Every repeated "example.org" (13 bytes) collapses to a 2-byte pointer
+        assert_eq!(12 + 17 + 10 * 16 + 18 + 47, compressed.pos());
+        assert!(compressed.pos() < uncompressed.pos());
+
+        compressed.seek(0).unwrap();
+        let parsed = DnsPacket::from_buffer(&mut compressed).unwrap();
+        assert_eq!(packet.questions, parsed.questions);
+        assert_eq!(packet.answers, parsed.answers);
+        assert_eq!(packet.authorities, parsed.authorities);
+    }
+
+    #[test]
+    fn messages_larger_than_the_fixed_buffer_roundtrip_through_vec() {
+        let mut packet = DnsPacket::new();
+        packet.header.id = 0x6553;
+        for i in 0..40 {
+            packet.answers.push(DnsRecord::TXT {
+                domain: "big.example".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 60,
+                data: vec![format!("{i:0>200}").into_bytes()],
+            });
+        }
+
+        let bytes = packet.to_bytes().unwrap();
+        assert!(bytes.len() > MAX_PACKET_SIZE);
+        assert!(matches!(
+            packet.write(&mut BytePacketBuffer::new()),
+            Err(EncodeError::InSection { .. })
+        ));
+
+        let parsed = DnsPacket::from_bytes(&bytes).unwrap();
+        assert_eq!(packet.answers, parsed.answers);
+    }
+
+    #[test]
+    fn write_into_caller_provided_slice() {
+        let mut packet = DnsPacket::new();
+        packet.questions.push(DnsQuestion::new(
+            "example.org".parse().unwrap(),
+            QueryType::A,
+        ));
+
+        let mut storage = [0u8; 64];
+        let mut buffer = BytePacketBuffer::with_storage(&mut storage[..]);
+        packet.write(&mut buffer).unwrap();
+        let len = buffer.pos();
+        assert_eq!(29, len);
+
+        let parsed = DnsPacket::from_bytes(&storage[..len]).unwrap();
+        assert_eq!(packet.questions, parsed.questions);
+
+        let mut tiny = [0u8; 16];
+        let err = packet
+            .write(&mut BytePacketBuffer::with_storage(&mut tiny[..]))
+            .unwrap_err();
+        assert_eq!(Some((Section::Question, 0)), err.location());
+        assert_eq!(&EncodeError::BufferFull { offset: 13 }, err.kind());
+    }
+
+    #[test]
+    fn extended_rescode_uses_opt_ttl() {
+        let mut packet = DnsPacket::new();
+        packet.set_rescode(ResultCode::NXDOMAIN);
+        assert!(packet.edns.is_none());
+        assert_eq!(ResultCode::NXDOMAIN, packet.rescode());
+
+        packet.set_rescode(ResultCode::BADCOOKIE);
+        assert_eq!(ResultCode::YXRRSET, packet.header.rescode);
+        assert_eq!(Some(1), packet.edns.as_ref().map(|edns| edns.ext_rcode));
+
+        let parsed = DnsPacket::from_bytes(&packet.to_bytes().unwrap()).unwrap();
+        assert_eq!(ResultCode::BADCOOKIE, parsed.rescode());
+
+        packet.set_rescode(ResultCode::UNKNOWN(0xABC));
+        assert_eq!(ResultCode::UNKNOWN(0xABC), packet.rescode());
+        packet.set_rescode(ResultCode::REFUSED);
+        assert_eq!(ResultCode::REFUSED, packet.rescode());
+    }
+
+    #[test]
+    fn update_messages_rename_sections() {
+        let mut packet = DnsPacket::new();
+        assert_eq!("question", packet.section_name(Section::Question));
+        assert_eq!("answer", packet.section_name(Section::Answer));
+
+        packet.header.opcode = Opcode::UPDATE;
+        assert_eq!("zone", packet.section_name(Section::Question));
+        assert_eq!("prerequisite", packet.section_name(Section::Answer));
+        assert_eq!("update", packet.section_name(Section::Authority));
+        assert_eq!("additional", packet.section_name(Section::Additional));
+    }
+
+    #[test]
+    fn opt_record_is_lifted_into_edns() {
+        let mut packet = DnsPacket::new();
+        packet.questions.push(DnsQuestion::new(
+            "example.com".parse().unwrap(),
+            QueryType::A,
+        ));
+        let mut edns = Edns::new();
+        edns.payload_size = 4096;
+        edns.dnssec_ok = true;
+        packet.edns = Some(edns.clone());
+
+        let bytes = packet.to_bytes().unwrap();
+        assert_eq!(1, packet.header.resource_entries);
+
+        let parsed = DnsPacket::from_bytes(&bytes).unwrap();
+        assert!(parsed.resources.is_empty());
+        assert_eq!(Some(edns), parsed.edns);
+        assert_eq!(
+            bytes,
+            DnsPacket::from_bytes(&bytes).unwrap().to_bytes().unwrap()
+        );
+    }
 }
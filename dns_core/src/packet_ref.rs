@@ -0,0 +1,490 @@
+use std::fmt;
+
+use crate::{
+    buffer::{BytePacketBuffer, walk_name},
+    edns::Edns,
+    error::{ParseError, Section},
+    header::{DnsHeader, HEADER_LEN},
+    name::{Name, fmt_label},
+    packet::DnsPacket,
+    question::DnsQuestion,
+    record::DnsRecord,
+    types::{DnsClass, QueryType},
+};
+
+/// Borrowed view of a DNS message.
+///
+/// `parse` validates the message once up front, running the owned decoder
+/// over every record so it accepts exactly what `DnsPacket` does; the
+/// section iterators and the views they hand out then cannot fail and do
+/// not allocate. RDATA is exposed as raw slices; use
+/// `RecordRef::to_record` to decode it.
+#[derive(Clone, Debug)]
+pub struct DnsPacketRef<'a> {
+    message: &'a [u8],
+    header: DnsHeader,
+    // Start offsets of the question, answer, authority and additional
+    // sections.
+    sections: [usize; 4],
+}
+
+impl<'a> DnsPacketRef<'a> {
+    pub fn parse(message: &'a [u8]) -> Result<DnsPacketRef<'a>, ParseError> {
+        let bytes = message.get(..HEADER_LEN).ok_or(ParseError::EndOfBuffer {
+            offset: message.len(),
+        })?;
+        let mut header = DnsHeader::new();
+        header.decode(bytes);
+
+        let mut buffer = BytePacketBuffer::from_bytes(message);
+        let mut sections = [HEADER_LEN; 4];
+        let mut edns = false;
+        let mut pos = HEADER_LEN;
+
+        for i in 0..header.questions as usize {
+            pos = QuestionRef::parse(message, pos)
+                .map_err(|e| e.in_section(Section::Question, i))?
+                .1;
+        }
+
+        let counts = [
+            (Section::Answer, header.answers),
+            (Section::Authority, header.authoritative_entries),
+            (Section::Additional, header.resource_entries),
+        ];
+        for (slot, (section, count)) in counts.into_iter().enumerate() {
+            sections[slot + 1] = pos;
+            for i in 0..count as usize {
+                let end = RecordRef::parse(message, pos)
+                    .map_err(|e| e.in_section(section, i))?
+                    .1;
+                buffer.seek(pos)?;
+                let record = DnsRecord::read(&mut buffer).map_err(|e| e.in_section(section, i))?;
+
+                // The first OPT record is decoded as EDNS, as `DnsPacket` does.
+                let opt = matches!(
+                    record,
+                    DnsRecord::UNKNOWN {
+                        qtype: QueryType::OPT,
+                        ..
+                    }
+                );
+                if opt && section == Section::Additional && !edns {
+                    edns = true;
+                    buffer.seek(pos)?;
+                    Edns::read(&mut buffer).map_err(|e| e.in_section(section, i))?;
+                }
+                pos = end;
+            }
+        }
+
+        Ok(DnsPacketRef {
+            message,
+            header,
+            sections,
+        })
+    }
+
+    pub fn header(&self) -> &DnsHeader {
+        &self.header
+    }
+
+    pub fn message(&self) -> &'a [u8] {
+        self.message
+    }
+
+    pub fn questions(&self) -> Questions<'a> {
+        Questions {
+            message: self.message,
+            pos: self.sections[0],
+            remaining: self.header.questions,
+        }
+    }
+
+    pub fn answers(&self) -> Records<'a> {
+        self.records(1, self.header.answers)
+    }
+
+    pub fn authorities(&self) -> Records<'a> {
+        self.records(2, self.header.authoritative_entries)
+    }
+
+    pub fn resources(&self) -> Records<'a> {
+        self.records(3, self.header.resource_entries)
+    }
+
+    fn records(&self, section: usize, count: u16) -> Records<'a> {
+        Records {
+            message: self.message,
+            pos: self.sections[section],
+            remaining: count,
+        }
+    }
+
+    /// Decodes the whole message into an owned `DnsPacket`.
+    pub fn to_packet(&self) -> Result<DnsPacket, ParseError> {
+        DnsPacket::from_bytes(self.message)
+    }
+}
+
+pub struct Questions<'a> {
+    message: &'a [u8],
+    pos: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for Questions<'a> {
+    type Item = QuestionRef<'a>;
+
+    fn next(&mut self) -> Option<QuestionRef<'a>> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let (question, end) = QuestionRef::parse(self.message, self.pos).ok()?;
+        self.pos = end;
+        Some(question)
+    }
+}
+
+pub struct Records<'a> {
+    message: &'a [u8],
+    pos: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = RecordRef<'a>;
+
+    fn next(&mut self) -> Option<RecordRef<'a>> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let (record, end) = RecordRef::parse(self.message, self.pos).ok()?;
+        self.pos = end;
+        Some(record)
+    }
+}
+
+/// A validated, possibly compressed name inside a message.
+#[derive(Copy, Clone)]
+pub struct NameRef<'a> {
+    message: &'a [u8],
+    start: usize,
+}
+
+impl<'a> NameRef<'a> {
+    /// Validates the name at `start`, returning it with the position just
+    /// past its in-place encoding.
+    pub fn parse(message: &'a [u8], start: usize) -> Result<(NameRef<'a>, usize), ParseError> {
+        let end = walk_name(message, start, |_| Ok(()))?;
+        Ok((NameRef { message, start }, end))
+    }
+
+    pub fn offset(&self) -> usize {
+        self.start
+    }
+
+    pub fn labels(&self) -> LabelRefs<'a> {
+        LabelRefs {
+            message: self.message,
+            pos: self.start,
+        }
+    }
+
+    pub fn to_name(&self) -> Name {
+        let mut name = Name::root();
+        for label in self.labels() {
+            // Already validated, so pushing cannot exceed the limits
+            let _ = name.push_label(label);
+        }
+        name
+    }
+}
+
+impl PartialEq<Name> for NameRef<'_> {
+    fn eq(&self, other: &Name) -> bool {
+        let mut ours = self.labels();
+        let mut theirs = other.labels();
+        loop {
+            match (ours.next(), theirs.next()) {
+                (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => {}
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl fmt::Display for NameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut labels = self.labels().peekable();
+        if labels.peek().is_none() {
+            return f.write_str(".");
+        }
+        for label in labels {
+            fmt_label(f, label)?;
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for NameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NameRef(\"{self}\")")
+    }
+}
+
+/// Labels of a `NameRef`, following compression pointers.
+pub struct LabelRefs<'a> {
+    message: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for LabelRefs<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        loop {
+            let length_byte = *self.message.get(self.pos)?;
+            if length_byte & 0xC0 == 0xC0 {
+                let byte_2nd = *self.message.get(self.pos + 1)?;
+                self.pos = (((length_byte as usize) & 0x3F) << 8) | (byte_2nd as usize);
+                continue;
+            }
+            if length_byte == 0 {
+                return None;
+            }
+
+            let start = self.pos + 1;
+            self.pos = start + length_byte as usize;
+            return self.message.get(start..self.pos);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct QuestionRef<'a> {
+    pub name: NameRef<'a>,
+    pub qtype: QueryType,
+    pub qclass: DnsClass,
+}
+
+impl<'a> QuestionRef<'a> {
+    fn parse(message: &'a [u8], start: usize) -> Result<(QuestionRef<'a>, usize), ParseError> {
+        let (name, pos) = NameRef::parse(message, start)?;
+        let qtype = QueryType::from_num(read_u16(message, pos)?);
+        let qclass = DnsClass::from_num(read_u16(message, pos + 2)?);
+        Ok((
+            QuestionRef {
+                name,
+                qtype,
+                qclass,
+            },
+            pos + 4,
+        ))
+    }
+
+    pub fn to_question(&self) -> DnsQuestion {
+        DnsQuestion::with_class(self.name.to_name(), self.qtype, self.qclass)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct RecordRef<'a> {
+    message: &'a [u8],
+    start: usize,
+    pub name: NameRef<'a>,
+    pub qtype: QueryType,
+    pub class: DnsClass,
+    pub ttl: u32,
+    rdata_start: usize,
+    rdata_len: usize,
+}
+
+impl<'a> RecordRef<'a> {
+    fn parse(message: &'a [u8], start: usize) -> Result<(RecordRef<'a>, usize), ParseError> {
+        let (name, pos) = NameRef::parse(message, start)?;
+        let qtype = QueryType::from_num(read_u16(message, pos)?);
+        let class = DnsClass::from_num(read_u16(message, pos + 2)?);
+        let ttl = read_u32(message, pos + 4)?;
+        let rdata_len = read_u16(message, pos + 8)? as usize;
+
+        let rdata_start = pos + 10;
+        let end = rdata_start + rdata_len;
+        if end > message.len() {
+            return Err(ParseError::EndOfBuffer {
+                offset: message.len(),
+            });
+        }
+
+        let record = RecordRef {
+            message,
+            start,
+            name,
+            qtype,
+            class,
+            ttl,
+            rdata_start,
+            rdata_len,
+        };
+        Ok((record, end))
+    }
+
+    pub fn rdata(&self) -> &'a [u8] {
+        &self.message[self.rdata_start..self.rdata_start + self.rdata_len]
+    }
+
+    /// Absolute offset of the RDATA within the message.
+    pub fn rdata_offset(&self) -> usize {
+        self.rdata_start
+    }
+
+    /// A name embedded in the RDATA, `offset` bytes from its start. Names
+    /// may be compressed against any earlier part of the message.
+    pub fn rdata_name(&self, offset: usize) -> Result<NameRef<'a>, ParseError> {
+        if offset >= self.rdata_len {
+            return Err(ParseError::EndOfBuffer {
+                offset: self.rdata_start + offset,
+            });
+        }
+        Ok(NameRef::parse(self.message, self.rdata_start + offset)?.0)
+    }
+
+    /// Decodes the record into an owned `DnsRecord`.
+    pub fn to_record(&self) -> Result<DnsRecord, ParseError> {
+        let mut buffer = BytePacketBuffer::from_bytes(self.message);
+        buffer.seek(self.start)?;
+        DnsRecord::read(&mut buffer)
+    }
+}
+
+fn read_u16(message: &[u8], pos: usize) -> Result<u16, ParseError> {
+    match message.get(pos..pos + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err(ParseError::EndOfBuffer {
+            offset: message.len(),
+        }),
+    }
+}
+
+fn read_u32(message: &[u8], pos: usize) -> Result<u32, ParseError> {
+    Ok(((read_u16(message, pos)? as u32) << 16) | read_u16(message, pos + 2)? as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DnsPacketRef;
+    use crate::{
+        error::{ParseError, Section},
+        packet::DnsPacket,
+        question::DnsQuestion,
+        record::DnsRecord,
+        types::{DnsClass, QueryType},
+    };
+    use std::net::Ipv4Addr;
+
+    fn sample_message() -> Vec<u8> {
+        let mut packet = DnsPacket::new();
+        packet.header.id = 0x4242;
+        packet.questions.push(DnsQuestion::new(
+            "WWW.example.org".parse().unwrap(),
+            QueryType::A,
+        ));
+        packet.answers.push(DnsRecord::CNAME {
+            domain: "WWW.example.org".parse().unwrap(),
+            class: DnsClass::IN,
+            host: "web.example.org".parse().unwrap(),
+            ttl: 300,
+        });
+        packet.answers.push(DnsRecord::A {
+            domain: "web.example.org".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 60,
+            addr: Ipv4Addr::new(192, 0, 2, 7),
+        });
+        packet.authorities.push(DnsRecord::NS {
+            domain: "example.org".parse().unwrap(),
+            class: DnsClass::IN,
+            host: "ns.example.org".parse().unwrap(),
+            ttl: 3600,
+        });
+
+        packet.to_bytes().unwrap()
+    }
+
+    #[test]
+    fn views_match_owned_parse() {
+        let message = sample_message();
+        let view = DnsPacketRef::parse(&message).unwrap();
+        let owned = view.to_packet().unwrap();
+
+        assert_eq!(0x4242, view.header().id);
+
+        let questions: Vec<DnsQuestion> = view.questions().map(|q| q.to_question()).collect();
+        assert_eq!(owned.questions, questions);
+
+        let answers: Vec<DnsRecord> = view.answers().map(|r| r.to_record().unwrap()).collect();
+        assert_eq!(owned.answers, answers);
+        assert_eq!(1, view.authorities().count());
+        assert_eq!(0, view.resources().count());
+    }
+
+    #[test]
+    fn names_and_rdata_point_into_the_message() {
+        let message = sample_message();
+        let view = DnsPacketRef::parse(&message).unwrap();
+
+        let question = view.questions().next().unwrap();
+        assert_eq!("WWW.example.org.", question.name.to_string());
+        assert_eq!(question.name, "www.EXAMPLE.org".parse().unwrap());
+
+        let cname = view.answers().next().unwrap();
+        assert_eq!(QueryType::CNAME, cname.qtype);
+        assert_eq!(300, cname.ttl);
+        // "web" label plus a pointer to the question's "example.org"
+        assert_eq!(6, cname.rdata().len());
+        assert_eq!("web.example.org.", cname.rdata_name(0).unwrap().to_string());
+
+        let a = view.answers().nth(1).unwrap();
+        assert_eq!(&[192, 0, 2, 7], a.rdata());
+        assert_eq!(message.as_ptr_range(), view.message().as_ptr_range());
+    }
+
+    #[test]
+    fn truncated_messages_are_rejected_with_location() {
+        let message = sample_message();
+        let err = DnsPacketRef::parse(&message[..message.len() - 1]).unwrap_err();
+        assert_eq!(Some((Section::Authority, 0)), err.location());
+
+        assert!(matches!(
+            DnsPacketRef::parse(&message[..5]),
+            Err(ParseError::EndOfBuffer { offset: 5 })
+        ));
+    }
+
+    #[test]
+    fn rdata_the_owned_parser_rejects_is_rejected() {
+        let mut message = sample_message();
+        let view = DnsPacketRef::parse(&message).unwrap();
+        let a = view.answers().nth(1).unwrap().rdata_offset();
+
+        // Boundaries still line up, but an address needs four bytes.
+        message[a - 1] = 3;
+        message.remove(a + 3);
+
+        let err = DnsPacketRef::parse(&message).unwrap_err();
+        assert_eq!(Some((Section::Answer, 1)), err.location());
+        let owned = DnsPacket::from_bytes(&message);
+        assert_eq!(err, owned.unwrap_err());
+    }
+
+    #[test]
+    fn malformed_opt_records_are_rejected() {
+        // The only option claims more bytes than the RDATA holds.
+        let message = [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, // header, ARCOUNT 1
+            0, 0, 41, 0x10, 0, 0, 0, 0, 0, 0, 4, // root, OPT, 4096, TTL, RDLENGTH
+            0, 10, 0, 8, // COOKIE, length 8
+        ];
+        let err = DnsPacketRef::parse(&message).unwrap_err();
+        assert_eq!(Some((Section::Additional, 0)), err.location());
+        assert_eq!(err, DnsPacket::from_bytes(&message).unwrap_err());
+    }
+}
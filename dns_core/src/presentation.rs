@@ -0,0 +1,1902 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    encoding::{
+        base32hex_decode, base32hex_encode, base64_decode, base64_encode, escape_bytes, hex_decode,
+        hex_encode, unescape_bytes,
+    },
+    error::PresentationError,
+    name::Name,
+    rdata::{
+        apl::AplItem,
+        caa,
+        dnssec::TypeBitmap,
+        gateway::Gateway,
+        generic::{GenericRdata, parse_generic_data},
+        sshfp::{SshfpAlgorithm, SshfpFingerprintType},
+        tlsa::{TlsaMatchingType, TlsaSelector, TlsaUsage},
+    },
+    record::DnsRecord,
+    types::{DnsClass, QueryType},
+};
+
+/// One word of presentation text with the 1-based line and column where it
+/// starts. Quoted strings are kept without their quotes and, like bare
+/// words, with their escapes unresolved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub(crate) text: String,
+    pub(crate) quoted: bool,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+/// The words of one master-file entry: a line, or several when joined by
+/// parentheses.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Entry {
+    pub(crate) tokens: Vec<Token>,
+    /// The entry's line began with whitespace, so it has no owner field.
+    pub(crate) blank_owner: bool,
+}
+
+/// A lexing failure and where it happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LexError {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) error: PresentationError,
+}
+
+/// Splits master-file text into entries. `;` starts a comment and
+/// parentheses continue an entry across line breaks.
+pub(crate) struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(text: &'a str) -> Lexer<'a> {
+        Lexer {
+            text,
+            pos: 0,
+            line: 1,
+            line_start: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.line_start = self.pos;
+        }
+        Some(c)
+    }
+
+    fn column(&self) -> usize {
+        self.pos - self.line_start + 1
+    }
+
+    fn error(&self, line: usize, column: usize, error: PresentationError) -> LexError {
+        LexError {
+            line,
+            column,
+            error,
+        }
+    }
+
+    /// The next entry with at least one word, or `None` at the end of the
+    /// text.
+    pub(crate) fn next_entry(&mut self) -> Result<Option<Entry>, LexError> {
+        let mut entry = Entry::default();
+        // Position of each open parenthesis, for reporting one left open.
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        let mut at_line_start = self.pos == self.line_start;
+
+        loop {
+            if at_line_start && open.is_empty() {
+                entry.blank_owner = matches!(self.peek(), Some(' ' | '\t'));
+            }
+            at_line_start = false;
+
+            let (line, column) = (self.line, self.column());
+            let Some(c) = self.peek() else {
+                if let Some(&(line, column)) = open.last() {
+                    return Err(self.error(
+                        line,
+                        column,
+                        PresentationError::Missing {
+                            field: "closing parenthesis",
+                        },
+                    ));
+                }
+                return Ok((!entry.tokens.is_empty()).then_some(entry));
+            };
+
+            match c {
+                '\n' => {
+                    self.bump();
+                    at_line_start = true;
+                    if open.is_empty() && !entry.tokens.is_empty() {
+                        return Ok(Some(entry));
+                    }
+                }
+                ';' => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                '(' => {
+                    self.bump();
+                    open.push((line, column));
+                }
+                ')' => {
+                    self.bump();
+                    if open.pop().is_none() {
+                        return Err(self.error(
+                            line,
+                            column,
+                            PresentationError::Invalid {
+                                field: "parenthesis",
+                                text: ")".to_string(),
+                            },
+                        ));
+                    }
+                }
+                c if c.is_whitespace() => {
+                    self.bump();
+                }
+                _ => {
+                    let token = self.word(line, column)?;
+                    entry.tokens.push(token);
+                }
+            }
+        }
+    }
+
+    // Reads a quoted string or a bare word. A quote inside a word, as in
+    // `alpn="h2,h3"`, runs to its closing quote even across whitespace.
+    fn word(&mut self, line: usize, column: usize) -> Result<Token, LexError> {
+        let start = self.pos;
+        let quoted = self.peek() == Some('"');
+        if quoted {
+            self.bump();
+        }
+
+        let mut text = String::new();
+        let mut in_quotes = quoted;
+        loop {
+            match self.peek() {
+                None | Some('\n') if in_quotes => {
+                    return Err(self.error(
+                        line,
+                        column,
+                        PresentationError::Invalid {
+                            field: "quoted string",
+                            text: self.text[start..self.pos].to_string(),
+                        },
+                    ));
+                }
+                None => break,
+                Some('"') if quoted => {
+                    self.bump();
+                    break;
+                }
+                Some(c) if !in_quotes && (c.is_whitespace() || matches!(c, ';' | '(' | ')')) => {
+                    break;
+                }
+                Some(c) => {
+                    self.bump();
+                    text.push(c);
+                    match c {
+                        '\\' => {
+                            if let Some(escaped) = self.peek().filter(|&c| c != '\n') {
+                                self.bump();
+                                text.push(escaped);
+                            }
+                        }
+                        '"' => in_quotes = !in_quotes,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(Token {
+            text,
+            quoted,
+            line,
+            column,
+        })
+    }
+}
+
+/// Splits the text of a single entry into words.
+pub(crate) fn tokenize(text: &str) -> Result<Vec<Token>, PresentationError> {
+    let mut lexer = Lexer::new(text);
+    let entry = lexer.next_entry().map_err(|err| err.error)?;
+    if let Some(extra) = lexer.next_entry().map_err(|err| err.error)? {
+        return Err(PresentationError::Invalid {
+            field: "trailing data",
+            text: extra.tokens[0].text.clone(),
+        });
+    }
+    Ok(entry.map(|entry| entry.tokens).unwrap_or_default())
+}
+
+/// Parses a possibly relative domain name. With an origin, `@` stands for
+/// the origin and names without a trailing dot are relative to it; without
+/// one every name is taken as absolute.
+pub(crate) fn parse_name(
+    text: &str,
+    field: &'static str,
+    origin: Option<&Name>,
+) -> Result<Name, PresentationError> {
+    let invalid = |_| PresentationError::Invalid {
+        field,
+        text: text.to_string(),
+    };
+
+    match origin {
+        Some(origin) if text == "@" => Ok(origin.clone()),
+        Some(origin) if !is_absolute(text) => {
+            let relative: Name = text.parse().map_err(invalid)?;
+            Name::from_labels(relative.labels().chain(origin.labels())).map_err(invalid)
+        }
+        _ => text.parse().map_err(invalid),
+    }
+}
+
+// True when the name ends in an unescaped dot.
+fn is_absolute(text: &str) -> bool {
+    let Some(rest) = text.strip_suffix('.') else {
+        return false;
+    };
+    let backslashes = rest.bytes().rev().take_while(|&b| b == b'\\').count();
+    backslashes % 2 == 0
+}
+
+/// A cursor over the words of one entry.
+pub(crate) struct Tokens<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(tokens: &'a [Token]) -> Tokens<'a> {
+        Tokens { tokens, pos: 0 }
+    }
+
+    /// Index of the next word.
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    pub(crate) fn next(&mut self, field: &'static str) -> Result<&'a Token, PresentationError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or(PresentationError::Missing { field })?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn remaining(&mut self) -> &'a [Token] {
+        let rest = &self.tokens[self.pos..];
+        self.pos = self.tokens.len();
+        rest
+    }
+
+    fn parse<T: FromStr>(&mut self, field: &'static str) -> Result<T, PresentationError> {
+        let token = self.next(field)?;
+        token.text.parse().map_err(|_| invalid(field, &token.text))
+    }
+
+    fn ttl(&mut self, field: &'static str) -> Result<u32, PresentationError> {
+        let token = self.next(field)?;
+        parse_ttl(&token.text).ok_or_else(|| invalid(field, &token.text))
+    }
+
+    pub(crate) fn name(
+        &mut self,
+        field: &'static str,
+        origin: Option<&Name>,
+    ) -> Result<Name, PresentationError> {
+        parse_name(&self.next(field)?.text, field, origin)
+    }
+
+    /// A character-string: at most 255 bytes once escapes are resolved.
+    fn character_string(&mut self, field: &'static str) -> Result<Vec<u8>, PresentationError> {
+        let token = self.next(field)?;
+        unescape_bytes(&token.text)
+            .filter(|bytes| bytes.len() <= 255)
+            .ok_or_else(|| invalid(field, &token.text))
+    }
+
+    /// The remaining words joined, for base64 and hex that may be split
+    /// across several.
+    fn joined(&mut self) -> String {
+        self.remaining()
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect()
+    }
+
+    fn base64(&mut self, field: &'static str) -> Result<Vec<u8>, PresentationError> {
+        let text = self.joined();
+        base64_decode(&text).ok_or_else(|| invalid(field, &text))
+    }
+
+    fn hex(&mut self, field: &'static str) -> Result<Vec<u8>, PresentationError> {
+        let text = self.joined();
+        hex_decode(&text).ok_or_else(|| invalid(field, &text))
+    }
+
+    /// A blob preceded by its size, where a size of zero has no word.
+    fn sized_base64(&mut self, field: &'static str) -> Result<Vec<u8>, PresentationError> {
+        let size: u16 = self.parse(field)?;
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+        let token = self.next(field)?;
+        base64_decode(&token.text)
+            .filter(|data| data.len() == size as usize)
+            .ok_or_else(|| invalid(field, &token.text))
+    }
+
+    fn type_bitmap(&mut self) -> Result<TypeBitmap, PresentationError> {
+        self.remaining()
+            .iter()
+            .map(|token| token.text.parse::<QueryType>())
+            .collect()
+    }
+
+    /// Fails if any words are left over.
+    pub(crate) fn finish(&self) -> Result<(), PresentationError> {
+        match self.peek() {
+            Some(token) => Err(invalid("trailing data", &token.text)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn invalid(field: &'static str, text: &str) -> PresentationError {
+    PresentationError::Invalid {
+        field,
+        text: text.to_string(),
+    }
+}
+
+/// Parses the RDATA of a record of type `qtype`, either in the type's own
+/// format or in the RFC 3597 generic form.
+pub(crate) fn parse_rdata(
+    domain: Name,
+    class: DnsClass,
+    ttl: u32,
+    qtype: QueryType,
+    t: &mut Tokens,
+    origin: Option<&Name>,
+) -> Result<DnsRecord, PresentationError> {
+    if t.peek()
+        .is_some_and(|token| !token.quoted && token.text == "\\#")
+    {
+        let rest = t.remaining();
+        let text = rest
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let data = parse_generic_data(rest.iter().map(|token| token.text.as_str()), &text)?;
+        return GenericRdata { qtype, data }
+            .to_record(domain, class, ttl)
+            .map_err(|_| invalid("generic RDATA", &text));
+    }
+
+    let record = match qtype {
+        QueryType::A => DnsRecord::A {
+            domain,
+            class,
+            ttl,
+            addr: t.parse("IPv4 address")?,
+        },
+        QueryType::NS => DnsRecord::NS {
+            domain,
+            class,
+            ttl,
+            host: t.name("NS host", origin)?,
+        },
+        QueryType::CNAME => DnsRecord::CNAME {
+            domain,
+            class,
+            ttl,
+            host: t.name("CNAME target", origin)?,
+        },
+        QueryType::MX => DnsRecord::MX {
+            domain,
+            class,
+            ttl,
+            priority: t.parse("MX preference")?,
+            host: t.name("MX exchange", origin)?,
+        },
+        QueryType::TXT => DnsRecord::TXT {
+            domain,
+            class,
+            ttl,
+            data: strings(t)?,
+        },
+        QueryType::SOA => DnsRecord::SOA {
+            domain,
+            class,
+            ttl,
+            mname: t.name("SOA MNAME", origin)?,
+            rname: t.name("SOA RNAME", origin)?,
+            serial: t.parse("SOA serial")?,
+            refresh: t.ttl("SOA refresh")?,
+            retry: t.ttl("SOA retry")?,
+            expire: t.ttl("SOA expire")?,
+            minimum: t.ttl("SOA minimum")?,
+        },
+        QueryType::PTR => DnsRecord::PTR {
+            domain,
+            class,
+            ttl,
+            host: t.name("PTR target", origin)?,
+        },
+        QueryType::HINFO => DnsRecord::HINFO {
+            domain,
+            class,
+            ttl,
+            cpu: t.character_string("HINFO CPU")?,
+            os: t.character_string("HINFO OS")?,
+        },
+        QueryType::MINFO => DnsRecord::MINFO {
+            domain,
+            class,
+            ttl,
+            rmailbx: t.name("MINFO RMAILBX", origin)?,
+            emailbx: t.name("MINFO EMAILBX", origin)?,
+        },
+        QueryType::RP => DnsRecord::RP {
+            domain,
+            class,
+            ttl,
+            mbox: t.name("RP mailbox", origin)?,
+            txt: t.name("RP TXT name", origin)?,
+        },
+        QueryType::AFSDB => DnsRecord::AFSDB {
+            domain,
+            class,
+            ttl,
+            subtype: t.parse("AFSDB subtype")?,
+            host: t.name("AFSDB host", origin)?,
+        },
+        QueryType::X25 => DnsRecord::X25 {
+            domain,
+            class,
+            ttl,
+            address: t.character_string("X25 address")?,
+        },
+        QueryType::ISDN => DnsRecord::ISDN {
+            domain,
+            class,
+            ttl,
+            address: t.character_string("ISDN address")?,
+            subaddress: match t.peek() {
+                Some(_) => Some(t.character_string("ISDN subaddress")?),
+                None => None,
+            },
+        },
+        QueryType::RT => DnsRecord::RT {
+            domain,
+            class,
+            ttl,
+            preference: t.parse("RT preference")?,
+            host: t.name("RT host", origin)?,
+        },
+        QueryType::NSAP => {
+            let token = t.next("NSAP address")?;
+            let address = token
+                .text
+                .get(..2)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("0x"))
+                .and_then(|_| hex_decode(&token.text[2..].replace('.', "")))
+                .ok_or_else(|| invalid("NSAP address", &token.text))?;
+            DnsRecord::NSAP {
+                domain,
+                class,
+                ttl,
+                address,
+            }
+        }
+        QueryType::NsapPtr => DnsRecord::NsapPtr {
+            domain,
+            class,
+            ttl,
+            host: t.name("NSAP-PTR target", origin)?,
+        },
+        QueryType::SIG | QueryType::RRSIG => {
+            let type_covered = t.parse("type covered")?;
+            let algorithm = t.parse("algorithm")?;
+            let labels = t.parse("labels")?;
+            let original_ttl = t.parse("original TTL")?;
+            let expiration = t.parse("signature expiration")?;
+            let inception = t.parse("signature inception")?;
+            let key_tag = t.parse("key tag")?;
+            let signer_name = t.name("signer name", origin)?;
+            let signature = t.base64("signature")?;
+            if qtype == QueryType::SIG {
+                DnsRecord::SIG {
+                    domain,
+                    class,
+                    ttl,
+                    type_covered,
+                    algorithm,
+                    labels,
+                    original_ttl,
+                    expiration,
+                    inception,
+                    key_tag,
+                    signer_name,
+                    signature,
+                }
+            } else {
+                DnsRecord::RRSIG {
+                    domain,
+                    class,
+                    ttl,
+                    type_covered,
+                    algorithm,
+                    labels,
+                    original_ttl,
+                    expiration,
+                    inception,
+                    key_tag,
+                    signer_name,
+                    signature,
+                }
+            }
+        }
+        QueryType::KEY | QueryType::DNSKEY | QueryType::CDNSKEY => {
+            let flags = t.parse("key flags")?;
+            let protocol = t.parse("key protocol")?;
+            let algorithm = t.parse("algorithm")?;
+            let public_key = t.base64("public key")?;
+            match qtype {
+                QueryType::KEY => DnsRecord::KEY {
+                    domain,
+                    class,
+                    ttl,
+                    flags,
+                    protocol,
+                    algorithm,
+                    public_key,
+                },
+                QueryType::DNSKEY => DnsRecord::DNSKEY {
+                    domain,
+                    class,
+                    ttl,
+                    flags,
+                    protocol,
+                    algorithm,
+                    public_key,
+                },
+                _ => DnsRecord::CDNSKEY {
+                    domain,
+                    class,
+                    ttl,
+                    flags,
+                    protocol,
+                    algorithm,
+                    public_key,
+                },
+            }
+        }
+        QueryType::PX => DnsRecord::PX {
+            domain,
+            class,
+            ttl,
+            preference: t.parse("PX preference")?,
+            map822: t.name("PX MAP822", origin)?,
+            mapx400: t.name("PX MAPX400", origin)?,
+        },
+        QueryType::AAAA => DnsRecord::AAAA {
+            domain,
+            class,
+            ttl,
+            addr: t.parse("IPv6 address")?,
+        },
+        QueryType::LOC => {
+            let text = t
+                .remaining()
+                .iter()
+                .map(|token| token.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            DnsRecord::LOC {
+                domain,
+                class,
+                ttl,
+                location: text.parse()?,
+            }
+        }
+        QueryType::SRV => DnsRecord::SRV {
+            domain,
+            class,
+            ttl,
+            priority: t.parse("SRV priority")?,
+            weight: t.parse("SRV weight")?,
+            port: t.parse("SRV port")?,
+            target: t.name("SRV target", origin)?,
+        },
+        QueryType::NAPTR => DnsRecord::NAPTR {
+            domain,
+            class,
+            ttl,
+            order: t.parse("NAPTR order")?,
+            preference: t.parse("NAPTR preference")?,
+            flags: t.character_string("NAPTR flags")?,
+            services: t.character_string("NAPTR services")?,
+            regexp: t.character_string("NAPTR regexp")?,
+            replacement: t.name("NAPTR replacement", origin)?,
+        },
+        QueryType::KX => DnsRecord::KX {
+            domain,
+            class,
+            ttl,
+            preference: t.parse("KX preference")?,
+            exchanger: t.name("KX exchanger", origin)?,
+        },
+        QueryType::CERT => DnsRecord::CERT {
+            domain,
+            class,
+            ttl,
+            cert_type: t.parse("certificate type")?,
+            key_tag: t.parse("key tag")?,
+            algorithm: t.parse("algorithm")?,
+            certificate: t.base64("certificate")?,
+        },
+        QueryType::DNAME => DnsRecord::DNAME {
+            domain,
+            class,
+            ttl,
+            host: t.name("DNAME target", origin)?,
+        },
+        QueryType::APL => DnsRecord::APL {
+            domain,
+            class,
+            ttl,
+            items: t
+                .remaining()
+                .iter()
+                .map(|token| token.text.parse::<AplItem>())
+                .collect::<Result<_, _>>()?,
+        },
+        QueryType::DS | QueryType::CDS | QueryType::TA | QueryType::DLV => {
+            let key_tag = t.parse("key tag")?;
+            let algorithm = t.parse("algorithm")?;
+            let digest_type = t.parse("digest type")?;
+            let digest = t.hex("digest")?;
+            match qtype {
+                QueryType::DS => DnsRecord::DS {
+                    domain,
+                    class,
+                    ttl,
+                    key_tag,
+                    algorithm,
+                    digest_type,
+                    digest,
+                },
+                QueryType::CDS => DnsRecord::CDS {
+                    domain,
+                    class,
+                    ttl,
+                    key_tag,
+                    algorithm,
+                    digest_type,
+                    digest,
+                },
+                QueryType::TA => DnsRecord::TA {
+                    domain,
+                    class,
+                    ttl,
+                    key_tag,
+                    algorithm,
+                    digest_type,
+                    digest,
+                },
+                _ => DnsRecord::DLV {
+                    domain,
+                    class,
+                    ttl,
+                    key_tag,
+                    algorithm,
+                    digest_type,
+                    digest,
+                },
+            }
+        }
+        QueryType::SSHFP => DnsRecord::SSHFP {
+            domain,
+            class,
+            ttl,
+            algorithm: SshfpAlgorithm::from_num(t.parse("SSHFP algorithm")?),
+            fingerprint_type: SshfpFingerprintType::from_num(t.parse("SSHFP fingerprint type")?),
+            fingerprint: t.hex("SSHFP fingerprint")?,
+        },
+        QueryType::IPSECKEY => {
+            let precedence = t.parse("IPSECKEY precedence")?;
+            let gateway_type = t.parse("IPSECKEY gateway type")?;
+            let algorithm = t.parse("IPSECKEY algorithm")?;
+            DnsRecord::IPSECKEY {
+                domain,
+                class,
+                ttl,
+                precedence,
+                algorithm,
+                gateway: gateway(t, gateway_type, origin)?,
+                public_key: t.base64("IPSECKEY public key")?,
+            }
+        }
+        QueryType::NSEC => DnsRecord::NSEC {
+            domain,
+            class,
+            ttl,
+            next_domain: t.name("NSEC next domain", origin)?,
+            types: t.type_bitmap()?,
+        },
+        QueryType::DHCID => DnsRecord::DHCID {
+            domain,
+            class,
+            ttl,
+            data: t.base64("DHCID data")?,
+        },
+        QueryType::NSEC3 => DnsRecord::NSEC3 {
+            domain,
+            class,
+            ttl,
+            hash_algorithm: t.parse("NSEC3 hash algorithm")?,
+            flags: t.parse("NSEC3 flags")?,
+            iterations: t.parse("NSEC3 iterations")?,
+            salt: salt(t)?,
+            next_hashed_owner: {
+                let token = t.next("NSEC3 next hashed owner")?;
+                base32hex_decode(&token.text)
+                    .ok_or_else(|| invalid("NSEC3 next hashed owner", &token.text))?
+            },
+            types: t.type_bitmap()?,
+        },
+        QueryType::NSEC3PARAM => DnsRecord::NSEC3PARAM {
+            domain,
+            class,
+            ttl,
+            hash_algorithm: t.parse("NSEC3 hash algorithm")?,
+            flags: t.parse("NSEC3 flags")?,
+            iterations: t.parse("NSEC3 iterations")?,
+            salt: salt(t)?,
+        },
+        QueryType::TLSA | QueryType::SMIMEA => {
+            let usage = TlsaUsage::from_num(t.parse("certificate usage")?);
+            let selector = TlsaSelector::from_num(t.parse("selector")?);
+            let matching_type = TlsaMatchingType::from_num(t.parse("matching type")?);
+            let association_data = t.hex("certificate association data")?;
+            if qtype == QueryType::TLSA {
+                DnsRecord::TLSA {
+                    domain,
+                    class,
+                    ttl,
+                    usage,
+                    selector,
+                    matching_type,
+                    association_data,
+                }
+            } else {
+                DnsRecord::SMIMEA {
+                    domain,
+                    class,
+                    ttl,
+                    usage,
+                    selector,
+                    matching_type,
+                    association_data,
+                }
+            }
+        }
+        QueryType::HIP => {
+            let pk_algorithm = t.parse("HIP public key algorithm")?;
+            let hit = t.next("HIP HIT")?;
+            let hit = hex_decode(&hit.text).ok_or_else(|| invalid("HIP HIT", &hit.text))?;
+            let public_key = t.next("HIP public key")?;
+            let public_key = base64_decode(&public_key.text)
+                .ok_or_else(|| invalid("HIP public key", &public_key.text))?;
+            let mut rendezvous_servers = Vec::new();
+            while t.peek().is_some() {
+                rendezvous_servers.push(t.name("HIP rendezvous server", origin)?);
+            }
+            DnsRecord::HIP {
+                domain,
+                class,
+                ttl,
+                pk_algorithm,
+                hit,
+                public_key,
+                rendezvous_servers,
+            }
+        }
+        QueryType::OPENPGPKEY => DnsRecord::OPENPGPKEY {
+            domain,
+            class,
+            ttl,
+            public_key: t.base64("OpenPGP key")?,
+        },
+        QueryType::CSYNC => DnsRecord::CSYNC {
+            domain,
+            class,
+            ttl,
+            serial: t.parse("CSYNC serial")?,
+            flags: t.parse("CSYNC flags")?,
+            types: t.type_bitmap()?,
+        },
+        QueryType::ZONEMD => DnsRecord::ZONEMD {
+            domain,
+            class,
+            ttl,
+            serial: t.parse("ZONEMD serial")?,
+            scheme: t.parse("ZONEMD scheme")?,
+            hash_algorithm: t.parse("ZONEMD hash algorithm")?,
+            digest: t.hex("ZONEMD digest")?,
+        },
+        QueryType::SVCB | QueryType::HTTPS => {
+            let priority = t.parse("SvcPriority")?;
+            let target = t.name("TargetName", origin)?;
+            let params = t
+                .remaining()
+                .iter()
+                .map(|token| match token.quoted {
+                    true => format!("\"{}\"", token.text),
+                    false => token.text.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+                .parse()?;
+            if qtype == QueryType::SVCB {
+                DnsRecord::SVCB {
+                    domain,
+                    class,
+                    ttl,
+                    priority,
+                    target,
+                    params,
+                }
+            } else {
+                DnsRecord::HTTPS {
+                    domain,
+                    class,
+                    ttl,
+                    priority,
+                    target,
+                    params,
+                }
+            }
+        }
+        QueryType::SPF => DnsRecord::SPF {
+            domain,
+            class,
+            ttl,
+            data: strings(t)?,
+        },
+        QueryType::NID => DnsRecord::NID {
+            domain,
+            class,
+            ttl,
+            preference: t.parse("NID preference")?,
+            node_id: identifier(t, "NID node ID")?,
+        },
+        QueryType::L32 => DnsRecord::L32 {
+            domain,
+            class,
+            ttl,
+            preference: t.parse("L32 preference")?,
+            locator: t.parse("L32 locator")?,
+        },
+        QueryType::L64 => DnsRecord::L64 {
+            domain,
+            class,
+            ttl,
+            preference: t.parse("L64 preference")?,
+            locator: identifier(t, "L64 locator")?,
+        },
+        QueryType::LP => DnsRecord::LP {
+            domain,
+            class,
+            ttl,
+            preference: t.parse("LP preference")?,
+            fqdn: t.name("LP FQDN", origin)?,
+        },
+        QueryType::EUI48 => DnsRecord::EUI48 {
+            domain,
+            class,
+            ttl,
+            address: eui(t, "EUI48 address")?,
+        },
+        QueryType::EUI64 => DnsRecord::EUI64 {
+            domain,
+            class,
+            ttl,
+            address: eui(t, "EUI64 address")?,
+        },
+        QueryType::TKEY => DnsRecord::TKEY {
+            domain,
+            class,
+            ttl,
+            algorithm: t.name("TKEY algorithm", origin)?,
+            inception: t.parse("TKEY inception")?,
+            expiration: t.parse("TKEY expiration")?,
+            mode: t.parse("TKEY mode")?,
+            error: t.parse("TKEY error")?,
+            key: t.sized_base64("TKEY key")?,
+            other: t.sized_base64("TKEY other data")?,
+        },
+        QueryType::TSIG => DnsRecord::TSIG {
+            domain,
+            class,
+            ttl,
+            algorithm: t.name("TSIG algorithm", origin)?,
+            time_signed: {
+                let token = t.next("TSIG time signed")?;
+                token
+                    .text
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|time| time >> 48 == 0)
+                    .ok_or_else(|| invalid("TSIG time signed", &token.text))?
+            },
+            fudge: t.parse("TSIG fudge")?,
+            mac: t.sized_base64("TSIG MAC")?,
+            original_id: t.parse("TSIG original ID")?,
+            error: t.parse("TSIG error")?,
+            other: t.sized_base64("TSIG other data")?,
+        },
+        QueryType::URI => DnsRecord::URI {
+            domain,
+            class,
+            ttl,
+            priority: t.parse("URI priority")?,
+            weight: t.parse("URI weight")?,
+            target: {
+                let token = t.next("URI target")?;
+                unescape_bytes(&token.text).ok_or_else(|| invalid("URI target", &token.text))?
+            },
+        },
+        QueryType::CAA => DnsRecord::CAA {
+            domain,
+            class,
+            ttl,
+            flags: t.parse("CAA flags")?,
+            tag: {
+                let token = t.next("CAA tag")?;
+                if !caa::is_valid_tag(token.text.as_bytes()) {
+                    return Err(invalid("CAA tag", &token.text));
+                }
+                token.text.clone()
+            },
+            value: {
+                let token = t.next("CAA value")?;
+                unescape_bytes(&token.text).ok_or_else(|| invalid("CAA value", &token.text))?
+            },
+        },
+        QueryType::AVC => DnsRecord::AVC {
+            domain,
+            class,
+            ttl,
+            data: strings(t)?,
+        },
+        QueryType::DOA => DnsRecord::DOA {
+            domain,
+            class,
+            ttl,
+            enterprise: t.parse("DOA enterprise")?,
+            doa_type: t.parse("DOA type")?,
+            location: t.parse("DOA location")?,
+            media_type: t.character_string("DOA media type")?,
+            data: match t.peek() {
+                Some(token) if token.text == "-" => {
+                    t.remaining();
+                    Vec::new()
+                }
+                _ => t.base64("DOA data")?,
+            },
+        },
+        QueryType::AMTRELAY => {
+            let precedence = t.parse("AMTRELAY precedence")?;
+            let discovery = t.next("AMTRELAY discovery optional")?;
+            let discovery_optional = match discovery.text.as_str() {
+                "0" => false,
+                "1" => true,
+                _ => return Err(invalid("AMTRELAY discovery optional", &discovery.text)),
+            };
+            let relay_type = t.parse("AMTRELAY type")?;
+            DnsRecord::AMTRELAY {
+                domain,
+                class,
+                ttl,
+                precedence,
+                discovery_optional,
+                relay: gateway(t, relay_type, origin)?,
+            }
+        }
+        _ => {
+            let text = t.joined();
+            return Err(invalid("generic RDATA", &text));
+        }
+    };
+    Ok(record)
+}
+
+fn strings(t: &mut Tokens) -> Result<Vec<Vec<u8>>, PresentationError> {
+    let mut data = Vec::new();
+    while t.peek().is_some() {
+        data.push(t.character_string("character string")?);
+    }
+    Ok(data)
+}
+
+fn salt(t: &mut Tokens) -> Result<Vec<u8>, PresentationError> {
+    let token = t.next("NSEC3 salt")?;
+    if token.text == "-" {
+        return Ok(Vec::new());
+    }
+    hex_decode(&token.text).ok_or_else(|| invalid("NSEC3 salt", &token.text))
+}
+
+fn gateway(
+    t: &mut Tokens,
+    type_num: u8,
+    origin: Option<&Name>,
+) -> Result<Gateway, PresentationError> {
+    let token = t.next("gateway")?;
+    let gateway = match type_num {
+        0 if token.text == "." => Gateway::None,
+        1 => Gateway::Ipv4(
+            token
+                .text
+                .parse()
+                .map_err(|_| invalid("gateway", &token.text))?,
+        ),
+        2 => Gateway::Ipv6(
+            token
+                .text
+                .parse()
+                .map_err(|_| invalid("gateway", &token.text))?,
+        ),
+        3 => Gateway::Name(parse_name(&token.text, "gateway", origin)?),
+        _ => return Err(invalid("gateway", &token.text)),
+    };
+    Ok(gateway)
+}
+
+/// A 64-bit ILNP value written as four groups of hex digits (RFC 6742).
+fn identifier(t: &mut Tokens, field: &'static str) -> Result<u64, PresentationError> {
+    let token = t.next(field)?;
+    let groups: Vec<&str> = token.text.split(':').collect();
+    if groups.len() != 4 {
+        return Err(invalid(field, &token.text));
+    }
+    groups.iter().try_fold(0u64, |value, group| {
+        if group.is_empty() || group.len() > 4 {
+            return Err(invalid(field, &token.text));
+        }
+        let group = u16::from_str_radix(group, 16).map_err(|_| invalid(field, &token.text))?;
+        Ok(value << 16 | group as u64)
+    })
+}
+
+/// An EUI-48 or EUI-64 address written as hyphen-separated hex pairs.
+fn eui<const N: usize>(t: &mut Tokens, field: &'static str) -> Result<[u8; N], PresentationError> {
+    let token = t.next(field)?;
+    let groups: Vec<&str> = token.text.split('-').collect();
+    let mut address = [0u8; N];
+    if groups.len() != N {
+        return Err(invalid(field, &token.text));
+    }
+    for (byte, group) in address.iter_mut().zip(groups) {
+        *byte = hex_decode(group)
+            .filter(|decoded| decoded.len() == 1)
+            .ok_or_else(|| invalid(field, &token.text))?[0];
+    }
+    Ok(address)
+}
+
+/// Reads the TTL and class, both optional and in either order, and the
+/// type that follow a record's owner.
+pub(crate) fn parse_ttl_class_type(
+    t: &mut Tokens,
+) -> Result<(Option<u32>, Option<DnsClass>, QueryType), PresentationError> {
+    let mut ttl = None;
+    let mut class = None;
+    loop {
+        let token = t.next("type")?;
+        if ttl.is_none() && token.text.starts_with(|c: char| c.is_ascii_digit()) {
+            ttl = Some(parse_ttl(&token.text).ok_or_else(|| invalid("TTL", &token.text))?);
+        } else if let (None, Ok(parsed)) = (class, token.text.parse::<DnsClass>()) {
+            class = Some(parsed);
+        } else {
+            return Ok((ttl, class, token.text.parse()?));
+        }
+    }
+}
+
+/// Parses a TTL given in seconds or with units, as in `1h30m`. The units
+/// are `w`, `d`, `h`, `m` and `s` in either case.
+pub(crate) fn parse_ttl(text: &str) -> Option<u32> {
+    if !text.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut total = 0u32;
+    let mut value = None;
+    for c in text.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            value = Some(value.unwrap_or(0u32).checked_mul(10)?.checked_add(digit)?);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            'w' => 604_800,
+            'd' => 86_400,
+            'h' => 3_600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        total = total.checked_add(value.take()?.checked_mul(unit)?)?;
+    }
+    // A trailing number without a unit counts seconds.
+    total.checked_add(value.unwrap_or(0))
+}
+
+impl FromStr for DnsRecord {
+    type Err = PresentationError;
+
+    /// Parses one record in master-file syntax: the owner, then the TTL and
+    /// class in either order, the type and the RDATA. The class defaults to
+    /// IN; the TTL must be given. Names are taken as absolute.
+    fn from_str(s: &str) -> Result<DnsRecord, PresentationError> {
+        let tokens = tokenize(s)?;
+        let mut t = Tokens::new(&tokens);
+        let domain = t.name("owner", None)?;
+
+        let (ttl, class, qtype) = parse_ttl_class_type(&mut t)?;
+        let ttl = ttl.ok_or(PresentationError::Missing { field: "TTL" })?;
+
+        let record = parse_rdata(
+            domain,
+            class.unwrap_or(DnsClass::IN),
+            ttl,
+            qtype,
+            &mut t,
+            None,
+        )?;
+        t.finish()?;
+        Ok(record)
+    }
+}
+
+impl fmt::Display for DnsRecord {
+    /// Formats the record as one master-file line: owner, TTL, class, type
+    /// and RDATA separated by tabs. Records kept as opaque data use the
+    /// RFC 3597 generic form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (domain, class, ttl) = self.owner();
+        write!(f, "{domain}\t{ttl}\t{class}\t{}\t", self.qtype())?;
+
+        match self {
+            DnsRecord::A { addr, .. } => write!(f, "{addr}"),
+            DnsRecord::AAAA { addr, .. } => write!(f, "{addr}"),
+            DnsRecord::NS { host, .. }
+            | DnsRecord::CNAME { host, .. }
+            | DnsRecord::PTR { host, .. }
+            | DnsRecord::DNAME { host, .. }
+            | DnsRecord::NsapPtr { host, .. } => write!(f, "{host}"),
+            DnsRecord::MX { priority, host, .. } => write!(f, "{priority} {host}"),
+            DnsRecord::TXT { data, .. }
+            | DnsRecord::SPF { data, .. }
+            | DnsRecord::AVC { data, .. } => write_strings(f, data.iter().map(Vec::as_slice)),
+            DnsRecord::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+                ..
+            } => write!(
+                f,
+                "{mname} {rname} {serial} {refresh} {retry} {expire} {minimum}"
+            ),
+            DnsRecord::HINFO { cpu, os, .. } => {
+                write_quoted(f, cpu)?;
+                f.write_str(" ")?;
+                write_quoted(f, os)
+            }
+            DnsRecord::MINFO {
+                rmailbx, emailbx, ..
+            } => write!(f, "{rmailbx} {emailbx}"),
+            DnsRecord::RP { mbox, txt, .. } => write!(f, "{mbox} {txt}"),
+            DnsRecord::AFSDB { subtype, host, .. } => write!(f, "{subtype} {host}"),
+            DnsRecord::X25 { address, .. } => write_quoted(f, address),
+            DnsRecord::ISDN {
+                address,
+                subaddress,
+                ..
+            } => {
+                write_quoted(f, address)?;
+                if let Some(subaddress) = subaddress {
+                    f.write_str(" ")?;
+                    write_quoted(f, subaddress)?;
+                }
+                Ok(())
+            }
+            DnsRecord::RT {
+                preference, host, ..
+            } => write!(f, "{preference} {host}"),
+            DnsRecord::NSAP { address, .. } => write!(f, "0x{}", hex_encode(address)),
+            DnsRecord::SIG {
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer_name,
+                signature,
+                ..
+            }
+            | DnsRecord::RRSIG {
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer_name,
+                signature,
+                ..
+            } => write!(
+                f,
+                "{type_covered} {algorithm} {labels} {original_ttl} {expiration} {inception} {key_tag} {signer_name} {}",
+                base64_encode(signature)
+            ),
+            DnsRecord::KEY {
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+                ..
+            }
+            | DnsRecord::DNSKEY {
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+                ..
+            }
+            | DnsRecord::CDNSKEY {
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+                ..
+            } => write!(
+                f,
+                "{flags} {protocol} {algorithm} {}",
+                base64_encode(public_key)
+            ),
+            DnsRecord::PX {
+                preference,
+                map822,
+                mapx400,
+                ..
+            } => write!(f, "{preference} {map822} {mapx400}"),
+            DnsRecord::LOC { location, .. } => write!(f, "{location}"),
+            DnsRecord::SRV {
+                priority,
+                weight,
+                port,
+                target,
+                ..
+            } => write!(f, "{priority} {weight} {port} {target}"),
+            DnsRecord::NAPTR {
+                order,
+                preference,
+                flags,
+                services,
+                regexp,
+                replacement,
+                ..
+            } => {
+                write!(f, "{order} {preference} ")?;
+                write_quoted(f, flags)?;
+                f.write_str(" ")?;
+                write_quoted(f, services)?;
+                f.write_str(" ")?;
+                write_quoted(f, regexp)?;
+                write!(f, " {replacement}")
+            }
+            DnsRecord::KX {
+                preference,
+                exchanger,
+                ..
+            } => write!(f, "{preference} {exchanger}"),
+            DnsRecord::CERT {
+                cert_type,
+                key_tag,
+                algorithm,
+                certificate,
+                ..
+            } => write!(
+                f,
+                "{cert_type} {key_tag} {algorithm} {}",
+                base64_encode(certificate)
+            ),
+            DnsRecord::APL { items, .. } => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                Ok(())
+            }
+            DnsRecord::DS {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+                ..
+            }
+            | DnsRecord::CDS {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+                ..
+            }
+            | DnsRecord::TA {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+                ..
+            }
+            | DnsRecord::DLV {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+                ..
+            } => write!(
+                f,
+                "{key_tag} {algorithm} {digest_type} {}",
+                hex_upper(digest)
+            ),
+            DnsRecord::SSHFP {
+                algorithm,
+                fingerprint_type,
+                fingerprint,
+                ..
+            } => write!(
+                f,
+                "{} {} {}",
+                algorithm.to_num(),
+                fingerprint_type.to_num(),
+                hex_upper(fingerprint)
+            ),
+            DnsRecord::IPSECKEY {
+                precedence,
+                algorithm,
+                gateway,
+                public_key,
+                ..
+            } => {
+                write!(
+                    f,
+                    "{precedence} {} {algorithm} {gateway}",
+                    gateway.type_num()
+                )?;
+                if !public_key.is_empty() {
+                    write!(f, " {}", base64_encode(public_key))?;
+                }
+                Ok(())
+            }
+            DnsRecord::NSEC {
+                next_domain, types, ..
+            } => {
+                write!(f, "{next_domain}")?;
+                write_types(f, types)
+            }
+            DnsRecord::DHCID { data, .. } => f.write_str(&base64_encode(data)),
+            DnsRecord::NSEC3 {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+                next_hashed_owner,
+                types,
+                ..
+            } => {
+                write!(
+                    f,
+                    "{hash_algorithm} {flags} {iterations} {} {}",
+                    salt_text(salt),
+                    base32hex_encode(next_hashed_owner)
+                )?;
+                write_types(f, types)
+            }
+            DnsRecord::NSEC3PARAM {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+                ..
+            } => write!(
+                f,
+                "{hash_algorithm} {flags} {iterations} {}",
+                salt_text(salt)
+            ),
+            DnsRecord::TLSA {
+                usage,
+                selector,
+                matching_type,
+                association_data,
+                ..
+            }
+            | DnsRecord::SMIMEA {
+                usage,
+                selector,
+                matching_type,
+                association_data,
+                ..
+            } => write!(
+                f,
+                "{} {} {} {}",
+                usage.to_num(),
+                selector.to_num(),
+                matching_type.to_num(),
+                hex_upper(association_data)
+            ),
+            DnsRecord::HIP {
+                pk_algorithm,
+                hit,
+                public_key,
+                rendezvous_servers,
+                ..
+            } => {
+                write!(
+                    f,
+                    "{pk_algorithm} {} {}",
+                    hex_upper(hit),
+                    base64_encode(public_key)
+                )?;
+                for server in rendezvous_servers {
+                    write!(f, " {server}")?;
+                }
+                Ok(())
+            }
+            DnsRecord::OPENPGPKEY { public_key, .. } => f.write_str(&base64_encode(public_key)),
+            DnsRecord::CSYNC {
+                serial,
+                flags,
+                types,
+                ..
+            } => {
+                write!(f, "{serial} {flags}")?;
+                write_types(f, types)
+            }
+            DnsRecord::ZONEMD {
+                serial,
+                scheme,
+                hash_algorithm,
+                digest,
+                ..
+            } => write!(
+                f,
+                "{serial} {scheme} {hash_algorithm} {}",
+                hex_upper(digest)
+            ),
+            DnsRecord::SVCB {
+                priority,
+                target,
+                params,
+                ..
+            }
+            | DnsRecord::HTTPS {
+                priority,
+                target,
+                params,
+                ..
+            } => {
+                write!(f, "{priority} {target}")?;
+                if !params.is_empty() {
+                    write!(f, " {params}")?;
+                }
+                Ok(())
+            }
+            DnsRecord::NID {
+                preference,
+                node_id,
+                ..
+            } => {
+                write!(f, "{preference} ")?;
+                write_identifier(f, *node_id)
+            }
+            DnsRecord::L32 {
+                preference,
+                locator,
+                ..
+            } => write!(f, "{preference} {locator}"),
+            DnsRecord::L64 {
+                preference,
+                locator,
+                ..
+            } => {
+                write!(f, "{preference} ")?;
+                write_identifier(f, *locator)
+            }
+            DnsRecord::LP {
+                preference, fqdn, ..
+            } => write!(f, "{preference} {fqdn}"),
+            DnsRecord::EUI48 { address, .. } => write_eui(f, address),
+            DnsRecord::EUI64 { address, .. } => write_eui(f, address),
+            DnsRecord::TKEY {
+                algorithm,
+                inception,
+                expiration,
+                mode,
+                error,
+                key,
+                other,
+                ..
+            } => {
+                write!(f, "{algorithm} {inception} {expiration} {mode} {error} ")?;
+                write_sized(f, key)?;
+                f.write_str(" ")?;
+                write_sized(f, other)
+            }
+            DnsRecord::TSIG {
+                algorithm,
+                time_signed,
+                fudge,
+                mac,
+                original_id,
+                error,
+                other,
+                ..
+            } => {
+                write!(f, "{algorithm} {time_signed} {fudge} ")?;
+                write_sized(f, mac)?;
+                write!(f, " {original_id} {error} ")?;
+                write_sized(f, other)
+            }
+            DnsRecord::URI {
+                priority,
+                weight,
+                target,
+                ..
+            } => {
+                write!(f, "{priority} {weight} ")?;
+                write_quoted(f, target)
+            }
+            DnsRecord::CAA {
+                flags, tag, value, ..
+            } => {
+                write!(f, "{flags} {tag} ")?;
+                write_quoted(f, value)
+            }
+            DnsRecord::DOA {
+                enterprise,
+                doa_type,
+                location,
+                media_type,
+                data,
+                ..
+            } => {
+                write!(f, "{enterprise} {doa_type} {location} ")?;
+                write_quoted(f, media_type)?;
+                if data.is_empty() {
+                    f.write_str(" -")
+                } else {
+                    write!(f, " {}", base64_encode(data))
+                }
+            }
+            DnsRecord::AMTRELAY {
+                precedence,
+                discovery_optional,
+                relay,
+                ..
+            } => write!(
+                f,
+                "{precedence} {} {} {relay}",
+                *discovery_optional as u8,
+                relay.type_num()
+            ),
+            DnsRecord::UNKNOWN { data, .. } => {
+                write!(f, "\\# {}", data.len())?;
+                if !data.is_empty() {
+                    write!(f, " {}", hex_encode(data))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn write_quoted(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    write!(f, "\"{}\"", escape_bytes(bytes))
+}
+
+fn write_strings<'a>(
+    f: &mut fmt::Formatter<'_>,
+    strings: impl Iterator<Item = &'a [u8]>,
+) -> fmt::Result {
+    for (i, bytes) in strings.enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        write_quoted(f, bytes)?;
+    }
+    Ok(())
+}
+
+fn write_types(f: &mut fmt::Formatter<'_>, types: &TypeBitmap) -> fmt::Result {
+    for qtype in types.iter() {
+        write!(f, " {qtype}")?;
+    }
+    Ok(())
+}
+
+fn write_identifier(f: &mut fmt::Formatter<'_>, value: u64) -> fmt::Result {
+    let groups: Vec<String> = value.to_be_bytes().chunks(2).map(hex_encode).collect();
+    f.write_str(&groups.join(":"))
+}
+
+fn write_eui(f: &mut fmt::Formatter<'_>, address: &[u8]) -> fmt::Result {
+    let groups: Vec<String> = address.iter().map(|byte| format!("{byte:02x}")).collect();
+    f.write_str(&groups.join("-"))
+}
+
+// A size followed by base64 data, which is omitted when empty.
+fn write_sized(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+    write!(f, "{}", data.len())?;
+    if !data.is_empty() {
+        write!(f, " {}", base64_encode(data))?;
+    }
+    Ok(())
+}
+
+fn hex_upper(data: &[u8]) -> String {
+    hex_encode(data).to_uppercase()
+}
+
+fn salt_text(salt: &[u8]) -> String {
+    if salt.is_empty() {
+        "-".to_string()
+    } else {
+        hex_encode(salt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Token, parse_ttl, tokenize};
+    use crate::{
+        error::PresentationError, rdata::generic::GenericRdata, record::DnsRecord, types::DnsClass,
+        types::QueryType,
+    };
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn tokenizer_handles_quotes_comments_and_parentheses() {
+        let tokens = tokenize(r#"a ( "b c" d\ e "f\"g" ) k="v w"; comment"#).unwrap();
+        let texts: Vec<(&str, bool)> = tokens
+            .iter()
+            .map(|Token { text, quoted, .. }| (text.as_str(), *quoted))
+            .collect();
+        assert_eq!(
+            vec![
+                ("a", false),
+                ("b c", true),
+                (r"d\ e", false),
+                (r#"f\"g"#, true),
+                (r#"k="v w""#, false),
+            ],
+            texts
+        );
+        assert!(tokenize(r#"a "b"#).is_err());
+    }
+
+    #[test]
+    fn ttls_accept_units() {
+        assert_eq!(Some(90), parse_ttl("90"));
+        assert_eq!(Some(5400), parse_ttl("1h30m"));
+        assert_eq!(Some(5400), parse_ttl("1H30M"));
+        assert_eq!(Some(604_800 + 86_400 + 1), parse_ttl("1w1d1"));
+        assert_eq!(Some(u32::MAX), parse_ttl("4294967295"));
+        for bad in ["", "h", "1x", "1hh", "+5", "4294967296", "7102w"] {
+            assert_eq!(None, parse_ttl(bad), "{bad}");
+        }
+    }
+
+    #[test]
+    fn every_type_roundtrips_through_presentation() {
+        let lines = [
+            "example.com.\t300\tIN\tA\t192.0.2.1",
+            "example.com.\t300\tIN\tNS\tns1.example.com.",
+            "www.example.com.\t300\tIN\tCNAME\texample.com.",
+            "example.com.\t300\tIN\tMX\t10 mail.example.com.",
+            "example.com.\t300\tIN\tTXT\t\"v=spf1 -all\" \"a \\\"quoted\\\" \\\\ word\\009\"",
+            "example.com.\t300\tIN\tTXT\t",
+            "example.com.\t300\tIN\tTXT\t\"\\255\\128\" \"\\195(\"",
+            "example.com.\t300\tIN\tSOA\tns1.example.com. hostmaster.example.com. 2024010101 7200 3600 1209600 300",
+            "1.2.0.192.in-addr.arpa.\t300\tIN\tPTR\thost.example.com.",
+            "example.com.\t300\tIN\tHINFO\t\"PDP-11/73\" \"UNIX\"",
+            "example.com.\t300\tIN\tMINFO\towner.example.com. errors.example.com.",
+            "example.com.\t300\tIN\tRP\tadmin.example.com. .",
+            "example.com.\t300\tIN\tAFSDB\t1 afs.example.com.",
+            "example.com.\t300\tIN\tX25\t\"311061700956\"",
+            "example.com.\t300\tIN\tISDN\t\"150862028003217\" \"004\"",
+            "example.com.\t300\tIN\tISDN\t\"150862028003217\"",
+            "example.com.\t300\tIN\tISDN\t\"\\128\" \"\\255\"",
+            "example.com.\t300\tIN\tHINFO\t\"\\195(\" \"\\254\"",
+            "example.com.\t300\tIN\tRT\t10 relay.example.com.",
+            "example.com.\t300\tIN\tNSAP\t0x47000580005a0000000001e133ffffff00016100",
+            "example.com.\t300\tIN\tNSAP-PTR\thost.example.com.",
+            ".\t0\tANY\tSIG\tTYPE0 15 0 0 20231114222820 20231114221820 4242 example.com. BwcH",
+            "example.com.\t300\tIN\tKEY\t512 3 15 CQkJ",
+            "example.com.\t300\tIN\tPX\t10 net2.it. PRMD-net2.ADMD-p400.C-it.",
+            "example.com.\t300\tIN\tAAAA\t2001:db8::1",
+            "example.com.\t300\tIN\tLOC\t52 22 23.000 N 4 53 32.000 E -2.00m 0.00m 10000m 10m",
+            "_sip._tcp.example.com.\t300\tIN\tSRV\t10 60 5060 sip.example.com.",
+            "example.com.\t300\tIN\tNAPTR\t100 10 \"u\" \"E2U+sip\" \"!^.*$!sip:info@example.com!\" .",
+            "example.com.\t300\tIN\tNAPTR\t0 0 \"\\255\" \"\" \"!a!\\128!\" .",
+            "example.com.\t300\tIN\tKX\t10 kx.example.com.",
+            "example.com.\t300\tIN\tCERT\tPGP 0 0 AQID",
+            "example.com.\t300\tIN\tDNAME\texample.net.",
+            "example.com.\t300\tIN\tAPL\t1:192.168.32.0/21 !1:192.168.38.0/28 2:ff00::/8",
+            "example.com.\t300\tIN\tDS\t20326 8 2 E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D",
+            "example.com.\t300\tIN\tSSHFP\t4 2 123456789ABCDEF0",
+            "example.com.\t300\tIN\tIPSECKEY\t10 3 2 gw.example.com. AQIDBA==",
+            "example.com.\t300\tIN\tIPSECKEY\t10 0 0 .",
+            "example.com.\t300\tIN\tRRSIG\tA 8 2 300 20231114222820 20231114221820 12345 example.com. BwcH",
+            "example.com.\t300\tIN\tNSEC\thost.example.com. A MX RRSIG NSEC TYPE1234",
+            "example.com.\t300\tIN\tDNSKEY\t257 3 8 AwEAAQ==",
+            "example.com.\t300\tIN\tDHCID\tAAEBqrs=",
+            "example.com.\t300\tIN\tNSEC3\t1 1 12 aabbccdd 2T7B4G4VSA5SMI47K61MV5BV1A22BOJR A RRSIG",
+            "example.com.\t300\tIN\tNSEC3PARAM\t1 0 0 -",
+            "_443._tcp.example.com.\t300\tIN\tTLSA\t3 1 1 ABCDEF",
+            "example.com.\t300\tIN\tSMIMEA\t3 0 0 0102",
+            "example.com.\t300\tIN\tHIP\t2 20202020202020202020202020202020 AwEAAQ== rvs.example.net.",
+            "example.com.\t300\tIN\tCDS\t0 0 0 00",
+            "example.com.\t300\tIN\tCDNSKEY\t0 3 0 AA==",
+            "example.com.\t300\tIN\tOPENPGPKEY\tAQID",
+            "example.com.\t300\tIN\tCSYNC\t66 3 A NS AAAA",
+            "example.com.\t300\tIN\tZONEMD\t2018031900 1 1 C6C6",
+            "example.com.\t300\tIN\tSVCB\t0 svc.example.net.",
+            "example.com.\t300\tIN\tHTTPS\t1 . alpn=\"h2,h3\" port=8443",
+            "example.com.\t300\tIN\tSPF\t\"v=spf1 -all\"",
+            "example.com.\t300\tIN\tNID\t10 0014:4fff:ff20:ee64",
+            "example.com.\t300\tIN\tL32\t10 10.1.2.0",
+            "example.com.\t300\tIN\tL64\t10 2001:0db8:1140:1000",
+            "example.com.\t300\tIN\tLP\t10 l64-subnet1.example.com.",
+            "example.com.\t300\tIN\tEUI48\t00-00-5e-00-53-2a",
+            "example.com.\t300\tIN\tEUI64\t00-00-5e-ef-10-00-00-2a",
+            "example.com.\t0\tANY\tTKEY\tgss-tsig. 20231114221320 20231114231320 3 0 3 AQID 0",
+            "example.com.\t0\tANY\tTSIG\thmac-sha256. 1700000000 300 3 AQID 4660 0 0",
+            "example.com.\t300\tIN\tURI\t10 1 \"ftp://ftp1.example.com/public\"",
+            "example.com.\t300\tIN\tURI\t10 1 \"ftp://\\254\\255/\"",
+            "example.com.\t300\tIN\tCAA\t0 issue \"ca.example.net\"",
+            "example.com.\t300\tIN\tAVC\t\"app-name=WebEx\" \"app-class=OC\"",
+            "example.com.\t300\tIN\tDOA\t0 1 2 \"image/gif\" R0lG",
+            "example.com.\t300\tIN\tDOA\t0 1 2 \"\" -",
+            "example.com.\t300\tIN\tDOA\t0 1 2 \"\\128\\191\" -",
+            "example.com.\t300\tIN\tAVC\t\"\\255\"",
+            "example.com.\t300\tIN\tSPF\t\"v=spf1\\254\" \"\"",
+            "example.com.\t300\tIN\tAMTRELAY\t10 1 2 2001:db8::15",
+            "example.com.\t300\tIN\tTA\t20326 8 2 E0E0",
+            "example.com.\t300\tIN\tDLV\t20326 8 2 E0E0",
+            "example.com.\t300\tIN\tTYPE65280\t\\# 4 0a000001",
+            "example.com.\t300\tIN\tTYPE65280\t\\# 0",
+            // RDATA the typed decoders keep opaque still round-trips.
+            "a.\t60\tIN\tAMTRELAY\t\\# 1 0a",
+            "example.com.\t300\tIN\tNSEC3\t\\# 6 010000000000",
+            "example.com.\t300\tIN\tHIP\t\\# 5 00020001aa",
+            "example.com.\t300\tIN\tCAA\t\\# 4 0002692d",
+        ];
+
+        for line in lines {
+            let record: DnsRecord = line.parse().unwrap_or_else(|err| panic!("{line}: {err}"));
+            assert_eq!(line, record.to_string());
+
+            // Every type also parses from the generic form.
+            let header: Vec<&str> = line.splitn(4, '\t').take(3).collect();
+            let generic = GenericRdata::from_record(&record).unwrap();
+            let generic_line = format!("{} {generic}", header.join(" "));
+            assert_eq!(Ok(record), generic_line.parse(), "{generic_line}");
+        }
+    }
+
+    #[test]
+    fn records_parse_in_master_file_syntax() {
+        let expected = DnsRecord::A {
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 300,
+            addr: Ipv4Addr::new(10, 0, 0, 1),
+        };
+        for line in [
+            "example.com 300 IN A 10.0.0.1",
+            "example.com IN 300 A 10.0.0.1 ; comment",
+            "example.com 300 A 10.0.0.1",
+            "example.com 5m IN A 10.0.0.1",
+            "example.com 300 IN A \\# 4 0A000001",
+            "example.com 300 IN TYPE1 \\# 4 0a 00 00 01",
+        ] {
+            assert_eq!(Ok(expected.clone()), line.parse(), "{line}");
+        }
+
+        let txt: DnsRecord = "example.com 300 TXT word \"two words\" ( split )"
+            .parse()
+            .unwrap();
+        let DnsRecord::TXT { data, .. } = txt else {
+            panic!("expected TXT, got {txt:?}");
+        };
+        assert_eq!(
+            vec![b"word".to_vec(), b"two words".to_vec(), b"split".to_vec()],
+            data
+        );
+
+        assert_eq!(
+            Err(PresentationError::Missing { field: "TTL" }),
+            "example.com IN A 10.0.0.1".parse::<DnsRecord>()
+        );
+        assert_eq!(
+            Err(PresentationError::Missing {
+                field: "MX exchange"
+            }),
+            "example.com 300 IN MX 10".parse::<DnsRecord>()
+        );
+        for bad in [
+            "example.com 300 IN A 10.0.0.1 extra",
+            "example.com 300 IN A 10.0.0",
+            "example.com 300 IN A \\# 3 0a000001",
+            "example.com 300 IN A \\# 3 0a0000",
+            "example.com 300 IN TYPE65280 0a000001",
+            "example.com 300 IN TXT \"unterminated",
+            "example.com 300 IN CAA 0 i-x \"v\"",
+            "example.com 300 IN CAA 0 \"\" \"v\"",
+            "example.com 300 IN EUI48 00-00-5e-00-53",
+            "example.com 300 IN NID 10 0014:4fff:ff20",
+            "example.com 300 IN TSIG hmac-sha256. 281474976710656 300 0 4660 0 0",
+        ] {
+            assert!(bad.parse::<DnsRecord>().is_err(), "{bad}");
+        }
+        assert!(matches!(
+            "example.com 300 IN BOGUS x".parse::<DnsRecord>(),
+            Err(PresentationError::UnknownMnemonic { .. })
+        ));
+        assert_eq!(QueryType::A, expected.qtype());
+    }
+}
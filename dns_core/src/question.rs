@@ -1,55 +1,188 @@
-use crate::{buffer::BytePacketBuffer, types::QueryType};
+use std::{fmt, str::FromStr};
+
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    error::{EncodeError, ParseError, PresentationError},
+    name::Name,
+    types::{DnsClass, QueryType},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DnsQuestion {
-    pub name: String,
+    pub name: Name,
     pub qtype: QueryType,
+    pub qclass: DnsClass,
 }
 
 impl DnsQuestion {
-    pub fn new(name: String, qtype: QueryType) -> DnsQuestion {
-        DnsQuestion { name, qtype }
+    pub fn new(name: Name, qtype: QueryType) -> DnsQuestion {
+        DnsQuestion {
+            name,
+            qtype,
+            qclass: DnsClass::IN,
+        }
+    }
+
+    pub fn with_class(name: Name, qtype: QueryType, qclass: DnsClass) -> DnsQuestion {
+        DnsQuestion {
+            name,
+            qtype,
+            qclass,
+        }
+    }
+
+    /// mDNS unicast-response bit (RFC 6762 section 5.4).
+    pub fn unicast_response(&self) -> bool {
+        self.qclass.split_mdns().1
+    }
+
+    /// The class with the mDNS unicast-response bit cleared.
+    pub fn base_class(&self) -> DnsClass {
+        self.qclass.split_mdns().0
     }
 
-    pub fn read(
-        &mut self,
-        buffer: &mut BytePacketBuffer,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn read<S: Storage>(&mut self, buffer: &mut BytePacketBuffer<S>) -> Result<(), ParseError> {
         buffer.read_qname(&mut self.name)?;
         self.qtype = QueryType::from_num(buffer.read_u16()?); // qtype
-        let _ = buffer.read_u16()?; // class
+        self.qclass = DnsClass::from_num(buffer.read_u16()?); // class
 
         Ok(())
     }
 
-    pub fn write(&self, buffer: &mut BytePacketBuffer) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn write<S: Storage>(&self, buffer: &mut BytePacketBuffer<S>) -> Result<(), EncodeError> {
         buffer.write_qname(&self.name)?;
 
         let typenum = self.qtype.to_num();
         buffer.write_u16(typenum)?;
-        buffer.write_u16(1)?;
+        buffer.write_u16(self.qclass.to_num())?;
 
         Ok(())
     }
 }
 
+impl fmt::Display for DnsQuestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\t{}\t{}", self.name, self.qclass, self.qtype)
+    }
+}
+
+impl FromStr for DnsQuestion {
+    type Err = PresentationError;
+
+    /// Parses `name [class] type`, with the class and type in either order.
+    /// The class defaults to IN.
+    fn from_str(s: &str) -> Result<DnsQuestion, PresentationError> {
+        let mut words = s.split_whitespace();
+        let text = words
+            .next()
+            .ok_or(PresentationError::Missing { field: "name" })?;
+        let name = text.parse().map_err(|_| PresentationError::Invalid {
+            field: "name",
+            text: text.to_string(),
+        })?;
+
+        let (qclass, qtype) = match words.collect::<Vec<_>>()[..] {
+            [] => return Err(PresentationError::Missing { field: "type" }),
+            // A lone word is the type, so `ANY` asks for every type rather
+            // than every class.
+            [qtype] => (DnsClass::IN, qtype.parse()?),
+            [first, second] => match (first.parse(), second.parse()) {
+                (Ok(qclass), Ok(qtype)) => (qclass, qtype),
+                _ => (second.parse()?, first.parse()?),
+            },
+            [_, _, trailing, ..] => {
+                return Err(PresentationError::Invalid {
+                    field: "trailing data",
+                    text: trailing.to_string(),
+                });
+            }
+        };
+
+        Ok(DnsQuestion {
+            name,
+            qtype,
+            qclass,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DnsQuestion;
-    use crate::{buffer::BytePacketBuffer, types::QueryType};
+    use crate::{
+        buffer::BytePacketBuffer,
+        name::Name,
+        types::{DnsClass, QueryType},
+    };
 
     #[test]
     fn question_write_and_read_roundtrip() {
-        let question = DnsQuestion::new("rust-lang.org".into(), QueryType::MX);
+        let question = DnsQuestion::new("rust-lang.org".parse().unwrap(), QueryType::MX);
         let mut buffer = BytePacketBuffer::new();
         question.write(&mut buffer).unwrap();
         let written_position = buffer.pos();
 
-        buffer.seek(0);
-        let mut parsed = DnsQuestion::new(String::new(), QueryType::UNKNOWN(0));
+        buffer.seek(0).unwrap();
+        let mut parsed = DnsQuestion::new(Name::root(), QueryType::UNKNOWN(0));
         parsed.read(&mut buffer).unwrap();
 
         assert_eq!(question, parsed);
         assert_eq!(written_position, buffer.pos());
     }
+
+    #[test]
+    fn question_class_roundtrip() {
+        for qclass in [DnsClass::CH, DnsClass::NONE, DnsClass::UNKNOWN(0x8001)] {
+            let question =
+                DnsQuestion::with_class("version.bind".parse().unwrap(), QueryType::TXT, qclass);
+            let mut buffer = BytePacketBuffer::new();
+            question.write(&mut buffer).unwrap();
+
+            buffer.seek(0).unwrap();
+            let mut parsed = DnsQuestion::new(Name::root(), QueryType::UNKNOWN(0));
+            parsed.read(&mut buffer).unwrap();
+            assert_eq!(qclass, parsed.qclass);
+        }
+    }
+
+    #[test]
+    fn mdns_unicast_response_bit_is_split_from_the_class() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"a.local".parse().unwrap()).unwrap();
+        buffer.write_u16(QueryType::A.to_num()).unwrap();
+        buffer.write_u16(0x8001).unwrap();
+
+        buffer.seek(0).unwrap();
+        let mut question = DnsQuestion::new(Name::root(), QueryType::UNKNOWN(0));
+        question.read(&mut buffer).unwrap();
+        assert!(question.unicast_response());
+        assert_eq!(DnsClass::IN, question.base_class());
+        assert_eq!(DnsClass::UNKNOWN(0x8001), question.qclass);
+
+        let plain = DnsQuestion::new("a.local".parse().unwrap(), QueryType::A);
+        assert!(!plain.unicast_response());
+        assert_eq!(DnsClass::IN, plain.base_class());
+    }
+
+    #[test]
+    fn question_presentation() {
+        let question: DnsQuestion = "example.com. MX".parse().unwrap();
+        assert_eq!(
+            DnsQuestion::new("example.com".parse().unwrap(), QueryType::MX),
+            question
+        );
+        assert_eq!("example.com.\tIN\tMX", question.to_string());
+
+        let chaos: DnsQuestion = "version.bind TXT CH".parse().unwrap();
+        assert_eq!(DnsClass::CH, chaos.qclass);
+        assert_eq!(Ok(chaos.clone()), chaos.to_string().parse());
+
+        let any: DnsQuestion = "example.com ANY".parse().unwrap();
+        assert_eq!((DnsClass::IN, QueryType::ANY), (any.qclass, any.qtype));
+        let both: DnsQuestion = "example.com ANY IN".parse().unwrap();
+        assert_eq!((DnsClass::IN, QueryType::ANY), (both.qclass, both.qtype));
+
+        assert!("example.com.".parse::<DnsQuestion>().is_err());
+        assert!("example.com. A A".parse::<DnsQuestion>().is_err());
+    }
 }
@@ -0,0 +1,214 @@
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    encoding::{hex_decode, hex_encode},
+    error::{EncodeError, ParseError, PresentationError},
+};
+
+/// One address prefix of an APL record (RFC 3123 section 4). The address
+/// is kept as sent, with trailing zero octets omitted.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AplItem {
+    pub family: u16,
+    pub prefix: u8,
+    pub negation: bool,
+    pub address: Vec<u8>,
+}
+
+impl AplItem {
+    pub fn new(negation: bool, addr: IpAddr, prefix: u8) -> AplItem {
+        let (family, mut address) = match addr {
+            IpAddr::V4(addr) => (1, addr.octets().to_vec()),
+            IpAddr::V6(addr) => (2, addr.octets().to_vec()),
+        };
+        while address.last() == Some(&0) {
+            address.pop();
+        }
+        AplItem {
+            family,
+            prefix,
+            negation,
+            address,
+        }
+    }
+
+    /// The address for the IPv4 and IPv6 families, zero-filled to full
+    /// length.
+    pub fn addr(&self) -> Option<IpAddr> {
+        match self.family {
+            1 => {
+                let mut octets = [0u8; 4];
+                octets[..self.address.len()].copy_from_slice(&self.address);
+                Some(IpAddr::V4(Ipv4Addr::from(octets)))
+            }
+            2 => {
+                let mut octets = [0u8; 16];
+                octets[..self.address.len()].copy_from_slice(&self.address);
+                Some(IpAddr::V6(Ipv6Addr::from(octets)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads items up to `end`, the end of the RDATA.
+    pub(crate) fn read_list<S: Storage>(
+        buffer: &mut BytePacketBuffer<S>,
+        end: usize,
+    ) -> Result<Vec<AplItem>, ParseError> {
+        let mut items = Vec::new();
+        while buffer.pos() < end {
+            let offset = buffer.pos();
+            let family = buffer.read_u16()?;
+            let prefix = buffer.read()?;
+            let length = buffer.read()?;
+            let address = buffer.read_bytes((length & 0x7f) as usize)?.to_vec();
+
+            let (max_prefix, max_len) = match family {
+                1 => (32, 4),
+                2 => (128, 16),
+                _ => (u8::MAX, usize::MAX),
+            };
+            if prefix > max_prefix || address.len() > max_len {
+                return Err(ParseError::InvalidRdata {
+                    offset,
+                    reason: "APL prefix or address too long for its family",
+                });
+            }
+            items.push(AplItem {
+                family,
+                prefix,
+                negation: length & 0x80 != 0,
+                address,
+            });
+        }
+        Ok(items)
+    }
+
+    pub(crate) fn write<S: Storage>(
+        &self,
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<(), EncodeError> {
+        if self.address.len() > 0x7f {
+            return Err(EncodeError::InvalidRdata {
+                offset: buffer.pos(),
+                reason: "APL address longer than 127 bytes",
+            });
+        }
+        buffer.write_u16(self.family)?;
+        buffer.write_u8(self.prefix)?;
+        buffer.write_u8((self.negation as u8) << 7 | self.address.len() as u8)?;
+        buffer.write_bytes(&self.address)
+    }
+}
+
+impl fmt::Display for AplItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negation {
+            f.write_str("!")?;
+        }
+        match self.addr() {
+            Some(addr) => write!(f, "{}:{addr}/{}", self.family, self.prefix),
+            // No presentation format exists for other families.
+            None => write!(
+                f,
+                "{}:0x{}/{}",
+                self.family,
+                hex_encode(&self.address),
+                self.prefix
+            ),
+        }
+    }
+}
+
+impl FromStr for AplItem {
+    type Err = PresentationError;
+
+    fn from_str(s: &str) -> Result<AplItem, PresentationError> {
+        let invalid = || PresentationError::Invalid {
+            field: "APL item",
+            text: s.to_string(),
+        };
+
+        let (negation, rest) = match s.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (family, rest) = rest.split_once(':').ok_or_else(invalid)?;
+        let (address, prefix) = rest.rsplit_once('/').ok_or_else(invalid)?;
+        let family: u16 = family.parse().map_err(|_| invalid())?;
+        let prefix: u8 = prefix.parse().map_err(|_| invalid())?;
+
+        match family {
+            1 | 2 => {
+                let addr: IpAddr = address.parse().map_err(|_| invalid())?;
+                let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+                if addr.is_ipv4() != (family == 1) || prefix > max_prefix {
+                    return Err(invalid());
+                }
+                Ok(AplItem::new(negation, addr, prefix))
+            }
+            _ => {
+                let address = address
+                    .strip_prefix("0x")
+                    .and_then(hex_decode)
+                    .filter(|address| address.len() <= 0x7f)
+                    .ok_or_else(invalid)?;
+                Ok(AplItem {
+                    family,
+                    prefix,
+                    negation,
+                    address,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AplItem;
+    use crate::{buffer::BytePacketBuffer, error::ParseError};
+
+    #[test]
+    fn items_roundtrip_with_trimmed_addresses() {
+        let items = vec![
+            AplItem::new(false, "192.168.32.0".parse().unwrap(), 21),
+            AplItem::new(true, "192.168.38.0".parse().unwrap(), 28),
+            AplItem::new(false, "ff00::".parse().unwrap(), 8),
+        ];
+        assert_eq!(vec![192, 168, 32], items[0].address);
+        assert_eq!("1:192.168.32.0/21", items[0].to_string());
+        assert_eq!("!1:192.168.38.0/28", items[1].to_string());
+        assert_eq!("2:ff00::/8", items[2].to_string());
+        for item in &items {
+            assert_eq!(Ok(item.clone()), item.to_string().parse());
+        }
+        assert!("1:10.0.0.0/33".parse::<AplItem>().is_err());
+        assert!("2:10.0.0.0/8".parse::<AplItem>().is_err());
+
+        let mut buffer = BytePacketBuffer::new();
+        for item in &items {
+            item.write(&mut buffer).unwrap();
+        }
+        let end = buffer.pos();
+        assert_eq!(&[0, 1, 21, 3, 192, 168, 32], &buffer.buffer[..7]);
+        buffer.seek(0).unwrap();
+        assert_eq!(items, AplItem::read_list(&mut buffer, end).unwrap());
+    }
+
+    #[test]
+    fn rejects_oversized_prefixes() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_bytes(&[0, 1, 33, 1, 10]).unwrap();
+        buffer.seek(0).unwrap();
+        assert!(matches!(
+            AplItem::read_list(&mut buffer, 5),
+            Err(ParseError::InvalidRdata { offset: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,289 @@
+use std::{fmt, str::FromStr};
+
+use crate::{error::PresentationError, name::Name, record::DnsRecord};
+
+/// Issuer-critical bit of the CAA flags octet.
+pub const CRITICAL_FLAG: u8 = 0x80;
+
+/// Whether `tag` is a valid property tag: one or more ASCII letters and
+/// digits (RFC 8659 section 4.1).
+pub fn is_valid_tag(tag: &[u8]) -> bool {
+    !tag.is_empty() && tag.iter().all(u8::is_ascii_alphanumeric)
+}
+
+/// A CAA property with the values of the tags defined in RFC 8659 parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaaProperty {
+    Issue(IssueValue),
+    IssueWild(IssueValue),
+    Iodef(String),
+    Other { tag: String, value: Vec<u8> },
+}
+
+impl CaaProperty {
+    /// Interprets a record's tag and value. Tags compare case-insensitively.
+    pub fn parse(tag: &str, value: &[u8]) -> Result<CaaProperty, PresentationError> {
+        let text = || {
+            std::str::from_utf8(value).map_err(|_| PresentationError::Invalid {
+                field: "CAA value",
+                text: String::from_utf8_lossy(value).to_string(),
+            })
+        };
+
+        let property = match tag.to_ascii_lowercase().as_str() {
+            "issue" => CaaProperty::Issue(text()?.parse()?),
+            "issuewild" => CaaProperty::IssueWild(text()?.parse()?),
+            "iodef" => {
+                let url = text()?;
+                let scheme = url.split_once(':').map(|(scheme, _)| scheme);
+                if !matches!(scheme, Some("mailto" | "http" | "https")) {
+                    return Err(PresentationError::Invalid {
+                        field: "CAA iodef URL",
+                        text: url.to_string(),
+                    });
+                }
+                CaaProperty::Iodef(url.to_string())
+            }
+            _ => CaaProperty::Other {
+                tag: tag.to_string(),
+                value: value.to_vec(),
+            },
+        };
+        Ok(property)
+    }
+
+    /// The property of a CAA record, or `None` for other record types.
+    pub fn from_record(record: &DnsRecord) -> Option<Result<CaaProperty, PresentationError>> {
+        match record {
+            DnsRecord::CAA { tag, value, .. } => Some(CaaProperty::parse(tag, value)),
+            _ => None,
+        }
+    }
+}
+
+/// Value of an `issue` or `issuewild` property, e.g.
+/// `ca.example.net; account=230123`. An empty issuer (`;`) forbids
+/// issuance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IssueValue {
+    pub issuer: Option<Name>,
+    pub parameters: Vec<(String, String)>,
+}
+
+impl FromStr for IssueValue {
+    type Err = PresentationError;
+
+    fn from_str(s: &str) -> Result<IssueValue, PresentationError> {
+        let invalid = || PresentationError::Invalid {
+            field: "CAA issue value",
+            text: s.to_string(),
+        };
+        let is_wsp = |c: char| c == ' ' || c == '\t';
+
+        let (issuer, parameters) = match s.split_once(';') {
+            Some((issuer, parameters)) => (issuer, Some(parameters)),
+            None => (s, None),
+        };
+
+        let issuer = issuer.trim_matches(is_wsp);
+        let issuer = if issuer.is_empty() {
+            None
+        } else {
+            let ldh = issuer.split('.').all(|label| {
+                label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                    && label.starts_with(|c: char| c.is_ascii_alphanumeric())
+                    && label.ends_with(|c: char| c.is_ascii_alphanumeric())
+            });
+            if !ldh {
+                return Err(invalid());
+            }
+            Some(issuer.parse().map_err(|_| invalid())?)
+        };
+
+        let mut parsed = Vec::new();
+        let parameters = parameters.map(|p| p.trim_matches(is_wsp)).unwrap_or("");
+        if !parameters.is_empty() {
+            for parameter in parameters.split(';') {
+                let (tag, value) = parameter.split_once('=').ok_or_else(invalid)?;
+                let tag = tag.trim_matches(is_wsp);
+                let value = value.trim_matches(is_wsp);
+                if tag.is_empty()
+                    || !tag.bytes().all(|b| b.is_ascii_alphanumeric())
+                    || !value.bytes().all(|b| b.is_ascii_graphic())
+                {
+                    return Err(invalid());
+                }
+                parsed.push((tag.to_string(), value.to_string()));
+            }
+        }
+
+        Ok(IssueValue {
+            issuer,
+            parameters: parsed,
+        })
+    }
+}
+
+impl fmt::Display for IssueValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(issuer) = &self.issuer {
+            let issuer = issuer.to_string();
+            f.write_str(issuer.trim_end_matches('.'))?;
+        }
+        if self.issuer.is_none() || !self.parameters.is_empty() {
+            f.write_str(";")?;
+        }
+        for (i, (tag, value)) in self.parameters.iter().enumerate() {
+            let separator = if i == 0 { " " } else { "; " };
+            write!(f, "{separator}{tag}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Decides whether the CA identified by `ca` may issue a certificate given
+/// the relevant CAA RRset (RFC 8659 section 4). `wildcard` selects the
+/// `issuewild` rules. Records of other types are ignored.
+pub fn issuance_allowed<'a, I>(rrset: I, ca: &Name, wildcard: bool) -> bool
+where
+    I: IntoIterator<Item = &'a DnsRecord>,
+{
+    let mut issue = Vec::new();
+    let mut issuewild = Vec::new();
+
+    for record in rrset {
+        let DnsRecord::CAA {
+            flags, tag, value, ..
+        } = record
+        else {
+            continue;
+        };
+        match CaaProperty::parse(tag, value) {
+            Ok(CaaProperty::Issue(value)) => issue.push(Some(value)),
+            Ok(CaaProperty::IssueWild(value)) => issuewild.push(Some(value)),
+            // A malformed issue value still restricts issuance but names no
+            // CA.
+            Err(_) if tag.eq_ignore_ascii_case("issue") => issue.push(None),
+            Err(_) if tag.eq_ignore_ascii_case("issuewild") => issuewild.push(None),
+            Ok(CaaProperty::Other { .. }) if flags & CRITICAL_FLAG != 0 => return false,
+            _ => {}
+        }
+    }
+
+    let relevant = if wildcard && !issuewild.is_empty() {
+        issuewild
+    } else {
+        issue
+    };
+    if relevant.is_empty() {
+        return true;
+    }
+    relevant
+        .iter()
+        .flatten()
+        .any(|value| value.issuer.as_ref() == Some(ca))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaaProperty, IssueValue, issuance_allowed};
+    use crate::{error::PresentationError, name::Name, record::DnsRecord, types::DnsClass};
+
+    fn caa(flags: u8, tag: &str, value: &str) -> DnsRecord {
+        DnsRecord::CAA {
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 300,
+            flags,
+            tag: tag.into(),
+            value: value.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn parses_issue_values() {
+        let value: IssueValue = "ca.example.net; account=230123; policy=ev".parse().unwrap();
+        assert_eq!(Some("ca.example.net".parse().unwrap()), value.issuer);
+        assert_eq!(
+            vec![
+                ("account".to_string(), "230123".to_string()),
+                ("policy".to_string(), "ev".to_string())
+            ],
+            value.parameters
+        );
+        assert_eq!(
+            "ca.example.net; account=230123; policy=ev",
+            value.to_string()
+        );
+
+        let forbid: IssueValue = " ; ".parse().unwrap();
+        assert_eq!(None, forbid.issuer);
+        assert_eq!(";", forbid.to_string());
+
+        for invalid in [
+            "ca..example",
+            "-ca.example",
+            "ca.example; account",
+            "ca; =1",
+        ] {
+            assert!(invalid.parse::<IssueValue>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_properties() {
+        assert_eq!(
+            Ok(CaaProperty::Iodef("mailto:security@example.com".into())),
+            CaaProperty::parse("iodef", b"mailto:security@example.com")
+        );
+        assert!(matches!(
+            CaaProperty::parse("iodef", b"ftp://example.com"),
+            Err(PresentationError::Invalid { .. })
+        ));
+        assert!(matches!(
+            CaaProperty::parse("ISSUEWILD", b"ca.example.net"),
+            Ok(CaaProperty::IssueWild(_))
+        ));
+        assert!(matches!(
+            CaaProperty::from_record(&caa(0, "tbs", "x")),
+            Some(Ok(CaaProperty::Other { .. }))
+        ));
+    }
+
+    #[test]
+    fn decides_issuance() {
+        let ca: Name = "CA.example.net".parse().unwrap();
+        let other: Name = "other.example".parse().unwrap();
+
+        assert!(issuance_allowed(&[], &ca, false));
+
+        let rrset = [
+            caa(0, "issue", "ca.example.net; account=1"),
+            caa(0, "issuewild", ";"),
+            caa(0, "iodef", "mailto:security@example.com"),
+        ];
+        assert!(issuance_allowed(&rrset, &ca, false));
+        assert!(!issuance_allowed(&rrset, &other, false));
+        assert!(!issuance_allowed(&rrset, &ca, true));
+
+        // Without issuewild, wildcard requests fall back to issue.
+        let issue_only = [caa(0, "issue", "other.example")];
+        assert!(issuance_allowed(&issue_only, &other, true));
+        assert!(!issuance_allowed(&issue_only, &ca, true));
+
+        // Only iodef: no restriction.
+        assert!(issuance_allowed(&rrset[2..], &other, false));
+
+        // Unknown critical property forbids issuance; non-critical is ignored.
+        let critical = [caa(0, "issue", "ca.example.net"), caa(128, "tbs", "x")];
+        assert!(!issuance_allowed(&critical, &ca, false));
+        let noncritical = [caa(0, "issue", "ca.example.net"), caa(0, "tbs", "x")];
+        assert!(issuance_allowed(&noncritical, &ca, false));
+
+        // Malformed issue values restrict without authorising anyone.
+        let malformed = [caa(0, "issue", "ca.example.net; account")];
+        assert!(!issuance_allowed(&malformed, &ca, false));
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::PresentationError;
+
+/// Certificate type of a CERT record (RFC 4398 section 2.1).
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum CertType {
+    UNKNOWN(u16),
+    PKIX,    // 1
+    SPKI,    // 2
+    PGP,     // 3
+    IPKIX,   // 4
+    ISPKI,   // 5
+    IPGP,    // 6
+    ACPKIX,  // 7
+    IACPKIX, // 8
+    URI,     // 253
+    OID,     // 254
+}
+
+impl CertType {
+    pub fn to_num(&self) -> u16 {
+        match *self {
+            CertType::UNKNOWN(x) => x,
+            CertType::PKIX => 1,
+            CertType::SPKI => 2,
+            CertType::PGP => 3,
+            CertType::IPKIX => 4,
+            CertType::ISPKI => 5,
+            CertType::IPGP => 6,
+            CertType::ACPKIX => 7,
+            CertType::IACPKIX => 8,
+            CertType::URI => 253,
+            CertType::OID => 254,
+        }
+    }
+
+    pub fn from_num(num: u16) -> CertType {
+        match num {
+            1 => CertType::PKIX,
+            2 => CertType::SPKI,
+            3 => CertType::PGP,
+            4 => CertType::IPKIX,
+            5 => CertType::ISPKI,
+            6 => CertType::IPGP,
+            7 => CertType::ACPKIX,
+            8 => CertType::IACPKIX,
+            253 => CertType::URI,
+            254 => CertType::OID,
+            _ => CertType::UNKNOWN(num),
+        }
+    }
+}
+
+impl fmt::Display for CertType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertType::UNKNOWN(num) => write!(f, "{num}"),
+            CertType::PKIX => f.write_str("PKIX"),
+            CertType::SPKI => f.write_str("SPKI"),
+            CertType::PGP => f.write_str("PGP"),
+            CertType::IPKIX => f.write_str("IPKIX"),
+            CertType::ISPKI => f.write_str("ISPKI"),
+            CertType::IPGP => f.write_str("IPGP"),
+            CertType::ACPKIX => f.write_str("ACPKIX"),
+            CertType::IACPKIX => f.write_str("IACPKIX"),
+            CertType::URI => f.write_str("URI"),
+            CertType::OID => f.write_str("OID"),
+        }
+    }
+}
+
+impl FromStr for CertType {
+    type Err = PresentationError;
+
+    /// Accepts the mnemonics case-insensitively or a decimal number
+    /// (RFC 4398 section 2.2).
+    fn from_str(s: &str) -> Result<CertType, PresentationError> {
+        let cert_type = match s.to_ascii_uppercase().as_str() {
+            "PKIX" => CertType::PKIX,
+            "SPKI" => CertType::SPKI,
+            "PGP" => CertType::PGP,
+            "IPKIX" => CertType::IPKIX,
+            "ISPKI" => CertType::ISPKI,
+            "IPGP" => CertType::IPGP,
+            "ACPKIX" => CertType::ACPKIX,
+            "IACPKIX" => CertType::IACPKIX,
+            "URI" => CertType::URI,
+            "OID" => CertType::OID,
+            _ => s.parse().map(CertType::from_num).map_err(|_| {
+                PresentationError::UnknownMnemonic {
+                    text: s.to_string(),
+                }
+            })?,
+        };
+        Ok(cert_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CertType;
+
+    #[test]
+    fn mnemonics_and_numbers() {
+        assert_eq!(Ok(CertType::PKIX), "pkix".parse());
+        assert_eq!(Ok(CertType::IPGP), "6".parse());
+        assert_eq!(Ok(CertType::UNKNOWN(65280)), "65280".parse());
+        assert!("X509".parse::<CertType>().is_err());
+
+        assert_eq!("OID", CertType::from_num(254).to_string());
+        assert_eq!("65280", CertType::from_num(65280).to_string());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{error::NameError, name::Name, record::DnsRecord};
+
+/// Rewrites `qname` through a DNAME record by replacing the owner suffix
+/// with the target (RFC 6672 section 2.2). Returns `None` when the record is
+/// not a DNAME or `qname` does not lie strictly below its owner; a DNAME
+/// never applies to its own owner name. Fails when the result would exceed
+/// the maximum name length, which servers report as YXDOMAIN.
+pub fn rewrite(qname: &Name, record: &DnsRecord) -> Option<Result<Name, NameError>> {
+    let DnsRecord::DNAME { domain, host, .. } = record else {
+        return None;
+    };
+    if qname.label_count() <= domain.label_count() || !qname.is_subdomain_of(domain) {
+        return None;
+    }
+
+    let prefix = qname.label_count() - domain.label_count();
+    Some(Name::from_labels(
+        qname.labels().take(prefix).chain(host.labels()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rewrite;
+    use crate::{error::NameError, name::Name, record::DnsRecord, types::DnsClass};
+
+    fn dname(owner: &str, target: &str) -> DnsRecord {
+        DnsRecord::DNAME {
+            domain: owner.parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 300,
+            host: target.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn rewrites_names_below_the_owner() {
+        let record = dname("example.com", "example.net");
+        let qname: Name = "www.Sub.EXAMPLE.com".parse().unwrap();
+        assert_eq!(
+            Some(Ok("www.sub.example.net".parse().unwrap())),
+            rewrite(&qname, &record)
+        );
+
+        assert_eq!(None, rewrite(&"example.com".parse().unwrap(), &record));
+        assert_eq!(None, rewrite(&"www.example.org".parse().unwrap(), &record));
+        assert_eq!(
+            None,
+            rewrite(&"www.notexample.com".parse().unwrap(), &record)
+        );
+
+        let to_root = dname("example.com", ".");
+        assert_eq!(
+            Some(Ok("www".parse().unwrap())),
+            rewrite(&"www.example.com".parse().unwrap(), &to_root)
+        );
+    }
+
+    #[test]
+    fn overlong_results_fail() {
+        let label = "a".repeat(63);
+        let target = format!("{label}.{label}.{label}");
+        let record = dname("example", &target);
+        let qname: Name = format!("{label}.{label}.example").parse().unwrap();
+        assert!(matches!(
+            rewrite(&qname, &record),
+            Some(Err(NameError::NameTooLong { .. }))
+        ));
+    }
+}
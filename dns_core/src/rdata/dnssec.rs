@@ -0,0 +1,374 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    encoding::{base64_encode, hex_encode},
+    error::{EncodeError, ParseError, PresentationError},
+    record::DnsRecord,
+    types::QueryType,
+};
+
+/// The set of types listed in an NSEC, NSEC3 or CSYNC type bitmap
+/// (RFC 4034 section 4.1.2). Iterates in numeric type order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeBitmap {
+    types: BTreeSet<u16>,
+}
+
+impl TypeBitmap {
+    pub fn new() -> TypeBitmap {
+        TypeBitmap::default()
+    }
+
+    pub fn insert(&mut self, qtype: QueryType) -> bool {
+        self.types.insert(qtype.to_num())
+    }
+
+    pub fn remove(&mut self, qtype: QueryType) -> bool {
+        self.types.remove(&qtype.to_num())
+    }
+
+    pub fn contains(&self, qtype: QueryType) -> bool {
+        self.types.contains(&qtype.to_num())
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = QueryType> + '_ {
+        self.types.iter().map(|&num| QueryType::from_num(num))
+    }
+
+    /// Reads window blocks up to `end`, the end of the RDATA. Windows must
+    /// appear in increasing order with bitmap lengths between 1 and 32.
+    pub(crate) fn read<S: Storage>(
+        buffer: &mut BytePacketBuffer<S>,
+        end: usize,
+    ) -> Result<TypeBitmap, ParseError> {
+        let mut types = BTreeSet::new();
+        let mut previous = None;
+        while buffer.pos() < end {
+            let offset = buffer.pos();
+            let window = buffer.read()?;
+            let len = buffer.read()? as usize;
+            if previous.is_some_and(|previous| window <= previous) {
+                return Err(ParseError::InvalidRdata {
+                    offset,
+                    reason: "type bitmap windows out of order",
+                });
+            }
+            if len == 0 || len > 32 {
+                return Err(ParseError::InvalidRdata {
+                    offset,
+                    reason: "type bitmap length must be between 1 and 32",
+                });
+            }
+            if buffer.pos() + len > end {
+                return Err(ParseError::RdataLengthMismatch {
+                    offset,
+                    expected: end - offset,
+                    actual: len + 2,
+                });
+            }
+            previous = Some(window);
+
+            for (i, &byte) in buffer.read_bytes(len)?.iter().enumerate() {
+                for bit in 0..8 {
+                    if byte & (0x80 >> bit) != 0 {
+                        types.insert((window as u16) << 8 | (i * 8 + bit) as u16);
+                    }
+                }
+            }
+        }
+        Ok(TypeBitmap { types })
+    }
+
+    pub(crate) fn write<S: Storage>(
+        &self,
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<(), EncodeError> {
+        let mut types = self.types.iter().peekable();
+        while let Some(&first) = types.peek() {
+            let window = (first >> 8) as u8;
+            let mut bitmap = [0u8; 32];
+            let mut len = 0;
+            while let Some(&&num) = types.peek() {
+                if (num >> 8) as u8 != window {
+                    break;
+                }
+                let low = (num & 0xff) as usize;
+                bitmap[low / 8] |= 0x80 >> (low % 8);
+                len = low / 8 + 1;
+                types.next();
+            }
+            buffer.write_u8(window)?;
+            buffer.write_u8(len as u8)?;
+            buffer.write_bytes(&bitmap[..len])?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<QueryType> for TypeBitmap {
+    fn from_iter<I: IntoIterator<Item = QueryType>>(iter: I) -> TypeBitmap {
+        TypeBitmap {
+            types: iter.into_iter().map(|qtype| qtype.to_num()).collect(),
+        }
+    }
+}
+
+/// An RRSIG inception or expiration time: seconds since the epoch modulo
+/// 2^32 (RFC 4034 section 3.1.5). Displays as `YYYYMMDDHHmmSS` in UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Timestamp(pub u32);
+
+impl Timestamp {
+    pub fn from_system_time(time: SystemTime) -> Timestamp {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0);
+        Timestamp(secs as u32)
+    }
+
+    /// The point in time this value denotes, resolving the wrap-around with
+    /// serial number arithmetic (RFC 1982) to the instant closest to `now`.
+    pub fn to_system_time(&self, now: SystemTime) -> SystemTime {
+        let now_secs = now
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0);
+        let offset = self.0.wrapping_sub(now_secs as u32) as i32 as i64;
+        UNIX_EPOCH + Duration::from_secs((now_secs as i64 + offset).max(0) as u64)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0 as i64;
+        let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+        let time = secs.rem_euclid(86400);
+        write!(
+            f,
+            "{year:04}{month:02}{day:02}{:02}{:02}{:02}",
+            time / 3600,
+            time / 60 % 60,
+            time % 60
+        )
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = PresentationError;
+
+    /// Accepts `YYYYMMDDHHmmSS` or, for any other length, a plain count of
+    /// seconds (RFC 4034 section 3.2).
+    fn from_str(s: &str) -> Result<Timestamp, PresentationError> {
+        let invalid = || PresentationError::Invalid {
+            field: "timestamp",
+            text: s.to_string(),
+        };
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        if s.len() != 14 {
+            return s.parse().map(Timestamp).map_err(|_| invalid());
+        }
+
+        let field = |range: std::ops::Range<usize>| s[range].parse::<i64>().unwrap();
+        let (year, month, day) = (field(0..4), field(4..6), field(6..8));
+        let (hour, minute, second) = (field(8..10), field(10..12), field(12..14));
+        let days = days_from_civil(year, month, day);
+        if year < 1970
+            || civil_from_days(days) != (year, month, day)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(invalid());
+        }
+        let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+        Ok(Timestamp(secs as u32))
+    }
+}
+
+// Proleptic Gregorian calendar conversions, after Howard Hinnant's
+// `days_from_civil` and `civil_from_days`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+impl DnsRecord {
+    /// The public key of a DNSKEY, CDNSKEY or KEY record in base64.
+    pub fn public_key_base64(&self) -> Option<String> {
+        match self {
+            DnsRecord::DNSKEY { public_key, .. }
+            | DnsRecord::CDNSKEY { public_key, .. }
+            | DnsRecord::KEY { public_key, .. } => Some(base64_encode(public_key)),
+            _ => None,
+        }
+    }
+
+    /// The digest of a DS, CDS, TA or DLV record in hex.
+    pub fn digest_hex(&self) -> Option<String> {
+        match self {
+            DnsRecord::DS { digest, .. }
+            | DnsRecord::CDS { digest, .. }
+            | DnsRecord::TA { digest, .. }
+            | DnsRecord::DLV { digest, .. } => Some(hex_encode(digest)),
+            _ => None,
+        }
+    }
+
+    /// The signature of an RRSIG or SIG record in base64.
+    pub fn signature_base64(&self) -> Option<String> {
+        match self {
+            DnsRecord::RRSIG { signature, .. } | DnsRecord::SIG { signature, .. } => {
+                Some(base64_encode(signature))
+            }
+            _ => None,
+        }
+    }
+
+    /// The salt of an NSEC3 or NSEC3PARAM record in hex, or `-` when empty
+    /// as in presentation format.
+    pub fn salt_hex(&self) -> Option<String> {
+        match self {
+            DnsRecord::NSEC3 { salt, .. } | DnsRecord::NSEC3PARAM { salt, .. } => {
+                if salt.is_empty() {
+                    Some("-".to_string())
+                } else {
+                    Some(hex_encode(salt))
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Timestamp, TypeBitmap};
+    use crate::{buffer::BytePacketBuffer, error::ParseError, types::QueryType};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn type_bitmap_matches_rfc_4034_example() {
+        let bitmap: TypeBitmap = [
+            QueryType::NSEC,
+            QueryType::A,
+            QueryType::UNKNOWN(1234),
+            QueryType::RRSIG,
+            QueryType::MX,
+        ]
+        .into_iter()
+        .collect();
+
+        let mut buffer = BytePacketBuffer::new();
+        bitmap.write(&mut buffer).unwrap();
+        let mut expected = vec![0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03, 0x04, 0x1b];
+        expected.extend_from_slice(&[0; 26]);
+        expected.push(0x20);
+        assert_eq!(&expected[..], &buffer.buffer[..buffer.pos()]);
+
+        let end = buffer.pos();
+        buffer.seek(0).unwrap();
+        let parsed = TypeBitmap::read(&mut buffer, end).unwrap();
+        assert_eq!(bitmap, parsed);
+        assert_eq!(
+            vec![
+                QueryType::A,
+                QueryType::MX,
+                QueryType::RRSIG,
+                QueryType::NSEC,
+                QueryType::UNKNOWN(1234)
+            ],
+            parsed.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn type_bitmap_rejects_malformed_windows() {
+        for (bytes, offset) in [
+            (&[0x01, 0x01, 0x80, 0x00, 0x01, 0x40][..], 3),
+            (&[0x00, 0x00][..], 0),
+            (&[0x00, 0x21][..], 0),
+        ] {
+            let mut buffer = BytePacketBuffer::new();
+            buffer.write_bytes(bytes).unwrap();
+            buffer.seek(0).unwrap();
+            assert!(matches!(
+                TypeBitmap::read(&mut buffer, bytes.len()),
+                Err(ParseError::InvalidRdata { offset: o, .. }) if o == offset
+            ));
+        }
+
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_bytes(&[0x00, 0x04, 0x40]).unwrap();
+        buffer.seek(0).unwrap();
+        assert!(matches!(
+            TypeBitmap::read(&mut buffer, 3),
+            Err(ParseError::RdataLengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn timestamps_use_utc_calendar_format() {
+        let expiration: Timestamp = "20030322173103".parse().unwrap();
+        assert_eq!(Timestamp(1048354263), expiration);
+        assert_eq!("20030322173103", expiration.to_string());
+        assert_eq!(Ok(Timestamp(1048354263)), "1048354263".parse());
+        assert_eq!("21060207062815", Timestamp(u32::MAX).to_string());
+
+        for invalid in [
+            "20030230000000",
+            "20031322000000",
+            "19691231235959",
+            "2003-03-22",
+        ] {
+            assert!(invalid.parse::<Timestamp>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn timestamps_resolve_wraparound_near_now() {
+        let now = UNIX_EPOCH + Duration::from_secs((1 << 32) + 1000);
+        assert_eq!(
+            UNIX_EPOCH + Duration::from_secs((1 << 32) + 500),
+            Timestamp(500).to_system_time(now)
+        );
+        assert_eq!(
+            UNIX_EPOCH + Duration::from_secs((1 << 32) - 10),
+            Timestamp(u32::MAX - 9).to_system_time(now)
+        );
+    }
+}
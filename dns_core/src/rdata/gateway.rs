@@ -0,0 +1,86 @@
+use std::{
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    error::{EncodeError, ParseError},
+    name::Name,
+};
+
+/// The variable-format gateway of IPSECKEY (RFC 4025 section 2.5) and relay
+/// of AMTRELAY (RFC 8777 section 4.2), selected by a type number.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Gateway {
+    None,
+    Ipv4(Ipv4Addr),
+    Ipv6(Ipv6Addr),
+    Name(Name),
+}
+
+impl Gateway {
+    pub fn type_num(&self) -> u8 {
+        match self {
+            Gateway::None => 0,
+            Gateway::Ipv4(_) => 1,
+            Gateway::Ipv6(_) => 2,
+            Gateway::Name(_) => 3,
+        }
+    }
+
+    /// Reads a gateway of the given type, or returns `None` without
+    /// consuming anything when the type is not one of the four defined or
+    /// its address does not fit before `rdata_end`.
+    pub(crate) fn read<S: Storage>(
+        buffer: &mut BytePacketBuffer<S>,
+        type_num: u8,
+        rdata_end: usize,
+    ) -> Result<Option<Gateway>, ParseError> {
+        let remaining = rdata_end.saturating_sub(buffer.pos());
+        let gateway = match type_num {
+            0 => Gateway::None,
+            1 if remaining >= 4 => {
+                let mut octets = [0u8; 4];
+                octets.copy_from_slice(buffer.read_bytes(4)?);
+                Gateway::Ipv4(Ipv4Addr::from(octets))
+            }
+            2 if remaining >= 16 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(buffer.read_bytes(16)?);
+                Gateway::Ipv6(Ipv6Addr::from(octets))
+            }
+            3 if remaining > 0 => {
+                let mut name = Name::root();
+                buffer.read_qname(&mut name)?;
+                Gateway::Name(name)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(gateway))
+    }
+
+    pub(crate) fn write<S: Storage>(
+        &self,
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<(), EncodeError> {
+        match self {
+            Gateway::None => Ok(()),
+            Gateway::Ipv4(addr) => buffer.write_bytes(&addr.octets()),
+            Gateway::Ipv6(addr) => buffer.write_bytes(&addr.octets()),
+            // Both RFCs forbid compressing the gateway name.
+            Gateway::Name(name) => buffer.write_qname_uncompressed(name),
+        }
+    }
+}
+
+impl fmt::Display for Gateway {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gateway::None => f.write_str("."),
+            Gateway::Ipv4(addr) => write!(f, "{addr}"),
+            Gateway::Ipv6(addr) => write!(f, "{addr}"),
+            Gateway::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
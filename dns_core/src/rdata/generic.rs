@@ -0,0 +1,214 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    buffer::BytePacketBuffer,
+    encoding::{hex_decode, hex_encode},
+    error::{EncodeError, ParseError, PresentationError},
+    name::Name,
+    record::DnsRecord,
+    types::{DnsClass, QueryType},
+};
+
+/// Type and data of a record in the RFC 3597 generic presentation form,
+/// e.g. `TYPE12345 \# 4 0a000001`. Any record can be written this way,
+/// which is how data of types this crate does not know is printed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenericRdata {
+    pub qtype: QueryType,
+    pub data: Vec<u8>,
+}
+
+impl GenericRdata {
+    /// The wire-format data of any record.
+    pub fn from_record(record: &DnsRecord) -> Result<GenericRdata, EncodeError> {
+        if let DnsRecord::UNKNOWN { qtype, data, .. } = record {
+            return Ok(GenericRdata {
+                qtype: *qtype,
+                data: data.clone(),
+            });
+        }
+
+        let mut buffer = BytePacketBuffer::growable();
+        buffer.set_compression(false);
+        let len = record.write(&mut buffer)?;
+        let bytes = buffer.into_inner();
+
+        // Uncompressed, the owner takes its full wire length and is followed
+        // by the type, class, TTL and RDLENGTH.
+        let rdata_start = record.owner().0.wire_len() + 10;
+        Ok(GenericRdata {
+            qtype: record.qtype(),
+            data: bytes[rdata_start..len].to_vec(),
+        })
+    }
+
+    /// Decodes the data as its type's wire format, so that known types
+    /// given in the generic form come back as their typed variants.
+    pub fn to_record(
+        &self,
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+    ) -> Result<DnsRecord, ParseError> {
+        let mut buffer = BytePacketBuffer::growable();
+        let encode_failed = |_: EncodeError| ParseError::InvalidRdata {
+            offset: 0,
+            reason: "generic RDATA does not fit in a record",
+        };
+        buffer.set_compression(false);
+        buffer.write_qname(&domain).map_err(encode_failed)?;
+        buffer
+            .write_u16(self.qtype.to_num())
+            .map_err(encode_failed)?;
+        buffer.write_u16(class.to_num()).map_err(encode_failed)?;
+        buffer.write_u32(ttl).map_err(encode_failed)?;
+        let rdlength = u16::try_from(self.data.len()).map_err(|_| ParseError::InvalidRdata {
+            offset: 0,
+            reason: "generic RDATA longer than 65535 bytes",
+        })?;
+        buffer.write_u16(rdlength).map_err(encode_failed)?;
+        buffer.write_bytes(&self.data).map_err(encode_failed)?;
+
+        buffer.seek(0)?;
+        DnsRecord::read(&mut buffer)
+    }
+}
+
+impl fmt::Display for GenericRdata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TYPE{} \\# {}", self.qtype.to_num(), self.data.len())?;
+        if !self.data.is_empty() {
+            write!(f, " {}", hex_encode(&self.data))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GenericRdata {
+    type Err = PresentationError;
+
+    /// Parses `TYPEnnn \# <length> <hex>...`, also accepting a type
+    /// mnemonic; the hex may be split into several words.
+    fn from_str(s: &str) -> Result<GenericRdata, PresentationError> {
+        let mut words = s.split_whitespace();
+
+        let qtype: QueryType = words.next().unwrap_or_default().parse()?;
+
+        let data = parse_generic_data(words, s)?;
+        Ok(GenericRdata { qtype, data })
+    }
+}
+
+/// Parses the `\# <length> <hex>...` part of the generic form, checking
+/// that the length matches the data.
+pub(crate) fn parse_generic_data<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+    text: &str,
+) -> Result<Vec<u8>, PresentationError> {
+    let invalid = || PresentationError::Invalid {
+        field: "generic RDATA",
+        text: text.to_string(),
+    };
+
+    if words.next() != Some("\\#") {
+        return Err(invalid());
+    }
+    let len: usize = words
+        .next()
+        .and_then(|word| word.parse().ok())
+        .ok_or_else(invalid)?;
+    let data = hex_decode(&words.collect::<String>()).ok_or_else(invalid)?;
+    if data.len() != len {
+        return Err(invalid());
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GenericRdata;
+    use crate::{
+        error::PresentationError, name::Name, record::DnsRecord, types::DnsClass, types::QueryType,
+    };
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn unknown_types_use_the_generic_form() {
+        let rdata: GenericRdata = "TYPE12345 \\# 4 0a000001".parse().unwrap();
+        assert_eq!(QueryType::UNKNOWN(12345), rdata.qtype);
+        assert_eq!(vec![10, 0, 0, 1], rdata.data);
+        assert_eq!("TYPE12345 \\# 4 0a000001", rdata.to_string());
+
+        let split: GenericRdata = "type12345 \\# 4 0A00 0001".parse().unwrap();
+        assert_eq!(rdata, split);
+
+        let empty: GenericRdata = "TYPE62347 \\# 0".parse().unwrap();
+        assert!(empty.data.is_empty());
+        assert_eq!("TYPE62347 \\# 0", empty.to_string());
+
+        for bad in [
+            "TYPE12345 4 0a000001",
+            "TYPE12345 \\# 3 0a000001",
+            "TYPE12345 \\# 4 0a00001",
+            "TYPE12345 \\#",
+        ] {
+            assert!(
+                matches!(
+                    bad.parse::<GenericRdata>(),
+                    Err(PresentationError::Invalid { .. })
+                ),
+                "{bad}"
+            );
+        }
+        let mnemonic: GenericRdata = "A \\# 4 0a000001".parse().unwrap();
+        assert_eq!(QueryType::A, mnemonic.qtype);
+        assert!(matches!(
+            "BOGUS \\# 4 0a000001".parse::<GenericRdata>(),
+            Err(PresentationError::UnknownMnemonic { .. })
+        ));
+    }
+
+    #[test]
+    fn known_types_decode_from_the_generic_form() {
+        let domain: Name = "example.com".parse().unwrap();
+        let rdata: GenericRdata = "TYPE1 \\# 4 0a000001".parse().unwrap();
+        let record = rdata.to_record(domain.clone(), DnsClass::IN, 300).unwrap();
+        assert_eq!(
+            DnsRecord::A {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 300,
+                addr: Ipv4Addr::new(10, 0, 0, 1),
+            },
+            record
+        );
+        assert_eq!(rdata, GenericRdata::from_record(&record).unwrap());
+
+        let mx = DnsRecord::MX {
+            domain: domain.clone(),
+            class: DnsClass::IN,
+            ttl: 300,
+            priority: 10,
+            host: "mail.example.com".parse().unwrap(),
+        };
+        let generic = GenericRdata::from_record(&mx).unwrap();
+        assert_eq!(
+            "TYPE15 \\# 20 000a046d61696c076578616d706c6503636f6d00",
+            generic.to_string()
+        );
+        assert_eq!(
+            mx,
+            generic
+                .to_record(domain.clone(), DnsClass::IN, 300)
+                .unwrap()
+        );
+
+        // An AMTRELAY cut short before its type field is kept opaque.
+        let relay: GenericRdata = "TYPE260 \\# 1 0a".parse().unwrap();
+        let opaque = relay.to_record(domain.clone(), DnsClass::IN, 60).unwrap();
+        assert_eq!(relay, GenericRdata::from_record(&opaque).unwrap());
+
+        let short: GenericRdata = "TYPE1 \\# 3 0a0000".parse().unwrap();
+        assert!(short.to_record(domain, DnsClass::IN, 300).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::PresentationError;
+
+// Latitude and longitude are thousandths of an arc second offset by 2^31
+// so that the equator and prime meridian sit in the middle of the range.
+const EQUATOR: u32 = 1 << 31;
+const MILLIS_PER_DEGREE: u32 = 3_600_000;
+// Altitude is in centimetres above a base 100,000 metres below the WGS 84
+// reference spheroid.
+const ALTITUDE_BASE: i64 = 10_000_000;
+
+/// Version 0 LOC data (RFC 1876) kept in wire form so that it round-trips
+/// exactly. Accessors convert to degrees and metres.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    pub size: u8,
+    pub horizontal_precision: u8,
+    pub vertical_precision: u8,
+    pub latitude: u32,
+    pub longitude: u32,
+    pub altitude: u32,
+}
+
+impl Location {
+    /// Decodes the 16 bytes of LOC RDATA. Returns `None` for other versions
+    /// and for sizes or coordinates outside what RFC 1876 allows.
+    pub fn from_wire(data: &[u8]) -> Option<Location> {
+        let &[
+            0,
+            size,
+            horizontal_precision,
+            vertical_precision,
+            ref rest @ ..,
+        ] = data
+        else {
+            return None;
+        };
+        if rest.len() != 12 {
+            return None;
+        }
+        let field = |i: usize| u32::from_be_bytes(rest[i * 4..i * 4 + 4].try_into().unwrap());
+
+        let location = Location {
+            size,
+            horizontal_precision,
+            vertical_precision,
+            latitude: field(0),
+            longitude: field(1),
+            altitude: field(2),
+        };
+        let valid = [size, horizontal_precision, vertical_precision]
+            .iter()
+            .all(|&byte| byte >> 4 <= 9 && byte & 0x0f <= 9)
+            && EQUATOR.abs_diff(location.latitude) <= 90 * MILLIS_PER_DEGREE
+            && EQUATOR.abs_diff(location.longitude) <= 180 * MILLIS_PER_DEGREE;
+        valid.then_some(location)
+    }
+
+    pub fn to_wire(&self) -> [u8; 16] {
+        let mut data = [0; 16];
+        data[1] = self.size;
+        data[2] = self.horizontal_precision;
+        data[3] = self.vertical_precision;
+        data[4..8].copy_from_slice(&self.latitude.to_be_bytes());
+        data[8..12].copy_from_slice(&self.longitude.to_be_bytes());
+        data[12..16].copy_from_slice(&self.altitude.to_be_bytes());
+        data
+    }
+
+    /// Degrees north of the equator; negative in the southern hemisphere.
+    pub fn latitude(&self) -> f64 {
+        (self.latitude as i64 - EQUATOR as i64) as f64 / MILLIS_PER_DEGREE as f64
+    }
+
+    /// Degrees east of the prime meridian; negative in the western
+    /// hemisphere.
+    pub fn longitude(&self) -> f64 {
+        (self.longitude as i64 - EQUATOR as i64) as f64 / MILLIS_PER_DEGREE as f64
+    }
+
+    /// Metres above the WGS 84 reference spheroid.
+    pub fn altitude(&self) -> f64 {
+        (self.altitude as i64 - ALTITUDE_BASE) as f64 / 100.0
+    }
+
+    /// Diameter in metres of a sphere enclosing the described entity.
+    pub fn size(&self) -> f64 {
+        decode_precision(self.size) as f64 / 100.0
+    }
+
+    pub fn horizontal_precision(&self) -> f64 {
+        decode_precision(self.horizontal_precision) as f64 / 100.0
+    }
+
+    pub fn vertical_precision(&self) -> f64 {
+        decode_precision(self.vertical_precision) as f64 / 100.0
+    }
+}
+
+/// Centimetres encoded as a mantissa in the high nibble and a power of ten
+/// in the low nibble.
+fn decode_precision(byte: u8) -> u64 {
+    (byte >> 4) as u64 * 10u64.pow((byte & 0x0f) as u32)
+}
+
+/// The largest mantissa and exponent not above `centimetres`.
+fn encode_precision(centimetres: u64) -> u8 {
+    let mut exponent = 0;
+    while exponent < 9 && centimetres >= 10u64.pow(exponent + 1) {
+        exponent += 1;
+    }
+    let mantissa = (centimetres / 10u64.pow(exponent)).min(9);
+    (mantissa as u8) << 4 | exponent as u8
+}
+
+fn write_coordinate(
+    f: &mut fmt::Formatter<'_>,
+    value: u32,
+    positive: char,
+    negative: char,
+) -> fmt::Result {
+    let (millis, hemisphere) = if value >= EQUATOR {
+        (value - EQUATOR, positive)
+    } else {
+        (EQUATOR - value, negative)
+    };
+    let seconds = millis / 1000;
+    write!(
+        f,
+        "{} {} {}.{:03} {hemisphere}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        millis % 1000
+    )
+}
+
+fn write_precision(f: &mut fmt::Formatter<'_>, byte: u8) -> fmt::Result {
+    let centimetres = decode_precision(byte);
+    if byte & 0x0f >= 2 {
+        write!(f, "{}m", centimetres / 100)
+    } else {
+        write!(f, "0.{centimetres:02}m")
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_coordinate(f, self.latitude, 'N', 'S')?;
+        f.write_str(" ")?;
+        write_coordinate(f, self.longitude, 'E', 'W')?;
+
+        let altitude = self.altitude as i64 - ALTITUDE_BASE;
+        let sign = if altitude < 0 { "-" } else { "" };
+        let altitude = altitude.unsigned_abs();
+        write!(f, " {sign}{}.{:02}m", altitude / 100, altitude % 100)?;
+
+        for precision in [
+            self.size,
+            self.horizontal_precision,
+            self.vertical_precision,
+        ] {
+            f.write_str(" ")?;
+            write_precision(f, precision)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Location {
+    type Err = PresentationError;
+
+    /// Parses `d [m [s]] N|S d [m [s]] E|W alt[m] [size[m] [hp[m] [vp[m]]]]`
+    /// (RFC 1876 section 3). Size and precisions default to 1m, 10000m and
+    /// 10m.
+    fn from_str(s: &str) -> Result<Location, PresentationError> {
+        let invalid = || PresentationError::Invalid {
+            field: "LOC",
+            text: s.to_string(),
+        };
+        let mut tokens = s.split_ascii_whitespace().peekable();
+
+        let latitude = parse_coordinate(&mut tokens, 90, 'N', 'S').ok_or_else(invalid)?;
+        let longitude = parse_coordinate(&mut tokens, 180, 'E', 'W').ok_or_else(invalid)?;
+
+        let altitude = tokens.next().ok_or_else(invalid)?;
+        let altitude = altitude.strip_suffix(['m', 'M']).unwrap_or(altitude);
+        let (negative, magnitude) = match altitude.strip_prefix('-') {
+            Some(magnitude) => (true, magnitude),
+            None => (false, altitude),
+        };
+        let magnitude = parse_decimal(magnitude, 2)
+            .and_then(|centimetres| i64::try_from(centimetres).ok())
+            .ok_or_else(invalid)?;
+        let altitude = ALTITUDE_BASE + if negative { -magnitude } else { magnitude };
+        let altitude = u32::try_from(altitude).map_err(|_| invalid())?;
+
+        let mut precisions = [100, 1_000_000, 1000];
+        for precision in precisions.iter_mut() {
+            let Some(token) = tokens.next() else {
+                break;
+            };
+            let token = token.strip_suffix(['m', 'M']).unwrap_or(token);
+            *precision = parse_decimal(token, 2)
+                .filter(|&centimetres| centimetres <= 9_000_000_000)
+                .ok_or_else(invalid)?;
+        }
+        if tokens.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(Location {
+            size: encode_precision(precisions[0]),
+            horizontal_precision: encode_precision(precisions[1]),
+            vertical_precision: encode_precision(precisions[2]),
+            latitude,
+            longitude,
+            altitude,
+        })
+    }
+}
+
+fn parse_coordinate<'a>(
+    tokens: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>,
+    max_degrees: u32,
+    positive: char,
+    negative: char,
+) -> Option<u32> {
+    let is_hemisphere = |token: &&str| {
+        token.eq_ignore_ascii_case(&positive.to_string())
+            || token.eq_ignore_ascii_case(&negative.to_string())
+    };
+
+    let degrees: u32 = tokens.next()?.parse().ok()?;
+    let mut minutes = 0;
+    let mut millis = 0;
+    if !tokens.peek().is_some_and(is_hemisphere) {
+        minutes = tokens.next()?.parse().ok()?;
+        if !tokens.peek().is_some_and(is_hemisphere) {
+            millis = parse_decimal(tokens.next()?, 3)?;
+        }
+    }
+    let hemisphere = tokens.next().filter(is_hemisphere)?;
+
+    if degrees > max_degrees || minutes >= 60 || millis >= 60_000 {
+        return None;
+    }
+    let offset = (degrees * 60 + minutes) as u64 * 60_000 + millis;
+    if offset > (max_degrees * MILLIS_PER_DEGREE) as u64 {
+        return None;
+    }
+    let offset = offset as u32;
+    if hemisphere.eq_ignore_ascii_case(&positive.to_string()) {
+        Some(EQUATOR + offset)
+    } else {
+        Some(EQUATOR - offset)
+    }
+}
+
+/// Parses an unsigned decimal with at most `places` fractional digits,
+/// scaled to an integer count of the smallest unit.
+fn parse_decimal(text: &str, places: usize) -> Option<u64> {
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if whole.is_empty()
+        || fraction.len() > places
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let whole: u64 = whole.parse().ok()?;
+    let fraction: u64 = format!("{fraction:0<places$}").parse().ok()?;
+    whole
+        .checked_mul(10u64.pow(places as u32))?
+        .checked_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Location;
+
+    #[test]
+    fn presentation_roundtrip() {
+        let text = "52 22 23.000 N 4 53 32.000 E -2.00m 0.00m 10000m 10m";
+        let location: Location = text.parse().unwrap();
+        assert_eq!(text, location.to_string());
+        assert!((location.latitude() - 52.373055).abs() < 1e-6);
+        assert!((location.longitude() - 4.892222).abs() < 1e-6);
+        assert_eq!(-2.0, location.altitude());
+        assert_eq!(0.0, location.size());
+        assert_eq!(10000.0, location.horizontal_precision());
+        assert_eq!(10.0, location.vertical_precision());
+        assert_eq!(Some(location), Location::from_wire(&location.to_wire()));
+
+        let origin: Location = "0 N 0 E 0m".parse().unwrap();
+        assert_eq!(
+            "0 0 0.000 N 0 0 0.000 E 0.00m 1m 10000m 10m",
+            origin.to_string()
+        );
+        assert_eq!(Ok(origin), origin.to_string().parse());
+    }
+
+    #[test]
+    fn omitted_fields_take_defaults() {
+        // From RFC 1876 section 4.
+        let location: Location = "42 21 54 N 71 06 18 W -24m 30m".parse().unwrap();
+        assert_eq!(
+            "42 21 54.000 N 71 6 18.000 W -24.00m 30m 10000m 10m",
+            location.to_string()
+        );
+        assert_eq!(0x33, location.size);
+
+        let location: Location = "37 S 122 2 E 10m".parse().unwrap();
+        assert_eq!(
+            "37 0 0.000 S 122 2 0.000 E 10.00m 1m 10000m 10m",
+            location.to_string()
+        );
+        assert!((location.latitude() + 37.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        for invalid in [
+            "91 N 0 E 0m",
+            "90 0 0.001 N 0 E 0m",
+            "0 60 N 0 E 0m",
+            "0 N 181 W 0m",
+            "0 N 0 E -100000.01m",
+            "0 N 0 E 0m 90000000.01m",
+            "0 N 0 E 0.001m",
+            "0 X 0 E 0m",
+            "0 N 0 E 0m 1m 1m 1m 1m",
+        ] {
+            assert!(invalid.parse::<Location>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn wire_decoding_rejects_unsupported_data() {
+        let mut wire = "0 N 0 E 0m".parse::<Location>().unwrap().to_wire();
+        assert!(Location::from_wire(&wire).is_some());
+        assert!(Location::from_wire(&wire[..15]).is_none());
+
+        wire[1] = 0xa0;
+        assert!(Location::from_wire(&wire).is_none());
+        wire[1] = 0x12;
+        wire[0] = 1;
+        assert!(Location::from_wire(&wire).is_none());
+    }
+}
@@ -0,0 +1,15 @@
+//! Helpers that interpret the data of particular record types.
+
+pub mod apl;
+pub mod caa;
+pub mod cert;
+pub mod dname;
+pub mod dnssec;
+pub mod gateway;
+pub mod generic;
+pub mod loc;
+pub mod naptr;
+pub mod srv;
+pub mod sshfp;
+pub mod svcb;
+pub mod tlsa;
@@ -0,0 +1,683 @@
+use std::str::FromStr;
+
+use crate::{encoding::escape_bytes, error::PresentationError, record::DnsRecord};
+
+/// The substitution expression of a NAPTR regexp field (RFC 3402 section
+/// 3.2), e.g. `!^\+44(.*)$!sip:\1@example.com!i`.
+///
+/// The expression is a POSIX extended regular expression supporting
+/// alternation, groups, bracket expressions with ranges and `[:class:]`
+/// names, anchors and the `* + ? {m,n}` quantifiers. The expression is
+/// compiled to an NFA that is simulated in time linear in the input.
+/// Quantifiers are greedy and earlier alternatives preferred, so ambiguous
+/// patterns take the leftmost-first rather than the leftmost-longest match.
+/// Expressions whose counted repetitions expand past a fixed size are
+/// rejected.
+#[derive(Clone, Debug)]
+pub struct SubstitutionExpression {
+    program: Vec<Inst>,
+    groups: usize,
+    replacement: Vec<Piece>,
+    case_insensitive: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Literal(char),
+    Any,
+    Class {
+        items: Vec<ClassItem>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Group(usize, Vec<Vec<Node>>),
+    Repeat(Box<Node>, u32, Option<u32>),
+}
+
+// NFA instructions. `Split` prefers its first target.
+#[derive(Clone, Debug)]
+enum Inst {
+    Literal(char),
+    Any,
+    Class {
+        items: Vec<ClassItem>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Save(usize),
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+// Limit on compiled instructions, which bounds the work per input character.
+const MAX_PROGRAM_LEN: usize = 4096;
+
+#[derive(Clone, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Named(fn(&char) -> bool),
+}
+
+#[derive(Clone, Debug)]
+enum Piece {
+    Text(String),
+    Backref(usize),
+}
+
+impl SubstitutionExpression {
+    /// The expression of a NAPTR record, or `None` for other record types
+    /// and for NAPTR records whose regexp field is empty. A field that is
+    /// not UTF-8 is invalid.
+    pub fn from_record(
+        record: &DnsRecord,
+    ) -> Option<Result<SubstitutionExpression, PresentationError>> {
+        match record {
+            DnsRecord::NAPTR { regexp, .. } if !regexp.is_empty() => Some(
+                std::str::from_utf8(regexp)
+                    .map_err(|_| PresentationError::Invalid {
+                        field: "NAPTR regexp",
+                        text: escape_bytes(regexp),
+                    })
+                    .and_then(str::parse),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Applies the expression to `input`, replacing the leftmost match with
+    /// the substitution. Returns `None` when the expression does not match.
+    pub fn apply(&self, input: &str) -> Option<String> {
+        let chars: Vec<char> = input.chars().collect();
+        let matcher = Matcher {
+            program: &self.program,
+            input: &chars,
+            case_insensitive: self.case_insensitive,
+        };
+        let slots = matcher.run(2 * (self.groups + 1))?;
+        let (start, end) = (slots[0]?, slots[1]?);
+
+        let mut out: String = chars[..start].iter().collect();
+        for piece in &self.replacement {
+            match piece {
+                Piece::Text(text) => out.push_str(text),
+                Piece::Backref(index) => {
+                    if let (Some(from), Some(to)) = (slots[2 * index], slots[2 * index + 1]) {
+                        out.extend(&chars[from..to]);
+                    }
+                }
+            }
+        }
+        out.extend(&chars[end..]);
+        Some(out)
+    }
+}
+
+impl FromStr for SubstitutionExpression {
+    type Err = PresentationError;
+
+    fn from_str(s: &str) -> Result<SubstitutionExpression, PresentationError> {
+        let invalid = || PresentationError::Invalid {
+            field: "NAPTR regexp",
+            text: s.to_string(),
+        };
+        // The field is a character-string, which also bounds how deeply the
+        // parser and compiler recurse.
+        if s.len() > 255 {
+            return Err(invalid());
+        }
+
+        let mut chars = s.chars();
+        let delim = chars.next().ok_or_else(invalid)?;
+        if delim == '\\' || delim == 'i' || ('1'..='9').contains(&delim) {
+            return Err(invalid());
+        }
+
+        // Split into the ERE, the replacement and the flags. An escaped
+        // delimiter is literal in both parts.
+        let mut parts = vec![String::new()];
+        while let Some(c) = chars.next() {
+            if c == delim {
+                parts.push(String::new());
+                continue;
+            }
+            let part = parts.last_mut().unwrap();
+            part.push(c);
+            if c == '\\' {
+                part.push(chars.next().ok_or_else(invalid)?);
+            }
+        }
+        let [ere, replacement, flags] = &parts[..] else {
+            return Err(invalid());
+        };
+        let case_insensitive = match flags.as_str() {
+            "" => false,
+            "i" => true,
+            _ => return Err(invalid()),
+        };
+
+        let mut parser = Parser {
+            chars: ere.chars().collect(),
+            pos: 0,
+            groups: 0,
+        };
+        let pattern = parser.alternation().ok_or_else(invalid)?;
+        if parser.pos != parser.chars.len() {
+            return Err(invalid());
+        }
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        compiler
+            .node(&Node::Group(0, pattern))
+            .and_then(|()| compiler.push(Inst::Match))
+            .ok_or_else(invalid)?;
+
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut replacement = replacement.chars();
+        while let Some(c) = replacement.next() {
+            if c != '\\' {
+                text.push(c);
+                continue;
+            }
+            let escaped = replacement.next().ok_or_else(invalid)?;
+            match escaped.to_digit(10) {
+                Some(index @ 1..=9) => {
+                    if index as usize > parser.groups {
+                        return Err(invalid());
+                    }
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    pieces.push(Piece::Backref(index as usize));
+                }
+                _ => text.push(escaped),
+            }
+        }
+        pieces.push(Piece::Text(text));
+
+        Ok(SubstitutionExpression {
+            program: compiler.program,
+            groups: parser.groups,
+            replacement: pieces,
+            case_insensitive,
+        })
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    groups: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn alternation(&mut self) -> Option<Vec<Vec<Node>>> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Some(alternatives)
+    }
+
+    fn sequence(&mut self) -> Option<Vec<Node>> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Some(nodes)
+    }
+
+    fn atom(&mut self) -> Option<Node> {
+        let node = match self.next()? {
+            '(' => {
+                self.groups += 1;
+                let index = self.groups;
+                let alternatives = self.alternation()?;
+                if self.next()? != ')' {
+                    return None;
+                }
+                Node::Group(index, alternatives)
+            }
+            '[' => self.class()?,
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '\\' => Node::Literal(self.next()?),
+            '*' | '+' | '?' | '{' => return None,
+            c => Node::Literal(c),
+        };
+        Some(node)
+    }
+
+    fn quantified(&mut self, mut node: Node) -> Option<Node> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => {
+                    self.pos += 1;
+                    let min = self.number()?;
+                    let max = if self.peek() == Some(',') {
+                        self.pos += 1;
+                        if self.peek() == Some('}') {
+                            None
+                        } else {
+                            Some(self.number()?)
+                        }
+                    } else {
+                        Some(min)
+                    };
+                    if self.next()? != '}' || max.is_some_and(|max| max < min) {
+                        return None;
+                    }
+                    node = Node::Repeat(Box::new(node), min, max);
+                    continue;
+                }
+                _ => return Some(node),
+            };
+            self.pos += 1;
+            node = Node::Repeat(Box::new(node), min, max);
+        }
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().ok().filter(|&n| n <= 255)
+    }
+
+    fn class(&mut self) -> Option<Node> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.next()?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+
+            if c == '[' && self.peek() == Some(':') {
+                let rest: String = self.chars[self.pos + 1..].iter().collect();
+                let name = rest.split(":]").next()?;
+                let test: fn(&char) -> bool = match name {
+                    "alnum" => char::is_ascii_alphanumeric,
+                    "alpha" => char::is_ascii_alphabetic,
+                    "digit" => char::is_ascii_digit,
+                    "lower" => char::is_ascii_lowercase,
+                    "upper" => char::is_ascii_uppercase,
+                    "space" => |c: &char| c.is_ascii_whitespace() || *c == '\x0b',
+                    "xdigit" => char::is_ascii_hexdigit,
+                    "punct" => char::is_ascii_punctuation,
+                    _ => return None,
+                };
+                self.pos += name.chars().count() + 3;
+                items.push(ClassItem::Named(test));
+                continue;
+            }
+
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&e| e != ']') {
+                self.pos += 1;
+                let end = self.next()?;
+                if end < c {
+                    return None;
+                }
+                items.push(ClassItem::Range(c, end));
+            } else {
+                items.push(ClassItem::Range(c, c));
+            }
+        }
+        Some(Node::Class { items, negated })
+    }
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    /// Appends an instruction and returns its index, or `None` once the
+    /// program is too large.
+    fn push(&mut self, inst: Inst) -> Option<usize> {
+        if self.program.len() >= MAX_PROGRAM_LEN {
+            return None;
+        }
+        self.program.push(inst);
+        Some(self.program.len() - 1)
+    }
+
+    fn node(&mut self, node: &Node) -> Option<()> {
+        match node {
+            Node::Literal(c) => {
+                self.push(Inst::Literal(*c))?;
+            }
+            Node::Any => {
+                self.push(Inst::Any)?;
+            }
+            Node::Class { items, negated } => {
+                self.push(Inst::Class {
+                    items: items.clone(),
+                    negated: *negated,
+                })?;
+            }
+            Node::Start => {
+                self.push(Inst::Start)?;
+            }
+            Node::End => {
+                self.push(Inst::End)?;
+            }
+            Node::Group(index, alternatives) => {
+                self.push(Inst::Save(2 * index))?;
+                self.alternation(alternatives)?;
+                self.push(Inst::Save(2 * index + 1))?;
+            }
+            Node::Repeat(inner, min, max) => {
+                for _ in 0..*min {
+                    self.node(inner)?;
+                }
+                // Each optional copy may be skipped to the end; an unbounded
+                // repeat loops back to its split instead.
+                let mut splits = Vec::new();
+                for _ in *min..max.unwrap_or(*min + 1) {
+                    splits.push(self.push(Inst::Split(0, 0))?);
+                    self.node(inner)?;
+                }
+                if max.is_none() {
+                    self.push(Inst::Jump(splits[0]))?;
+                }
+                let end = self.program.len();
+                for split in splits {
+                    self.program[split] = Inst::Split(split + 1, end);
+                }
+            }
+        }
+        Some(())
+    }
+
+    fn alternation(&mut self, alternatives: &[Vec<Node>]) -> Option<()> {
+        let mut jumps = Vec::new();
+        for (i, alternative) in alternatives.iter().enumerate() {
+            let split = if i + 1 < alternatives.len() {
+                Some(self.push(Inst::Split(0, 0))?)
+            } else {
+                None
+            };
+            for node in alternative {
+                self.node(node)?;
+            }
+            if let Some(split) = split {
+                jumps.push(self.push(Inst::Jump(0))?);
+                self.program[split] = Inst::Split(split + 1, self.program.len());
+            }
+        }
+        let end = self.program.len();
+        for jump in jumps {
+            self.program[jump] = Inst::Jump(end);
+        }
+        Some(())
+    }
+}
+
+type Slots = Vec<Option<usize>>;
+
+struct Thread {
+    pc: usize,
+    slots: Slots,
+}
+
+struct Matcher<'a> {
+    program: &'a [Inst],
+    input: &'a [char],
+    case_insensitive: bool,
+}
+
+impl Matcher<'_> {
+    fn char_matches(&self, pos: usize, test: impl Fn(char) -> bool) -> bool {
+        let Some(&c) = self.input.get(pos) else {
+            return false;
+        };
+        if self.case_insensitive {
+            test(c.to_ascii_lowercase()) || test(c.to_ascii_uppercase())
+        } else {
+            test(c)
+        }
+    }
+
+    /// Finds the leftmost-first match, returning the capture positions.
+    /// Threads are kept in priority order and each instruction is run by at
+    /// most one thread per position.
+    fn run(&self, slot_count: usize) -> Option<Slots> {
+        let mut current = Vec::new();
+        let mut seen = vec![false; self.program.len()];
+        let mut matched = None;
+
+        for pos in 0..=self.input.len() {
+            // A match starting here ranks below every earlier start.
+            if matched.is_none() {
+                self.add_thread(&mut current, &mut seen, 0, vec![None; slot_count], pos);
+            }
+            if current.is_empty() && matched.is_some() {
+                break;
+            }
+
+            let mut next = Vec::new();
+            let mut next_seen = vec![false; self.program.len()];
+            for thread in current.drain(..) {
+                let step = match &self.program[thread.pc] {
+                    Inst::Match => {
+                        // Lower-priority threads can no longer win.
+                        matched = Some(thread.slots);
+                        break;
+                    }
+                    Inst::Literal(expected) => self.char_matches(pos, |c| c == *expected),
+                    Inst::Any => pos < self.input.len(),
+                    Inst::Class { items, negated } => {
+                        let in_class = |c: char| {
+                            items.iter().any(|item| match item {
+                                ClassItem::Range(low, high) => (*low..=*high).contains(&c),
+                                ClassItem::Named(test) => test(&c),
+                            })
+                        };
+                        pos < self.input.len() && self.char_matches(pos, in_class) != *negated
+                    }
+                    _ => unreachable!("threads wait only on input or a match"),
+                };
+                if step {
+                    self.add_thread(
+                        &mut next,
+                        &mut next_seen,
+                        thread.pc + 1,
+                        thread.slots,
+                        pos + 1,
+                    );
+                }
+            }
+            current = next;
+            seen = next_seen;
+        }
+        matched
+    }
+
+    /// Follows the empty transitions from `pc` at `pos`, queueing a thread
+    /// at every instruction that consumes input or matches, in priority
+    /// order.
+    fn add_thread(
+        &self,
+        threads: &mut Vec<Thread>,
+        seen: &mut [bool],
+        pc: usize,
+        slots: Slots,
+        pos: usize,
+    ) {
+        let mut stack = vec![(pc, slots)];
+        while let Some((pc, mut slots)) = stack.pop() {
+            if std::mem::replace(&mut seen[pc], true) {
+                continue;
+            }
+            match &self.program[pc] {
+                Inst::Jump(target) => stack.push((*target, slots)),
+                Inst::Split(first, second) => {
+                    stack.push((*second, slots.clone()));
+                    stack.push((*first, slots));
+                }
+                Inst::Save(slot) => {
+                    slots[*slot] = Some(pos);
+                    stack.push((pc + 1, slots));
+                }
+                Inst::Start => {
+                    if pos == 0 {
+                        stack.push((pc + 1, slots));
+                    }
+                }
+                Inst::End => {
+                    if pos == self.input.len() {
+                        stack.push((pc + 1, slots));
+                    }
+                }
+                _ => threads.push(Thread { pc, slots }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SubstitutionExpression;
+    use crate::{name::Name, record::DnsRecord, types::DnsClass};
+
+    fn apply(expression: &str, input: &str) -> Option<String> {
+        expression
+            .parse::<SubstitutionExpression>()
+            .unwrap()
+            .apply(input)
+    }
+
+    #[test]
+    fn applies_enum_style_expressions() {
+        assert_eq!(
+            Some("sip:info@example.com".into()),
+            apply("!^.*$!sip:info@example.com!", "+441632960083")
+        );
+        assert_eq!(
+            Some("sip:1632960083@example.com".into()),
+            apply(r"!^\+44(.*)$!sip:\1@example.com!", "+441632960083")
+        );
+        assert_eq!(
+            Some("mailto:user@example.com".into()),
+            apply(
+                r"/^\+1([0-9]{3})[0-9]{7}$/mailto:user@example.com/",
+                "+15551234567"
+            )
+        );
+        assert_eq!(None, apply(r"!^\+44(.*)$!\1!", "+3312345"));
+    }
+
+    #[test]
+    fn replaces_only_the_leftmost_match() {
+        assert_eq!(Some("aXcbb".into()), apply("/b+/X/", "abbbcbb"));
+        assert_eq!(Some("x-y".into()), apply("/[[:digit:]]+/-/", "x42y"));
+        assert_eq!(Some("BAR".into()), apply(r"!^(foo|bar)baz$!\1!i", "BARBAZ"));
+        assert_eq!(None, apply(r"!^(foo|bar)baz$!\1!", "BARBAZ"));
+        assert_eq!(Some("/#".into()), apply(r"#(a?)*b#/\#\1#", "b"));
+        assert_eq!(
+            Some("bcd-a".into()),
+            apply(r"!(a|ab)(c|bcd)!\2-\1!", "abcd")
+        );
+    }
+
+    #[test]
+    fn matching_stays_bounded_on_pathological_expressions() {
+        // Expands to far more instructions than the limit allows.
+        assert!(
+            "!((((x?){255}){255}){255})!y!"
+                .parse::<SubstitutionExpression>()
+                .is_err()
+        );
+
+        // Exponential for a backtracking matcher.
+        let nested: SubstitutionExpression = "!(a*)*(a*)*(a*)*b!y!".parse().unwrap();
+        assert_eq!(None, nested.apply(&"a".repeat(30)));
+        assert_eq!(None, nested.apply(&"a".repeat(10_000)));
+        assert_eq!(
+            Some("y".into()),
+            nested.apply(&format!("{}b", "a".repeat(30)))
+        );
+
+        assert_eq!(Some("Xa".into()), apply("/a{1,3}/X/", "aaaa"));
+        assert_eq!(Some("-X-".into()), apply("/a{2}b?/X/", "-aa-"));
+    }
+
+    #[test]
+    fn reads_the_expression_of_naptr_records() {
+        let naptr = |regexp: &[u8]| DnsRecord::NAPTR {
+            domain: "4.3.2.1.e164.arpa".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 60,
+            order: 100,
+            preference: 10,
+            flags: "u".into(),
+            services: "E2U+sip".into(),
+            regexp: regexp.to_vec(),
+            replacement: Name::root(),
+        };
+
+        let expression = SubstitutionExpression::from_record(&naptr(b"!^.*$!sip:a@b!"));
+        assert_eq!(
+            Some("sip:a@b".into()),
+            expression.unwrap().unwrap().apply("+1234")
+        );
+        assert!(SubstitutionExpression::from_record(&naptr(b"")).is_none());
+        assert!(matches!(
+            SubstitutionExpression::from_record(&naptr(b"!^.*$!\xff!")),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for invalid in [
+            "",
+            "!abc!def",
+            "!a!b!x",
+            r"\a\b\",
+            "1a1b1",
+            "!(a!b!",
+            "!a{3,1}!b!",
+            "!*a!b!",
+            r"!a!\1!",
+            "![[:bogus:]]!b!",
+        ] {
+            assert!(
+                invalid.parse::<SubstitutionExpression>().is_err(),
+                "{invalid}"
+            );
+        }
+        let nested = format!("!{}a{}!b!", "(".repeat(200), ")".repeat(200));
+        assert!(nested.parse::<SubstitutionExpression>().is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::record::DnsRecord;
+
+/// Orders SRV records for connection attempts as described in RFC 2782:
+/// lowest priority first and, within a priority, a weighted random pick.
+/// `random(total)` must return a uniformly chosen value in `0..=total`.
+/// Records of other types are skipped.
+pub fn selection_order<'a, I>(records: I, mut random: impl FnMut(u32) -> u32) -> Vec<&'a DnsRecord>
+where
+    I: IntoIterator<Item = &'a DnsRecord>,
+{
+    let mut srvs: Vec<(u16, u16, &DnsRecord)> = records
+        .into_iter()
+        .filter_map(|record| match record {
+            DnsRecord::SRV {
+                priority, weight, ..
+            } => Some((*priority, *weight, record)),
+            _ => None,
+        })
+        .collect();
+    // Zero-weight records go first so they are only picked by a zero draw.
+    srvs.sort_by_key(|(priority, weight, _)| (*priority, *weight != 0));
+
+    let mut ordered = Vec::with_capacity(srvs.len());
+    for group in srvs.chunk_by(|a, b| a.0 == b.0) {
+        let mut group = group.to_vec();
+        while !group.is_empty() {
+            let total: u32 = group.iter().map(|(_, weight, _)| *weight as u32).sum();
+            let pick = random(total).min(total);
+
+            let mut running = 0;
+            let index = group
+                .iter()
+                .position(|(_, weight, _)| {
+                    running += *weight as u32;
+                    running >= pick
+                })
+                .unwrap_or(0);
+            ordered.push(group.remove(index).2);
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::selection_order;
+    use crate::{record::DnsRecord, types::DnsClass};
+
+    fn srv(priority: u16, weight: u16, target: &str) -> DnsRecord {
+        DnsRecord::SRV {
+            domain: "_http._tcp.example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 60,
+            priority,
+            weight,
+            port: 80,
+            target: target.parse().unwrap(),
+        }
+    }
+
+    fn targets(ordered: Vec<&DnsRecord>) -> Vec<String> {
+        ordered
+            .into_iter()
+            .map(|record| match record {
+                DnsRecord::SRV { target, .. } => target.to_string(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn priorities_are_tried_in_ascending_order() {
+        let records = [
+            srv(20, 0, "backup.example."),
+            srv(10, 5, "a.example."),
+            DnsRecord::A {
+                domain: "a.example".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 60,
+                addr: [192, 0, 2, 1].into(),
+            },
+            srv(10, 0, "zero.example."),
+            srv(10, 15, "b.example."),
+        ];
+
+        // Always drawing zero picks the zero-weight record, then the first
+        // remaining one.
+        assert_eq!(
+            vec![
+                "zero.example.",
+                "a.example.",
+                "b.example.",
+                "backup.example."
+            ],
+            targets(selection_order(&records, |_| 0))
+        );
+        // Always drawing the total picks the last remaining record.
+        assert_eq!(
+            vec![
+                "b.example.",
+                "a.example.",
+                "zero.example.",
+                "backup.example."
+            ],
+            targets(selection_order(&records, |total| total))
+        );
+    }
+
+    #[test]
+    fn weights_bias_the_first_choice() {
+        let records = [srv(0, 1, "light.example."), srv(0, 3, "heavy.example.")];
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut random = |total: u32| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (total as u64 + 1)) as u32
+        };
+
+        let heavy_first = (0..4000)
+            .filter(|_| targets(selection_order(&records, &mut random))[0] == "heavy.example.")
+            .count();
+        // Drawing from 0..=4 as RFC 2782 specifies, 3 of the 5 values land
+        // on the heavy record.
+        assert!((2200..2600).contains(&heavy_first), "{heavy_first}");
+    }
+}
@@ -0,0 +1,60 @@
+//! Field values of SSHFP records (RFC 4255, RFC 6594, RFC 7479, RFC 8709).
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum SshfpAlgorithm {
+    UNKNOWN(u8),
+    RSA,     // 1
+    DSA,     // 2
+    ECDSA,   // 3
+    ED25519, // 4
+    ED448,   // 6
+}
+
+impl SshfpAlgorithm {
+    pub fn to_num(&self) -> u8 {
+        match *self {
+            SshfpAlgorithm::UNKNOWN(x) => x,
+            SshfpAlgorithm::RSA => 1,
+            SshfpAlgorithm::DSA => 2,
+            SshfpAlgorithm::ECDSA => 3,
+            SshfpAlgorithm::ED25519 => 4,
+            SshfpAlgorithm::ED448 => 6,
+        }
+    }
+
+    pub fn from_num(num: u8) -> SshfpAlgorithm {
+        match num {
+            1 => SshfpAlgorithm::RSA,
+            2 => SshfpAlgorithm::DSA,
+            3 => SshfpAlgorithm::ECDSA,
+            4 => SshfpAlgorithm::ED25519,
+            6 => SshfpAlgorithm::ED448,
+            _ => SshfpAlgorithm::UNKNOWN(num),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum SshfpFingerprintType {
+    UNKNOWN(u8),
+    SHA1,   // 1
+    SHA256, // 2
+}
+
+impl SshfpFingerprintType {
+    pub fn to_num(&self) -> u8 {
+        match *self {
+            SshfpFingerprintType::UNKNOWN(x) => x,
+            SshfpFingerprintType::SHA1 => 1,
+            SshfpFingerprintType::SHA256 => 2,
+        }
+    }
+
+    pub fn from_num(num: u8) -> SshfpFingerprintType {
+        match num {
+            1 => SshfpFingerprintType::SHA1,
+            2 => SshfpFingerprintType::SHA256,
+            _ => SshfpFingerprintType::UNKNOWN(num),
+        }
+    }
+}
@@ -0,0 +1,663 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    encoding::{base64_decode, base64_encode, escape_bytes, unescape_bytes},
+    error::{EncodeError, ParseError, PresentationError},
+};
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum SvcParamKey {
+    UNKNOWN(u16),
+    MANDATORY,     // 0
+    ALPN,          // 1
+    NoDefaultAlpn, // 2
+    PORT,          // 3
+    IPV4HINT,      // 4
+    ECH,           // 5
+    IPV6HINT,      // 6
+}
+
+impl SvcParamKey {
+    pub fn to_num(&self) -> u16 {
+        match *self {
+            SvcParamKey::UNKNOWN(x) => x,
+            SvcParamKey::MANDATORY => 0,
+            SvcParamKey::ALPN => 1,
+            SvcParamKey::NoDefaultAlpn => 2,
+            SvcParamKey::PORT => 3,
+            SvcParamKey::IPV4HINT => 4,
+            SvcParamKey::ECH => 5,
+            SvcParamKey::IPV6HINT => 6,
+        }
+    }
+
+    pub fn from_num(num: u16) -> SvcParamKey {
+        match num {
+            0 => SvcParamKey::MANDATORY,
+            1 => SvcParamKey::ALPN,
+            2 => SvcParamKey::NoDefaultAlpn,
+            3 => SvcParamKey::PORT,
+            4 => SvcParamKey::IPV4HINT,
+            5 => SvcParamKey::ECH,
+            6 => SvcParamKey::IPV6HINT,
+            _ => SvcParamKey::UNKNOWN(num),
+        }
+    }
+}
+
+impl fmt::Display for SvcParamKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SvcParamKey::UNKNOWN(num) => write!(f, "key{num}"),
+            SvcParamKey::MANDATORY => f.write_str("mandatory"),
+            SvcParamKey::ALPN => f.write_str("alpn"),
+            SvcParamKey::NoDefaultAlpn => f.write_str("no-default-alpn"),
+            SvcParamKey::PORT => f.write_str("port"),
+            SvcParamKey::IPV4HINT => f.write_str("ipv4hint"),
+            SvcParamKey::ECH => f.write_str("ech"),
+            SvcParamKey::IPV6HINT => f.write_str("ipv6hint"),
+        }
+    }
+}
+
+impl FromStr for SvcParamKey {
+    type Err = PresentationError;
+
+    fn from_str(s: &str) -> Result<SvcParamKey, PresentationError> {
+        let key = match s {
+            "mandatory" => SvcParamKey::MANDATORY,
+            "alpn" => SvcParamKey::ALPN,
+            "no-default-alpn" => SvcParamKey::NoDefaultAlpn,
+            "port" => SvcParamKey::PORT,
+            "ipv4hint" => SvcParamKey::IPV4HINT,
+            "ech" => SvcParamKey::ECH,
+            "ipv6hint" => SvcParamKey::IPV6HINT,
+            _ => s
+                .strip_prefix("key")
+                .filter(|num| !num.is_empty() && num.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|num| num.parse::<u16>().ok())
+                // key65535 is reserved
+                .filter(|&num| num != u16::MAX)
+                .map(SvcParamKey::from_num)
+                .ok_or_else(|| PresentationError::UnknownMnemonic {
+                    text: s.to_string(),
+                })?,
+        };
+        Ok(key)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SvcParam {
+    UNKNOWN(u16, Vec<u8>),
+    MANDATORY(Vec<SvcParamKey>),
+    ALPN(Vec<Vec<u8>>),
+    NoDefaultAlpn,
+    PORT(u16),
+    IPV4HINT(Vec<Ipv4Addr>),
+    /// ECHConfigList, base64 in presentation format.
+    ECH(Vec<u8>),
+    IPV6HINT(Vec<Ipv6Addr>),
+}
+
+impl SvcParam {
+    pub fn key(&self) -> SvcParamKey {
+        match self {
+            SvcParam::UNKNOWN(num, _) => SvcParamKey::from_num(*num),
+            SvcParam::MANDATORY(_) => SvcParamKey::MANDATORY,
+            SvcParam::ALPN(_) => SvcParamKey::ALPN,
+            SvcParam::NoDefaultAlpn => SvcParamKey::NoDefaultAlpn,
+            SvcParam::PORT(_) => SvcParamKey::PORT,
+            SvcParam::IPV4HINT(_) => SvcParamKey::IPV4HINT,
+            SvcParam::ECH(_) => SvcParamKey::ECH,
+            SvcParam::IPV6HINT(_) => SvcParamKey::IPV6HINT,
+        }
+    }
+
+    fn decode(key: u16, data: &[u8]) -> Option<SvcParam> {
+        let param = match SvcParamKey::from_num(key) {
+            SvcParamKey::MANDATORY => {
+                if data.is_empty() || data.len() % 2 != 0 {
+                    return None;
+                }
+                let keys: Vec<SvcParamKey> = data
+                    .chunks(2)
+                    .map(|pair| SvcParamKey::from_num(u16::from_be_bytes([pair[0], pair[1]])))
+                    .collect();
+                if !keys.windows(2).all(|w| w[0].to_num() < w[1].to_num()) {
+                    return None;
+                }
+                SvcParam::MANDATORY(keys)
+            }
+            SvcParamKey::ALPN => {
+                let mut ids = Vec::new();
+                let mut rest = data;
+                while let Some((&len, tail)) = rest.split_first() {
+                    if len == 0 || tail.len() < len as usize {
+                        return None;
+                    }
+                    let (id, tail) = tail.split_at(len as usize);
+                    ids.push(id.to_vec());
+                    rest = tail;
+                }
+                if ids.is_empty() {
+                    return None;
+                }
+                SvcParam::ALPN(ids)
+            }
+            SvcParamKey::NoDefaultAlpn if data.is_empty() => SvcParam::NoDefaultAlpn,
+            SvcParamKey::PORT => SvcParam::PORT(u16::from_be_bytes(data.try_into().ok()?)),
+            SvcParamKey::IPV4HINT if !data.is_empty() && data.len() % 4 == 0 => SvcParam::IPV4HINT(
+                data.chunks(4)
+                    .map(|octets| Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]))
+                    .collect(),
+            ),
+            SvcParamKey::ECH => SvcParam::ECH(data.to_vec()),
+            SvcParamKey::IPV6HINT if !data.is_empty() && data.len() % 16 == 0 => {
+                SvcParam::IPV6HINT(
+                    data.chunks(16)
+                        .map(|octets| Ipv6Addr::from(<[u8; 16]>::try_from(octets).unwrap()))
+                        .collect(),
+                )
+            }
+            SvcParamKey::UNKNOWN(num) => SvcParam::UNKNOWN(num, data.to_vec()),
+            _ => return None,
+        };
+        Some(param)
+    }
+
+    fn encode<S: Storage>(&self, buffer: &mut BytePacketBuffer<S>) -> Result<(), EncodeError> {
+        let offset = buffer.pos();
+        let invalid = |reason| EncodeError::InvalidRdata { offset, reason };
+        match self {
+            SvcParam::UNKNOWN(_, data) | SvcParam::ECH(data) => buffer.write_bytes(data),
+            SvcParam::MANDATORY(keys) => {
+                let mut nums: Vec<u16> = keys.iter().map(SvcParamKey::to_num).collect();
+                nums.sort_unstable();
+                if nums.is_empty() || nums.windows(2).any(|w| w[0] == w[1]) {
+                    return Err(invalid("mandatory keys must be unique and non-empty"));
+                }
+                for num in nums {
+                    buffer.write_u16(num)?;
+                }
+                Ok(())
+            }
+            SvcParam::ALPN(ids) => {
+                if ids.is_empty() || ids.iter().any(Vec::is_empty) {
+                    return Err(invalid("alpn ids must be non-empty"));
+                }
+                for id in ids {
+                    buffer.write_character_string(id)?;
+                }
+                Ok(())
+            }
+            SvcParam::NoDefaultAlpn => Ok(()),
+            SvcParam::PORT(port) => buffer.write_u16(*port),
+            SvcParam::IPV4HINT(addrs) => {
+                if addrs.is_empty() {
+                    return Err(invalid("ipv4hint must list an address"));
+                }
+                for addr in addrs {
+                    buffer.write_bytes(&addr.octets())?;
+                }
+                Ok(())
+            }
+            SvcParam::IPV6HINT(addrs) => {
+                if addrs.is_empty() {
+                    return Err(invalid("ipv6hint must list an address"));
+                }
+                for addr in addrs {
+                    buffer.write_bytes(&addr.octets())?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn parse(key: SvcParamKey, value: Option<&[u8]>) -> Result<SvcParam, PresentationError> {
+        let text = value.map(|v| String::from_utf8_lossy(v).to_string());
+        let invalid = || PresentationError::Invalid {
+            field: "SvcParam value",
+            text: match &text {
+                Some(text) => format!("{key}={text}"),
+                None => key.to_string(),
+            },
+        };
+
+        let param = match (key, value) {
+            (SvcParamKey::NoDefaultAlpn, None) => SvcParam::NoDefaultAlpn,
+            (SvcParamKey::UNKNOWN(num), value) => {
+                SvcParam::UNKNOWN(num, value.unwrap_or_default().to_vec())
+            }
+            (SvcParamKey::NoDefaultAlpn, Some(_)) | (_, None) => return Err(invalid()),
+            (SvcParamKey::ECH, Some(value)) => SvcParam::ECH(
+                std::str::from_utf8(value)
+                    .ok()
+                    .and_then(base64_decode)
+                    .ok_or_else(invalid)?,
+            ),
+            (SvcParamKey::ALPN, Some(value)) => {
+                let ids = split_value_list(value);
+                if ids.iter().any(Vec::is_empty) {
+                    return Err(invalid());
+                }
+                SvcParam::ALPN(ids)
+            }
+            (key, Some(value)) => {
+                let text = std::str::from_utf8(value).map_err(|_| invalid())?;
+                let items = text.split(',');
+                match key {
+                    SvcParamKey::MANDATORY => {
+                        // Any order is accepted; keep the wire order.
+                        let mut keys = items
+                            .map(|item| item.parse().map_err(|_| invalid()))
+                            .collect::<Result<Vec<SvcParamKey>, _>>()?;
+                        keys.sort_by_key(SvcParamKey::to_num);
+                        SvcParam::MANDATORY(keys)
+                    }
+                    SvcParamKey::PORT => SvcParam::PORT(text.parse().map_err(|_| invalid())?),
+                    SvcParamKey::IPV4HINT => SvcParam::IPV4HINT(
+                        items
+                            .map(|item| item.parse().map_err(|_| invalid()))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    SvcParamKey::IPV6HINT => SvcParam::IPV6HINT(
+                        items
+                            .map(|item| item.parse().map_err(|_| invalid()))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    _ => unreachable!(),
+                }
+            }
+        };
+        Ok(param)
+    }
+}
+
+// Splits an RFC 9460 value-list on unescaped commas; `\,` and `\\` are
+// the escapes left after character-string decoding.
+fn split_value_list(value: &[u8]) -> Vec<Vec<u8>> {
+    let mut items = vec![Vec::new()];
+    let mut bytes = value.iter();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'\\' => {
+                if let Some(&escaped) = bytes.next() {
+                    items.last_mut().unwrap().push(escaped);
+                }
+            }
+            b',' => items.push(Vec::new()),
+            _ => items.last_mut().unwrap().push(byte),
+        }
+    }
+    items
+}
+
+impl fmt::Display for SvcParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = self.key();
+        match self {
+            SvcParam::NoDefaultAlpn => write!(f, "{key}"),
+            SvcParam::UNKNOWN(_, data) => write!(f, "{key}=\"{}\"", escape_bytes(data)),
+            SvcParam::MANDATORY(keys) => {
+                let keys: Vec<String> = keys.iter().map(SvcParamKey::to_string).collect();
+                write!(f, "{key}={}", keys.join(","))
+            }
+            SvcParam::ALPN(ids) => {
+                let ids: Vec<Vec<u8>> = ids
+                    .iter()
+                    .map(|id| {
+                        id.iter()
+                            .flat_map(|&b| match b {
+                                b',' | b'\\' => vec![b'\\', b],
+                                _ => vec![b],
+                            })
+                            .collect()
+                    })
+                    .collect();
+                write!(f, "{key}=\"{}\"", escape_bytes(&ids.join(&b',')))
+            }
+            SvcParam::PORT(port) => write!(f, "{key}={port}"),
+            SvcParam::IPV4HINT(addrs) => {
+                let addrs: Vec<String> = addrs.iter().map(Ipv4Addr::to_string).collect();
+                write!(f, "{key}={}", addrs.join(","))
+            }
+            SvcParam::ECH(data) => write!(f, "{key}={}", base64_encode(data)),
+            SvcParam::IPV6HINT(addrs) => {
+                let addrs: Vec<String> = addrs.iter().map(Ipv6Addr::to_string).collect();
+                write!(f, "{key}={}", addrs.join(","))
+            }
+        }
+    }
+}
+
+/// The SvcParams of an SVCB or HTTPS record (RFC 9460), kept in key order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SvcParams {
+    params: BTreeMap<u16, SvcParam>,
+}
+
+impl SvcParams {
+    pub fn new() -> SvcParams {
+        SvcParams::default()
+    }
+
+    /// Adds `param`, returning any previous value for its key.
+    pub fn insert(&mut self, param: SvcParam) -> Option<SvcParam> {
+        self.params.insert(param.key().to_num(), param)
+    }
+
+    pub fn get(&self, key: SvcParamKey) -> Option<&SvcParam> {
+        self.params.get(&key.to_num())
+    }
+
+    pub fn remove(&mut self, key: SvcParamKey) -> Option<SvcParam> {
+        self.params.remove(&key.to_num())
+    }
+
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Parameters in ascending key order.
+    pub fn iter(&self) -> impl Iterator<Item = &SvcParam> {
+        self.params.values()
+    }
+
+    /// Reads parameters up to the absolute offset `end`. Keys must be
+    /// strictly increasing.
+    pub(crate) fn read<S: Storage>(
+        buffer: &mut BytePacketBuffer<S>,
+        end: usize,
+    ) -> Result<SvcParams, ParseError> {
+        let mut params = SvcParams::new();
+        let mut last_key = None;
+
+        while buffer.pos() < end {
+            let key_pos = buffer.pos();
+            let key = buffer.read_u16()?;
+            let len = buffer.read_u16()? as usize;
+            if last_key.is_some_and(|last| key <= last) {
+                return Err(ParseError::InvalidRdata {
+                    offset: key_pos,
+                    reason: "SvcParam keys must be strictly increasing",
+                });
+            }
+            last_key = Some(key);
+
+            let value_pos = buffer.pos();
+            let param =
+                SvcParam::decode(key, buffer.read_bytes(len)?).ok_or(ParseError::InvalidRdata {
+                    offset: value_pos,
+                    reason: "malformed SvcParam value",
+                })?;
+            params.insert(param);
+        }
+
+        params
+            .check_mandatory()
+            .map_err(|reason| ParseError::InvalidRdata {
+                offset: end,
+                reason,
+            })?;
+        Ok(params)
+    }
+
+    /// Writes the parameters in key order after checking the mandatory
+    /// rules of RFC 9460 section 8.
+    pub(crate) fn write<S: Storage>(
+        &self,
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<(), EncodeError> {
+        self.check_mandatory()
+            .map_err(|reason| EncodeError::InvalidRdata {
+                offset: buffer.pos(),
+                reason,
+            })?;
+
+        for (key, param) in &self.params {
+            buffer.write_u16(*key)?;
+            let len_pos = buffer.pos();
+            buffer.write_u16(0)?;
+            let value_start = buffer.pos();
+
+            param.encode(buffer)?;
+
+            let len = buffer.pos() - value_start;
+            if len > u16::MAX as usize {
+                return Err(EncodeError::RdataTooLong {
+                    offset: value_start,
+                    len,
+                });
+            }
+            buffer.set_u16(len_pos, len as u16)?;
+        }
+        Ok(())
+    }
+
+    fn check_mandatory(&self) -> Result<(), &'static str> {
+        if let Some(SvcParam::MANDATORY(keys)) = self.get(SvcParamKey::MANDATORY) {
+            if keys.contains(&SvcParamKey::MANDATORY) {
+                return Err("mandatory must not list itself");
+            }
+            if keys.iter().any(|key| self.get(*key).is_none()) {
+                return Err("mandatory lists a key that is not present");
+            }
+        }
+        if self.get(SvcParamKey::NoDefaultAlpn).is_some() && self.get(SvcParamKey::ALPN).is_none() {
+            return Err("no-default-alpn requires alpn");
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SvcParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, param) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{param}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SvcParams {
+    type Err = PresentationError;
+
+    /// Parses whitespace-separated `key[=value]` pairs in any order. Values
+    /// may be quoted and use character-string escapes.
+    fn from_str(s: &str) -> Result<SvcParams, PresentationError> {
+        let mut params = SvcParams::new();
+        let mut rest = s.trim_start();
+
+        while !rest.is_empty() {
+            let key_end = rest
+                .find(|c: char| c == '=' || c.is_ascii_whitespace())
+                .unwrap_or(rest.len());
+            let key: SvcParamKey = rest[..key_end].parse()?;
+            rest = &rest[key_end..];
+
+            let value = match rest.strip_prefix('=') {
+                Some(value) => {
+                    let (raw, tail) =
+                        split_value(value).ok_or_else(|| PresentationError::Invalid {
+                            field: "SvcParam value",
+                            text: value.to_string(),
+                        })?;
+                    rest = tail;
+                    Some(
+                        unescape_bytes(raw).ok_or_else(|| PresentationError::Invalid {
+                            field: "SvcParam value",
+                            text: raw.to_string(),
+                        })?,
+                    )
+                }
+                None => None,
+            };
+
+            let param = SvcParam::parse(key, value.as_deref())?;
+            if params.insert(param).is_some() {
+                return Err(PresentationError::Invalid {
+                    field: "duplicate SvcParam",
+                    text: key.to_string(),
+                });
+            }
+            rest = rest.trim_start();
+        }
+
+        params
+            .check_mandatory()
+            .map_err(|reason| PresentationError::Invalid {
+                field: reason,
+                text: s.to_string(),
+            })?;
+        Ok(params)
+    }
+}
+
+// Splits off one value, quoted or bare, returning it still escaped along
+// with the remaining input.
+fn split_value(text: &str) -> Option<(&str, &str)> {
+    if let Some(quoted) = text.strip_prefix('"') {
+        let mut escaped = false;
+        for (i, c) in quoted.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => return Some((&quoted[..i], &quoted[i + 1..])),
+                _ => {}
+            }
+        }
+        return None;
+    }
+
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c.is_ascii_whitespace() => return Some((&text[..i], &text[i..])),
+            _ => {}
+        }
+    }
+    Some((text, ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SvcParam, SvcParamKey, SvcParams};
+    use crate::{
+        buffer::BytePacketBuffer,
+        error::{EncodeError, ParseError},
+    };
+    use std::net::Ipv4Addr;
+
+    fn wire(params: &SvcParams) -> Vec<u8> {
+        let mut buffer = BytePacketBuffer::growable();
+        params.write(&mut buffer).unwrap();
+        buffer.into_inner()
+    }
+
+    fn read(bytes: &[u8]) -> Result<SvcParams, ParseError> {
+        SvcParams::read(&mut BytePacketBuffer::from_bytes(bytes), bytes.len())
+    }
+
+    #[test]
+    fn rfc_9460_test_vectors() {
+        // Appendix D.2, figure 5: port
+        let params: SvcParams = "port=53".parse().unwrap();
+        assert_eq!(vec![0, 3, 0, 2, 0, 53], wire(&params));
+
+        // Figure 7: alpn with escaped comma and backslash
+        let params: SvcParams = r#"alpn="f\\\\oo\\,bar,h2""#.parse().unwrap();
+        assert_eq!(
+            Some(&SvcParam::ALPN(vec![b"f\\oo,bar".to_vec(), b"h2".to_vec()])),
+            params.get(SvcParamKey::ALPN)
+        );
+        let mut expected = vec![0, 1, 0, 12, 8];
+        expected.extend_from_slice(b"f\\oo,bar");
+        expected.extend_from_slice(&[2, b'h', b'2']);
+        assert_eq!(expected, wire(&params));
+        assert_eq!(params, read(&expected).unwrap());
+        assert_eq!(params, params.to_string().parse().unwrap());
+
+        // Figure 9: keys listed out of order, mandatory
+        let params: SvcParams = "key65333=ex1 key65444=ex2 mandatory=key65444,alpn alpn=h2"
+            .parse()
+            .unwrap();
+        let bytes = wire(&params);
+        assert_eq!(&[0, 0, 0, 4, 0, 1, 0xff, 0xa4], &bytes[..8]);
+        assert_eq!(params, read(&bytes).unwrap());
+        assert_eq!(
+            r#"mandatory=alpn,key65444 alpn="h2" key65333="ex1" key65444="ex2""#,
+            params.to_string()
+        );
+    }
+
+    #[test]
+    fn presentation_roundtrip() {
+        let text = r#"alpn="h3,h2" no-default-alpn port=8443 ipv4hint=192.0.2.1,192.0.2.2 ech=AEX+/w== ipv6hint=2001:db8::1"#;
+        let params: SvcParams = text.parse().unwrap();
+        assert_eq!(6, params.len());
+        assert_eq!(
+            Some(&SvcParam::IPV4HINT(vec![
+                Ipv4Addr::new(192, 0, 2, 1),
+                Ipv4Addr::new(192, 0, 2, 2)
+            ])),
+            params.get(SvcParamKey::IPV4HINT)
+        );
+        assert_eq!(text, params.to_string());
+        assert_eq!(params, read(&wire(&params)).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        for text in [
+            "port",
+            "no-default-alpn=x",
+            "port=1 port=2",
+            "mandatory=port",
+            "mandatory=mandatory",
+            "no-default-alpn",
+            "alpn=h2,,h3",
+            "key65535=x",
+            "ech=!!",
+            "alpn=\"h2",
+        ] {
+            assert!(text.parse::<SvcParams>().is_err(), "{text}");
+        }
+
+        // Keys out of order and duplicated on the wire.
+        assert!(matches!(
+            read(&[0, 3, 0, 2, 0, 53, 0, 1, 0, 3, 2, b'h', b'2']),
+            Err(ParseError::InvalidRdata { offset: 6, .. })
+        ));
+        assert!(matches!(
+            read(&[0, 3, 0, 1, 0]),
+            Err(ParseError::InvalidRdata { offset: 4, .. })
+        ));
+
+        // Mandatory rules are enforced on write too.
+        let mut params = SvcParams::new();
+        params.insert(SvcParam::MANDATORY(vec![SvcParamKey::PORT]));
+        let mut buffer = BytePacketBuffer::new();
+        assert!(matches!(
+            params.write(&mut buffer),
+            Err(EncodeError::InvalidRdata { .. })
+        ));
+        params.insert(SvcParam::PORT(443));
+        assert!(params.write(&mut buffer).is_ok());
+    }
+}
@@ -0,0 +1,112 @@
+//! Field values of TLSA (RFC 6698) and SMIMEA (RFC 8162) records, named
+//! after the acronyms of RFC 7218.
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum TlsaUsage {
+    UNKNOWN(u8),
+    PKIXTA,   // 0
+    PKIXEE,   // 1
+    DANETA,   // 2
+    DANEEE,   // 3
+    PRIVCERT, // 255
+}
+
+impl TlsaUsage {
+    pub fn to_num(&self) -> u8 {
+        match *self {
+            TlsaUsage::UNKNOWN(x) => x,
+            TlsaUsage::PKIXTA => 0,
+            TlsaUsage::PKIXEE => 1,
+            TlsaUsage::DANETA => 2,
+            TlsaUsage::DANEEE => 3,
+            TlsaUsage::PRIVCERT => 255,
+        }
+    }
+
+    pub fn from_num(num: u8) -> TlsaUsage {
+        match num {
+            0 => TlsaUsage::PKIXTA,
+            1 => TlsaUsage::PKIXEE,
+            2 => TlsaUsage::DANETA,
+            3 => TlsaUsage::DANEEE,
+            255 => TlsaUsage::PRIVCERT,
+            _ => TlsaUsage::UNKNOWN(num),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum TlsaSelector {
+    UNKNOWN(u8),
+    CERT,    // 0
+    SPKI,    // 1
+    PRIVSEL, // 255
+}
+
+impl TlsaSelector {
+    pub fn to_num(&self) -> u8 {
+        match *self {
+            TlsaSelector::UNKNOWN(x) => x,
+            TlsaSelector::CERT => 0,
+            TlsaSelector::SPKI => 1,
+            TlsaSelector::PRIVSEL => 255,
+        }
+    }
+
+    pub fn from_num(num: u8) -> TlsaSelector {
+        match num {
+            0 => TlsaSelector::CERT,
+            1 => TlsaSelector::SPKI,
+            255 => TlsaSelector::PRIVSEL,
+            _ => TlsaSelector::UNKNOWN(num),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum TlsaMatchingType {
+    UNKNOWN(u8),
+    FULL,      // 0
+    SHA256,    // 1
+    SHA512,    // 2
+    PRIVMATCH, // 255
+}
+
+impl TlsaMatchingType {
+    pub fn to_num(&self) -> u8 {
+        match *self {
+            TlsaMatchingType::UNKNOWN(x) => x,
+            TlsaMatchingType::FULL => 0,
+            TlsaMatchingType::SHA256 => 1,
+            TlsaMatchingType::SHA512 => 2,
+            TlsaMatchingType::PRIVMATCH => 255,
+        }
+    }
+
+    pub fn from_num(num: u8) -> TlsaMatchingType {
+        match num {
+            0 => TlsaMatchingType::FULL,
+            1 => TlsaMatchingType::SHA256,
+            2 => TlsaMatchingType::SHA512,
+            255 => TlsaMatchingType::PRIVMATCH,
+            _ => TlsaMatchingType::UNKNOWN(num),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TlsaMatchingType, TlsaSelector, TlsaUsage};
+
+    #[test]
+    fn numbers_roundtrip() {
+        for num in 0..=u8::MAX {
+            assert_eq!(num, TlsaUsage::from_num(num).to_num());
+            assert_eq!(num, TlsaSelector::from_num(num).to_num());
+            assert_eq!(num, TlsaMatchingType::from_num(num).to_num());
+        }
+        assert_eq!(TlsaUsage::DANEEE, TlsaUsage::from_num(3));
+        assert_eq!(TlsaSelector::SPKI, TlsaSelector::from_num(1));
+        assert_eq!(TlsaMatchingType::UNKNOWN(3), TlsaMatchingType::from_num(3));
+    }
+}
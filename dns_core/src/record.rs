@@ -1,53 +1,69 @@
 use std::net::{Ipv4Addr, Ipv6Addr};
 
-use crate::{buffer::BytePacketBuffer, types::QueryType};
+use crate::{
+    buffer::{BytePacketBuffer, Storage},
+    error::{EncodeError, ParseError},
+    name::Name,
+    rdata::{
+        apl::AplItem,
+        caa,
+        cert::CertType,
+        dnssec::{Timestamp, TypeBitmap},
+        gateway::Gateway,
+        loc::Location,
+        sshfp::{SshfpAlgorithm, SshfpFingerprintType},
+        svcb::SvcParams,
+        tlsa::{TlsaMatchingType, TlsaSelector, TlsaUsage},
+    },
+    types::{DnsClass, QueryType},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum DnsRecord {
     UNKNOWN {
-        domain: String,
+        domain: Name,
         qtype: QueryType,
-        class: u16,
+        class: DnsClass,
         ttl: u32,
         data: Vec<u8>,
     },
     A {
-        domain: String,
-        class: u16,
+        domain: Name,
+        class: DnsClass,
         ttl: u32,
         addr: std::net::Ipv4Addr,
     },
     NS {
-        domain: String,
-        class: u16,
-        host: String,
+        domain: Name,
+        class: DnsClass,
+        host: Name,
         ttl: u32,
     }, // 2
     CNAME {
-        domain: String,
-        class: u16,
-        host: String,
+        domain: Name,
+        class: DnsClass,
+        host: Name,
         ttl: u32,
     }, // 5
     MX {
-        domain: String,
+        domain: Name,
         priority: u16,
-        class: u16,
-        host: String,
+        class: DnsClass,
+        host: Name,
         ttl: u32,
     }, // 15
     TXT {
-        domain: String,
-        class: u16,
+        domain: Name,
+        class: DnsClass,
         ttl: u32,
-        data: Vec<String>,
+        data: Vec<Vec<u8>>,
     }, // 16
     SOA {
-        domain: String,
-        class: u16,
+        domain: Name,
+        class: DnsClass,
         ttl: u32,
-        mname: String,
-        rname: String,
+        mname: Name,
+        rname: Name,
         serial: u32,
         refresh: u32,
         retry: u32,
@@ -55,17 +71,456 @@ pub enum DnsRecord {
         minimum: u32,
     }, // 6
     PTR {
-        domain: String,
-        class: u16,
-        host: String,
+        domain: Name,
+        class: DnsClass,
+        host: Name,
         ttl: u32,
     }, // 12
+    HINFO {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        cpu: Vec<u8>,
+        os: Vec<u8>,
+    }, // 13
+    MINFO {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        rmailbx: Name,
+        emailbx: Name,
+    }, // 14
+    RP {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        mbox: Name,
+        txt: Name,
+    }, // 17
+    AFSDB {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        subtype: u16,
+        host: Name,
+    }, // 18
+    X25 {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        address: Vec<u8>,
+    }, // 19
+    ISDN {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        address: Vec<u8>,
+        subaddress: Option<Vec<u8>>,
+    }, // 20
+    RT {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        preference: u16,
+        host: Name,
+    }, // 21
+    NSAP {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        address: Vec<u8>,
+    }, // 22
+    NsapPtr {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        host: Name,
+    }, // 23
+    SIG {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        type_covered: QueryType,
+        algorithm: u8,
+        labels: u8,
+        original_ttl: u32,
+        expiration: Timestamp,
+        inception: Timestamp,
+        key_tag: u16,
+        signer_name: Name,
+        signature: Vec<u8>,
+    }, // 24
+    KEY {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        flags: u16,
+        protocol: u8,
+        algorithm: u8,
+        public_key: Vec<u8>,
+    }, // 25
+    PX {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        preference: u16,
+        map822: Name,
+        mapx400: Name,
+    }, // 26
     AAAA {
-        domain: String,
+        domain: Name,
         addr: Ipv6Addr,
-        class: u16,
+        class: DnsClass,
         ttl: u32,
     }, // 28
+    LOC {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        location: Location,
+    }, // 29
+    SRV {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: Name,
+    }, // 33
+    NAPTR {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        order: u16,
+        preference: u16,
+        flags: Vec<u8>,
+        services: Vec<u8>,
+        regexp: Vec<u8>,
+        replacement: Name,
+    }, // 35
+    KX {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        preference: u16,
+        exchanger: Name,
+    }, // 36
+    CERT {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        cert_type: CertType,
+        key_tag: u16,
+        algorithm: u8,
+        certificate: Vec<u8>,
+    }, // 37
+    DNAME {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        host: Name,
+    }, // 39
+    APL {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        items: Vec<AplItem>,
+    }, // 42
+    DS {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        key_tag: u16,
+        algorithm: u8,
+        digest_type: u8,
+        digest: Vec<u8>,
+    }, // 43
+    SSHFP {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        algorithm: SshfpAlgorithm,
+        fingerprint_type: SshfpFingerprintType,
+        fingerprint: Vec<u8>,
+    }, // 44
+    IPSECKEY {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        precedence: u8,
+        algorithm: u8,
+        gateway: Gateway,
+        public_key: Vec<u8>,
+    }, // 45
+    RRSIG {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        type_covered: QueryType,
+        algorithm: u8,
+        labels: u8,
+        original_ttl: u32,
+        expiration: Timestamp,
+        inception: Timestamp,
+        key_tag: u16,
+        signer_name: Name,
+        signature: Vec<u8>,
+    }, // 46
+    NSEC {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        next_domain: Name,
+        types: TypeBitmap,
+    }, // 47
+    DNSKEY {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        flags: u16,
+        protocol: u8,
+        algorithm: u8,
+        public_key: Vec<u8>,
+    }, // 48
+    DHCID {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        data: Vec<u8>,
+    }, // 49
+    NSEC3 {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: Vec<u8>,
+        next_hashed_owner: Vec<u8>,
+        types: TypeBitmap,
+    }, // 50
+    NSEC3PARAM {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: Vec<u8>,
+    }, // 51
+    TLSA {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        usage: TlsaUsage,
+        selector: TlsaSelector,
+        matching_type: TlsaMatchingType,
+        association_data: Vec<u8>,
+    }, // 52
+    SMIMEA {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        usage: TlsaUsage,
+        selector: TlsaSelector,
+        matching_type: TlsaMatchingType,
+        association_data: Vec<u8>,
+    }, // 53
+    HIP {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        pk_algorithm: u8,
+        hit: Vec<u8>,
+        public_key: Vec<u8>,
+        rendezvous_servers: Vec<Name>,
+    }, // 55
+    CDS {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        key_tag: u16,
+        algorithm: u8,
+        digest_type: u8,
+        digest: Vec<u8>,
+    }, // 59
+    CDNSKEY {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        flags: u16,
+        protocol: u8,
+        algorithm: u8,
+        public_key: Vec<u8>,
+    }, // 60
+    OPENPGPKEY {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        public_key: Vec<u8>,
+    }, // 61
+    CSYNC {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        serial: u32,
+        flags: u16,
+        types: TypeBitmap,
+    }, // 62
+    ZONEMD {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        serial: u32,
+        scheme: u8,
+        hash_algorithm: u8,
+        digest: Vec<u8>,
+    }, // 63
+    SVCB {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        priority: u16,
+        target: Name,
+        params: SvcParams,
+    }, // 64
+    HTTPS {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        priority: u16,
+        target: Name,
+        params: SvcParams,
+    }, // 65
+    SPF {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        data: Vec<Vec<u8>>,
+    }, // 99
+    NID {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        preference: u16,
+        node_id: u64,
+    }, // 104
+    L32 {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        preference: u16,
+        locator: Ipv4Addr,
+    }, // 105
+    L64 {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        preference: u16,
+        locator: u64,
+    }, // 106
+    LP {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        preference: u16,
+        fqdn: Name,
+    }, // 107
+    EUI48 {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        address: [u8; 6],
+    }, // 108
+    EUI64 {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        address: [u8; 8],
+    }, // 109
+    TKEY {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        algorithm: Name,
+        inception: Timestamp,
+        expiration: Timestamp,
+        mode: u16,
+        error: u16,
+        key: Vec<u8>,
+        other: Vec<u8>,
+    }, // 249
+    TSIG {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        algorithm: Name,
+        time_signed: u64,
+        fudge: u16,
+        mac: Vec<u8>,
+        original_id: u16,
+        error: u16,
+        other: Vec<u8>,
+    }, // 250
+    URI {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        priority: u16,
+        weight: u16,
+        target: Vec<u8>,
+    }, // 256
+    CAA {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        flags: u8,
+        tag: String,
+        value: Vec<u8>,
+    }, // 257
+    AVC {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        data: Vec<Vec<u8>>,
+    }, // 258
+    DOA {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        enterprise: u32,
+        doa_type: u32,
+        location: u8,
+        media_type: Vec<u8>,
+        data: Vec<u8>,
+    }, // 259
+    AMTRELAY {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        precedence: u8,
+        discovery_optional: bool,
+        relay: Gateway,
+    }, // 260
+    TA {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        key_tag: u16,
+        algorithm: u8,
+        digest_type: u8,
+        digest: Vec<u8>,
+    }, // 32768
+    DLV {
+        domain: Name,
+        class: DnsClass,
+        ttl: u32,
+        key_tag: u16,
+        algorithm: u8,
+        digest_type: u8,
+        digest: Vec<u8>,
+    }, // 32769
 }
 
 impl DnsRecord {
@@ -74,33 +529,303 @@ impl DnsRecord {
     // HEX   c0  0c  00  01  00  01  00  00  01  25  00  04  d8  3a  d3  8e
     // DEC   192 12    1       1           293         4     216 58  211 142
 
-    pub fn read(buffer: &mut BytePacketBuffer) -> Result<DnsRecord, Box<dyn std::error::Error>> {
-        let mut domain = String::new();
+    pub fn qtype(&self) -> QueryType {
+        match self {
+            DnsRecord::UNKNOWN { qtype, .. } => *qtype,
+            DnsRecord::A { .. } => QueryType::A,
+            DnsRecord::NS { .. } => QueryType::NS,
+            DnsRecord::CNAME { .. } => QueryType::CNAME,
+            DnsRecord::MX { .. } => QueryType::MX,
+            DnsRecord::TXT { .. } => QueryType::TXT,
+            DnsRecord::SOA { .. } => QueryType::SOA,
+            DnsRecord::PTR { .. } => QueryType::PTR,
+            DnsRecord::HINFO { .. } => QueryType::HINFO,
+            DnsRecord::MINFO { .. } => QueryType::MINFO,
+            DnsRecord::RP { .. } => QueryType::RP,
+            DnsRecord::AFSDB { .. } => QueryType::AFSDB,
+            DnsRecord::X25 { .. } => QueryType::X25,
+            DnsRecord::ISDN { .. } => QueryType::ISDN,
+            DnsRecord::RT { .. } => QueryType::RT,
+            DnsRecord::NSAP { .. } => QueryType::NSAP,
+            DnsRecord::NsapPtr { .. } => QueryType::NsapPtr,
+            DnsRecord::SIG { .. } => QueryType::SIG,
+            DnsRecord::KEY { .. } => QueryType::KEY,
+            DnsRecord::PX { .. } => QueryType::PX,
+            DnsRecord::AAAA { .. } => QueryType::AAAA,
+            DnsRecord::LOC { .. } => QueryType::LOC,
+            DnsRecord::SRV { .. } => QueryType::SRV,
+            DnsRecord::NAPTR { .. } => QueryType::NAPTR,
+            DnsRecord::KX { .. } => QueryType::KX,
+            DnsRecord::CERT { .. } => QueryType::CERT,
+            DnsRecord::DNAME { .. } => QueryType::DNAME,
+            DnsRecord::APL { .. } => QueryType::APL,
+            DnsRecord::DS { .. } => QueryType::DS,
+            DnsRecord::SSHFP { .. } => QueryType::SSHFP,
+            DnsRecord::IPSECKEY { .. } => QueryType::IPSECKEY,
+            DnsRecord::RRSIG { .. } => QueryType::RRSIG,
+            DnsRecord::NSEC { .. } => QueryType::NSEC,
+            DnsRecord::DNSKEY { .. } => QueryType::DNSKEY,
+            DnsRecord::DHCID { .. } => QueryType::DHCID,
+            DnsRecord::NSEC3 { .. } => QueryType::NSEC3,
+            DnsRecord::NSEC3PARAM { .. } => QueryType::NSEC3PARAM,
+            DnsRecord::TLSA { .. } => QueryType::TLSA,
+            DnsRecord::SMIMEA { .. } => QueryType::SMIMEA,
+            DnsRecord::HIP { .. } => QueryType::HIP,
+            DnsRecord::CDS { .. } => QueryType::CDS,
+            DnsRecord::CDNSKEY { .. } => QueryType::CDNSKEY,
+            DnsRecord::OPENPGPKEY { .. } => QueryType::OPENPGPKEY,
+            DnsRecord::CSYNC { .. } => QueryType::CSYNC,
+            DnsRecord::ZONEMD { .. } => QueryType::ZONEMD,
+            DnsRecord::SVCB { .. } => QueryType::SVCB,
+            DnsRecord::HTTPS { .. } => QueryType::HTTPS,
+            DnsRecord::SPF { .. } => QueryType::SPF,
+            DnsRecord::NID { .. } => QueryType::NID,
+            DnsRecord::L32 { .. } => QueryType::L32,
+            DnsRecord::L64 { .. } => QueryType::L64,
+            DnsRecord::LP { .. } => QueryType::LP,
+            DnsRecord::EUI48 { .. } => QueryType::EUI48,
+            DnsRecord::EUI64 { .. } => QueryType::EUI64,
+            DnsRecord::TKEY { .. } => QueryType::TKEY,
+            DnsRecord::TSIG { .. } => QueryType::TSIG,
+            DnsRecord::URI { .. } => QueryType::URI,
+            DnsRecord::CAA { .. } => QueryType::CAA,
+            DnsRecord::AVC { .. } => QueryType::AVC,
+            DnsRecord::DOA { .. } => QueryType::DOA,
+            DnsRecord::AMTRELAY { .. } => QueryType::AMTRELAY,
+            DnsRecord::TA { .. } => QueryType::TA,
+            DnsRecord::DLV { .. } => QueryType::DLV,
+        }
+    }
+
+    /// Owner name, class and TTL, common to every variant.
+    pub(crate) fn owner(&self) -> (&Name, DnsClass, u32) {
+        match self {
+            DnsRecord::A {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NS {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::CNAME {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::MX {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::TXT {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::SOA {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::PTR {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::AAAA {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::SRV {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::CAA {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::SVCB {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::HTTPS {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::DS {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::RRSIG {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NSEC {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::DNSKEY {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NSEC3 {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NSEC3PARAM {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::CDS {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::CDNSKEY {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::CERT {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::SSHFP {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::TLSA {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::SMIMEA {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::OPENPGPKEY {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NAPTR {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::DNAME {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::URI {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::LOC {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::HINFO {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::MINFO {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::RP {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::AFSDB {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::X25 {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::ISDN {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::RT {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::PX {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::KX {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NSAP {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NsapPtr {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::SIG {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::KEY {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::APL {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::IPSECKEY {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::DHCID {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::HIP {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::CSYNC {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::ZONEMD {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::SPF {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::NID {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::L32 {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::L64 {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::LP {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::EUI48 {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::EUI64 {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::TKEY {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::TSIG {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::AVC {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::DOA {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::AMTRELAY {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::TA {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::DLV {
+                domain, class, ttl, ..
+            }
+            | DnsRecord::UNKNOWN {
+                domain, class, ttl, ..
+            } => (domain, *class, *ttl),
+        }
+    }
+
+    /// mDNS cache-flush bit (RFC 6762 section 10.2).
+    pub fn cache_flush(&self) -> bool {
+        self.owner().1.split_mdns().1
+    }
+
+    /// The class with the mDNS cache-flush bit cleared.
+    pub fn base_class(&self) -> DnsClass {
+        self.owner().1.split_mdns().0
+    }
+
+    pub fn read<S: Storage>(buffer: &mut BytePacketBuffer<S>) -> Result<DnsRecord, ParseError> {
+        let mut domain = Name::root();
         buffer.read_qname(&mut domain)?;
 
         let qtype = QueryType::from_num(buffer.read_u16()?);
-        let class = buffer.read_u16()?;
+        let class = DnsClass::from_num(buffer.read_u16()?);
         let ttl = buffer.read_u32()?;
         let len = buffer.read_u16()?;
+        let rdata_start = buffer.pos();
+        let rdata_end = rdata_start + len as usize;
 
-        match qtype {
+        let record = match qtype {
             QueryType::A => {
-                let ip_byte = buffer.read_u32()?;
-                let ip = Ipv4Addr::new(
-                    ((ip_byte >> 24) & 0xff) as u8,
-                    ((ip_byte >> 16) & 0xff) as u8,
-                    ((ip_byte >> 8) & 0xff) as u8,
-                    (ip_byte & 0xff) as u8,
-                );
+                let mut octets = [0u8; 4];
+                octets.copy_from_slice(buffer.read_bytes(4)?);
                 Ok(DnsRecord::A {
                     domain,
                     class,
                     ttl,
-                    addr: ip,
+                    addr: Ipv4Addr::from(octets),
                 })
             }
             QueryType::NS => {
-                let mut host = String::new();
+                let mut host = Name::root();
                 buffer.read_qname(&mut host)?;
 
                 Ok(DnsRecord::NS {
@@ -111,10 +836,10 @@ impl DnsRecord {
                 })
             }
             QueryType::SOA => {
-                let mut mname = String::new();
+                let mut mname = Name::root();
                 buffer.read_qname(&mut mname)?;
 
-                let mut rname = String::new();
+                let mut rname = Name::root();
                 buffer.read_qname(&mut rname)?;
 
                 let serial = buffer.read_u32()?;
@@ -137,7 +862,7 @@ impl DnsRecord {
                 })
             }
             QueryType::CNAME => {
-                let mut host = String::new();
+                let mut host = Name::root();
                 buffer.read_qname(&mut host)?;
 
                 Ok(DnsRecord::CNAME {
@@ -148,7 +873,7 @@ impl DnsRecord {
                 })
             }
             QueryType::PTR => {
-                let mut host = String::new();
+                let mut host = Name::root();
                 buffer.read_qname(&mut host)?;
 
                 Ok(DnsRecord::PTR {
@@ -160,7 +885,7 @@ impl DnsRecord {
             }
             QueryType::MX => {
                 let priority = buffer.read_u16()?;
-                let mut host = String::new();
+                let mut host = Name::root();
                 buffer.read_qname(&mut host)?;
 
                 Ok(DnsRecord::MX {
@@ -171,44 +896,170 @@ impl DnsRecord {
                     class,
                 })
             }
-            QueryType::TXT => {
+            QueryType::TXT | QueryType::SPF | QueryType::AVC => {
                 let mut data = Vec::new();
-                let mut bytes_read = 0u16;
-                while bytes_read < len {
-                    let txt_len = buffer.read()? as u16;
+                let mut bytes_read = 0usize;
+                while bytes_read < len as usize {
+                    let txt_len = buffer.read()? as usize;
                     bytes_read += 1;
-                    if bytes_read + txt_len > len {
-                        return Err("TXT record length exceeds rdata".into());
+                    if bytes_read + txt_len > len as usize {
+                        return Err(ParseError::RdataLengthMismatch {
+                            offset: rdata_start,
+                            expected: len as usize,
+                            actual: bytes_read + txt_len,
+                        });
                     }
-                    let start = buffer.pos();
-                    let txt_bytes = buffer.get_range(start, txt_len as usize)?.to_vec();
-                    buffer.step(txt_len as usize);
+                    let txt_bytes = buffer.read_bytes(txt_len)?;
                     bytes_read += txt_len;
-                    data.push(String::from_utf8_lossy(&txt_bytes).to_string());
+                    data.push(txt_bytes.to_vec());
                 }
 
-                Ok(DnsRecord::TXT {
+                Ok(match qtype {
+                    QueryType::TXT => DnsRecord::TXT {
+                        domain,
+                        class,
+                        ttl,
+                        data,
+                    },
+                    QueryType::SPF => DnsRecord::SPF {
+                        domain,
+                        class,
+                        ttl,
+                        data,
+                    },
+                    _ => DnsRecord::AVC {
+                        domain,
+                        class,
+                        ttl,
+                        data,
+                    },
+                })
+            }
+            QueryType::HINFO => {
+                let cpu = buffer.read_character_string()?.to_vec();
+                let os = buffer.read_character_string()?.to_vec();
+
+                Ok(DnsRecord::HINFO {
                     domain,
                     class,
                     ttl,
-                    data,
+                    cpu,
+                    os,
+                })
+            }
+            QueryType::MINFO => {
+                let mut rmailbx = Name::root();
+                buffer.read_qname(&mut rmailbx)?;
+                let mut emailbx = Name::root();
+                buffer.read_qname(&mut emailbx)?;
+
+                Ok(DnsRecord::MINFO {
+                    domain,
+                    class,
+                    ttl,
+                    rmailbx,
+                    emailbx,
+                })
+            }
+            QueryType::RP => {
+                let mut mbox = Name::root();
+                buffer.read_qname(&mut mbox)?;
+                let mut txt = Name::root();
+                buffer.read_qname(&mut txt)?;
+
+                Ok(DnsRecord::RP {
+                    domain,
+                    class,
+                    ttl,
+                    mbox,
+                    txt,
+                })
+            }
+            QueryType::AFSDB => {
+                let subtype = buffer.read_u16()?;
+                let mut host = Name::root();
+                buffer.read_qname(&mut host)?;
+
+                Ok(DnsRecord::AFSDB {
+                    domain,
+                    class,
+                    ttl,
+                    subtype,
+                    host,
+                })
+            }
+            QueryType::X25 => {
+                let address = buffer.read_character_string()?.to_vec();
+
+                Ok(DnsRecord::X25 {
+                    domain,
+                    class,
+                    ttl,
+                    address,
+                })
+            }
+            QueryType::ISDN => {
+                let address = buffer.read_character_string()?.to_vec();
+                let subaddress = if buffer.pos() < rdata_end {
+                    Some(buffer.read_character_string()?.to_vec())
+                } else {
+                    None
+                };
+
+                Ok(DnsRecord::ISDN {
+                    domain,
+                    class,
+                    ttl,
+                    address,
+                    subaddress,
+                })
+            }
+            QueryType::RT => {
+                let preference = buffer.read_u16()?;
+                let mut host = Name::root();
+                buffer.read_qname(&mut host)?;
+
+                Ok(DnsRecord::RT {
+                    domain,
+                    class,
+                    ttl,
+                    preference,
+                    host,
+                })
+            }
+            QueryType::PX => {
+                let preference = buffer.read_u16()?;
+                let mut map822 = Name::root();
+                buffer.read_qname(&mut map822)?;
+                let mut mapx400 = Name::root();
+                buffer.read_qname(&mut mapx400)?;
+
+                Ok(DnsRecord::PX {
+                    domain,
+                    class,
+                    ttl,
+                    preference,
+                    map822,
+                    mapx400,
+                })
+            }
+            QueryType::KX => {
+                let preference = buffer.read_u16()?;
+                let mut exchanger = Name::root();
+                buffer.read_qname(&mut exchanger)?;
+
+                Ok(DnsRecord::KX {
+                    domain,
+                    class,
+                    ttl,
+                    preference,
+                    exchanger,
                 })
             }
             QueryType::AAAA => {
-                let raw_addr1 = buffer.read_u32()?;
-                let raw_addr2 = buffer.read_u32()?;
-                let raw_addr3 = buffer.read_u32()?;
-                let raw_addr4 = buffer.read_u32()?;
-                let addr = Ipv6Addr::new(
-                    ((raw_addr1 >> 16) & 0xFFFF) as u16,
-                    (raw_addr1 & 0xFFFF) as u16,
-                    ((raw_addr2 >> 16) & 0xFFFF) as u16,
-                    (raw_addr2 & 0xFFFF) as u16,
-                    ((raw_addr3 >> 16) & 0xFFFF) as u16,
-                    (raw_addr3 & 0xFFFF) as u16,
-                    ((raw_addr4 >> 16) & 0xFFFF) as u16,
-                    (raw_addr4 & 0xFFFF) as u16,
-                );
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(buffer.read_bytes(16)?);
+                let addr = Ipv6Addr::from(octets);
 
                 Ok(DnsRecord::AAAA {
                     domain,
@@ -217,209 +1068,1762 @@ impl DnsRecord {
                     class,
                 })
             }
-            _ => {
-                let data = buffer.get_range(buffer.pos(), len as usize)?.to_vec();
-                buffer.step(len as usize);
-                Ok(DnsRecord::UNKNOWN {
+            QueryType::LOC => {
+                let data = buffer.read_bytes(len as usize)?;
+                // Other versions and out-of-range values stay opaque.
+                match Location::from_wire(data) {
+                    Some(location) => Ok(DnsRecord::LOC {
+                        domain,
+                        class,
+                        ttl,
+                        location,
+                    }),
+                    None => Ok(DnsRecord::UNKNOWN {
+                        domain,
+                        qtype,
+                        class,
+                        ttl,
+                        data: data.to_vec(),
+                    }),
+                }
+            }
+            QueryType::SRV => {
+                let priority = buffer.read_u16()?;
+                let weight = buffer.read_u16()?;
+                let port = buffer.read_u16()?;
+                let mut target = Name::root();
+                buffer.read_qname(&mut target)?;
+
+                Ok(DnsRecord::SRV {
                     domain,
-                    qtype,
                     class,
                     ttl,
-                    data,
+                    priority,
+                    weight,
+                    port,
+                    target,
                 })
             }
-        }
-    }
+            QueryType::NAPTR => {
+                let order = buffer.read_u16()?;
+                let preference = buffer.read_u16()?;
+                let flags = buffer.read_character_string()?.to_vec();
+                let services = buffer.read_character_string()?.to_vec();
+                let regexp = buffer.read_character_string()?.to_vec();
+                let mut replacement = Name::root();
+                buffer.read_qname(&mut replacement)?;
 
-    pub fn write(
-        &self,
-        buffer: &mut BytePacketBuffer,
-    ) -> Result<usize, Box<dyn std::error::Error>> {
-        let start_pos = buffer.pos();
-        match self {
-            DnsRecord::A {
+                Ok(DnsRecord::NAPTR {
+                    domain,
+                    class,
+                    ttl,
+                    order,
+                    preference,
+                    flags,
+                    services,
+                    regexp,
+                    replacement,
+                })
+            }
+            QueryType::DNAME => {
+                let mut host = Name::root();
+                buffer.read_qname(&mut host)?;
+
+                Ok(DnsRecord::DNAME {
+                    domain,
+                    class,
+                    ttl,
+                    host,
+                })
+            }
+            QueryType::CERT => {
+                let cert_type = CertType::from_num(buffer.read_u16()?);
+                let key_tag = buffer.read_u16()?;
+                let algorithm = buffer.read()?;
+                let certificate = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::CERT {
+                    domain,
+                    class,
+                    ttl,
+                    cert_type,
+                    key_tag,
+                    algorithm,
+                    certificate,
+                })
+            }
+            QueryType::DS | QueryType::CDS | QueryType::TA | QueryType::DLV => {
+                let key_tag = buffer.read_u16()?;
+                let algorithm = buffer.read()?;
+                let digest_type = buffer.read()?;
+                let digest = read_remaining(buffer, rdata_end)?;
+
+                Ok(match qtype {
+                    QueryType::DS => DnsRecord::DS {
+                        domain,
+                        class,
+                        ttl,
+                        key_tag,
+                        algorithm,
+                        digest_type,
+                        digest,
+                    },
+                    QueryType::CDS => DnsRecord::CDS {
+                        domain,
+                        class,
+                        ttl,
+                        key_tag,
+                        algorithm,
+                        digest_type,
+                        digest,
+                    },
+                    QueryType::TA => DnsRecord::TA {
+                        domain,
+                        class,
+                        ttl,
+                        key_tag,
+                        algorithm,
+                        digest_type,
+                        digest,
+                    },
+                    _ => DnsRecord::DLV {
+                        domain,
+                        class,
+                        ttl,
+                        key_tag,
+                        algorithm,
+                        digest_type,
+                        digest,
+                    },
+                })
+            }
+            QueryType::SSHFP => {
+                let algorithm = SshfpAlgorithm::from_num(buffer.read()?);
+                let fingerprint_type = SshfpFingerprintType::from_num(buffer.read()?);
+                let fingerprint = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::SSHFP {
+                    domain,
+                    class,
+                    ttl,
+                    algorithm,
+                    fingerprint_type,
+                    fingerprint,
+                })
+            }
+            QueryType::RRSIG | QueryType::SIG => {
+                let type_covered = QueryType::from_num(buffer.read_u16()?);
+                let algorithm = buffer.read()?;
+                let labels = buffer.read()?;
+                let original_ttl = buffer.read_u32()?;
+                let expiration = Timestamp(buffer.read_u32()?);
+                let inception = Timestamp(buffer.read_u32()?);
+                let key_tag = buffer.read_u16()?;
+                let mut signer_name = Name::root();
+                buffer.read_qname(&mut signer_name)?;
+                let signature = read_remaining(buffer, rdata_end)?;
+
+                if qtype == QueryType::RRSIG {
+                    Ok(DnsRecord::RRSIG {
+                        domain,
+                        class,
+                        ttl,
+                        type_covered,
+                        algorithm,
+                        labels,
+                        original_ttl,
+                        expiration,
+                        inception,
+                        key_tag,
+                        signer_name,
+                        signature,
+                    })
+                } else {
+                    Ok(DnsRecord::SIG {
+                        domain,
+                        class,
+                        ttl,
+                        type_covered,
+                        algorithm,
+                        labels,
+                        original_ttl,
+                        expiration,
+                        inception,
+                        key_tag,
+                        signer_name,
+                        signature,
+                    })
+                }
+            }
+            QueryType::NSEC => {
+                let mut next_domain = Name::root();
+                buffer.read_qname(&mut next_domain)?;
+                let types = TypeBitmap::read(buffer, rdata_end)?;
+
+                Ok(DnsRecord::NSEC {
+                    domain,
+                    class,
+                    ttl,
+                    next_domain,
+                    types,
+                })
+            }
+            QueryType::DNSKEY | QueryType::CDNSKEY | QueryType::KEY => {
+                let flags = buffer.read_u16()?;
+                let protocol = buffer.read()?;
+                let algorithm = buffer.read()?;
+                let public_key = read_remaining(buffer, rdata_end)?;
+
+                Ok(match qtype {
+                    QueryType::DNSKEY => DnsRecord::DNSKEY {
+                        domain,
+                        class,
+                        ttl,
+                        flags,
+                        protocol,
+                        algorithm,
+                        public_key,
+                    },
+                    QueryType::CDNSKEY => DnsRecord::CDNSKEY {
+                        domain,
+                        class,
+                        ttl,
+                        flags,
+                        protocol,
+                        algorithm,
+                        public_key,
+                    },
+                    _ => DnsRecord::KEY {
+                        domain,
+                        class,
+                        ttl,
+                        flags,
+                        protocol,
+                        algorithm,
+                        public_key,
+                    },
+                })
+            }
+            QueryType::NSEC3 => {
+                let hash_algorithm = buffer.read()?;
+                let flags = buffer.read()?;
+                let iterations = buffer.read_u16()?;
+                let salt = buffer.read_character_string()?.to_vec();
+                let next_hashed_owner = buffer.read_character_string()?.to_vec();
+                // RFC 5155 section 3.2: the hash is at least one byte, and an
+                // empty one has no presentation form.
+                if next_hashed_owner.is_empty() {
+                    buffer.seek(rdata_start)?;
+                    Ok(DnsRecord::UNKNOWN {
+                        domain,
+                        qtype,
+                        class,
+                        ttl,
+                        data: read_remaining(buffer, rdata_end)?,
+                    })
+                } else {
+                    let types = TypeBitmap::read(buffer, rdata_end)?;
+
+                    Ok(DnsRecord::NSEC3 {
+                        domain,
+                        class,
+                        ttl,
+                        hash_algorithm,
+                        flags,
+                        iterations,
+                        salt,
+                        next_hashed_owner,
+                        types,
+                    })
+                }
+            }
+            QueryType::NSEC3PARAM => {
+                let hash_algorithm = buffer.read()?;
+                let flags = buffer.read()?;
+                let iterations = buffer.read_u16()?;
+                let salt = buffer.read_character_string()?.to_vec();
+
+                Ok(DnsRecord::NSEC3PARAM {
+                    domain,
+                    class,
+                    ttl,
+                    hash_algorithm,
+                    flags,
+                    iterations,
+                    salt,
+                })
+            }
+            QueryType::TLSA | QueryType::SMIMEA => {
+                let usage = TlsaUsage::from_num(buffer.read()?);
+                let selector = TlsaSelector::from_num(buffer.read()?);
+                let matching_type = TlsaMatchingType::from_num(buffer.read()?);
+                let association_data = read_remaining(buffer, rdata_end)?;
+
+                if qtype == QueryType::TLSA {
+                    Ok(DnsRecord::TLSA {
+                        domain,
+                        class,
+                        ttl,
+                        usage,
+                        selector,
+                        matching_type,
+                        association_data,
+                    })
+                } else {
+                    Ok(DnsRecord::SMIMEA {
+                        domain,
+                        class,
+                        ttl,
+                        usage,
+                        selector,
+                        matching_type,
+                        association_data,
+                    })
+                }
+            }
+            QueryType::OPENPGPKEY => {
+                let public_key = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::OPENPGPKEY {
+                    domain,
+                    class,
+                    ttl,
+                    public_key,
+                })
+            }
+            QueryType::SVCB | QueryType::HTTPS => {
+                let priority = buffer.read_u16()?;
+                let mut target = Name::root();
+                buffer.read_qname(&mut target)?;
+                let params = SvcParams::read(buffer, rdata_end)?;
+
+                if qtype == QueryType::SVCB {
+                    Ok(DnsRecord::SVCB {
+                        domain,
+                        class,
+                        ttl,
+                        priority,
+                        target,
+                        params,
+                    })
+                } else {
+                    Ok(DnsRecord::HTTPS {
+                        domain,
+                        class,
+                        ttl,
+                        priority,
+                        target,
+                        params,
+                    })
+                }
+            }
+            QueryType::URI => {
+                let priority = buffer.read_u16()?;
+                let weight = buffer.read_u16()?;
+                let target = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::URI {
+                    domain,
+                    class,
+                    ttl,
+                    priority,
+                    weight,
+                    target,
+                })
+            }
+            QueryType::CAA => {
+                let data = buffer.read_bytes(len as usize)?;
+                // Records with an invalid tag stay opaque.
+                let tag_len = data.get(1).map_or(0, |&n| n as usize);
+                match data.get(2..2 + tag_len) {
+                    Some(tag) if caa::is_valid_tag(tag) => Ok(DnsRecord::CAA {
+                        domain,
+                        class,
+                        ttl,
+                        flags: data[0],
+                        tag: tag.iter().map(|&b| char::from(b)).collect(),
+                        value: data[2 + tag_len..].to_vec(),
+                    }),
+                    _ => Ok(DnsRecord::UNKNOWN {
+                        domain,
+                        qtype,
+                        class,
+                        ttl,
+                        data: data.to_vec(),
+                    }),
+                }
+            }
+            QueryType::NSAP => {
+                let address = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::NSAP {
+                    domain,
+                    class,
+                    ttl,
+                    address,
+                })
+            }
+            QueryType::NsapPtr => {
+                let mut host = Name::root();
+                buffer.read_qname(&mut host)?;
+
+                Ok(DnsRecord::NsapPtr {
+                    domain,
+                    class,
+                    ttl,
+                    host,
+                })
+            }
+            QueryType::APL => {
+                let items = AplItem::read_list(buffer, rdata_end)?;
+
+                Ok(DnsRecord::APL {
+                    domain,
+                    class,
+                    ttl,
+                    items,
+                })
+            }
+            // Shorter RDATA than the fixed fields falls through to the
+            // opaque arm below.
+            QueryType::IPSECKEY if len >= 3 => {
+                let precedence = buffer.read()?;
+                let gateway_type = buffer.read()?;
+                let algorithm = buffer.read()?;
+                match Gateway::read(buffer, gateway_type, rdata_end)? {
+                    Some(gateway) => Ok(DnsRecord::IPSECKEY {
+                        domain,
+                        class,
+                        ttl,
+                        precedence,
+                        algorithm,
+                        gateway,
+                        public_key: read_remaining(buffer, rdata_end)?,
+                    }),
+                    // Unknown gateway types and truncated gateways.
+                    None => {
+                        buffer.seek(rdata_start)?;
+                        Ok(DnsRecord::UNKNOWN {
+                            domain,
+                            qtype,
+                            class,
+                            ttl,
+                            data: read_remaining(buffer, rdata_end)?,
+                        })
+                    }
+                }
+            }
+            QueryType::DHCID => {
+                let data = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::DHCID {
+                    domain,
+                    class,
+                    ttl,
+                    data,
+                })
+            }
+            QueryType::HIP => {
+                let hit_len = buffer.read()? as usize;
+                let pk_algorithm = buffer.read()?;
+                let pk_len = buffer.read_u16()? as usize;
+                // RFC 8005 section 5 requires both the HIT and the key;
+                // without them the record has no presentation form.
+                if hit_len == 0 || pk_len == 0 {
+                    buffer.seek(rdata_start)?;
+                    Ok(DnsRecord::UNKNOWN {
+                        domain,
+                        qtype,
+                        class,
+                        ttl,
+                        data: read_remaining(buffer, rdata_end)?,
+                    })
+                } else {
+                    let hit = buffer.read_bytes(hit_len)?.to_vec();
+                    let public_key = buffer.read_bytes(pk_len)?.to_vec();
+                    let mut rendezvous_servers = Vec::new();
+                    while buffer.pos() < rdata_end {
+                        let mut server = Name::root();
+                        buffer.read_qname(&mut server)?;
+                        rendezvous_servers.push(server);
+                    }
+
+                    Ok(DnsRecord::HIP {
+                        domain,
+                        class,
+                        ttl,
+                        pk_algorithm,
+                        hit,
+                        public_key,
+                        rendezvous_servers,
+                    })
+                }
+            }
+            QueryType::CSYNC => {
+                let serial = buffer.read_u32()?;
+                let flags = buffer.read_u16()?;
+                let types = TypeBitmap::read(buffer, rdata_end)?;
+
+                Ok(DnsRecord::CSYNC {
+                    domain,
+                    class,
+                    ttl,
+                    serial,
+                    flags,
+                    types,
+                })
+            }
+            QueryType::ZONEMD => {
+                let serial = buffer.read_u32()?;
+                let scheme = buffer.read()?;
+                let hash_algorithm = buffer.read()?;
+                let digest = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::ZONEMD {
+                    domain,
+                    class,
+                    ttl,
+                    serial,
+                    scheme,
+                    hash_algorithm,
+                    digest,
+                })
+            }
+            QueryType::NID | QueryType::L64 => {
+                let preference = buffer.read_u16()?;
+                let value = (buffer.read_u32()? as u64) << 32 | buffer.read_u32()? as u64;
+
+                if qtype == QueryType::NID {
+                    Ok(DnsRecord::NID {
+                        domain,
+                        class,
+                        ttl,
+                        preference,
+                        node_id: value,
+                    })
+                } else {
+                    Ok(DnsRecord::L64 {
+                        domain,
+                        class,
+                        ttl,
+                        preference,
+                        locator: value,
+                    })
+                }
+            }
+            QueryType::L32 => {
+                let preference = buffer.read_u16()?;
+                let mut octets = [0u8; 4];
+                octets.copy_from_slice(buffer.read_bytes(4)?);
+
+                Ok(DnsRecord::L32 {
+                    domain,
+                    class,
+                    ttl,
+                    preference,
+                    locator: Ipv4Addr::from(octets),
+                })
+            }
+            QueryType::LP => {
+                let preference = buffer.read_u16()?;
+                let mut fqdn = Name::root();
+                buffer.read_qname(&mut fqdn)?;
+
+                Ok(DnsRecord::LP {
+                    domain,
+                    class,
+                    ttl,
+                    preference,
+                    fqdn,
+                })
+            }
+            QueryType::EUI48 => {
+                let mut address = [0u8; 6];
+                address.copy_from_slice(buffer.read_bytes(6)?);
+
+                Ok(DnsRecord::EUI48 {
+                    domain,
+                    class,
+                    ttl,
+                    address,
+                })
+            }
+            QueryType::EUI64 => {
+                let mut address = [0u8; 8];
+                address.copy_from_slice(buffer.read_bytes(8)?);
+
+                Ok(DnsRecord::EUI64 {
+                    domain,
+                    class,
+                    ttl,
+                    address,
+                })
+            }
+            QueryType::TKEY => {
+                let mut algorithm = Name::root();
+                buffer.read_qname(&mut algorithm)?;
+                let inception = Timestamp(buffer.read_u32()?);
+                let expiration = Timestamp(buffer.read_u32()?);
+                let mode = buffer.read_u16()?;
+                let error = buffer.read_u16()?;
+                let key_len = buffer.read_u16()? as usize;
+                let key = buffer.read_bytes(key_len)?.to_vec();
+                let other_len = buffer.read_u16()? as usize;
+                let other = buffer.read_bytes(other_len)?.to_vec();
+
+                Ok(DnsRecord::TKEY {
+                    domain,
+                    class,
+                    ttl,
+                    algorithm,
+                    inception,
+                    expiration,
+                    mode,
+                    error,
+                    key,
+                    other,
+                })
+            }
+            QueryType::TSIG => {
+                let mut algorithm = Name::root();
+                buffer.read_qname(&mut algorithm)?;
+                let time_signed = (buffer.read_u16()? as u64) << 32 | buffer.read_u32()? as u64;
+                let fudge = buffer.read_u16()?;
+                let mac_len = buffer.read_u16()? as usize;
+                let mac = buffer.read_bytes(mac_len)?.to_vec();
+                let original_id = buffer.read_u16()?;
+                let error = buffer.read_u16()?;
+                let other_len = buffer.read_u16()? as usize;
+                let other = buffer.read_bytes(other_len)?.to_vec();
+
+                Ok(DnsRecord::TSIG {
+                    domain,
+                    class,
+                    ttl,
+                    algorithm,
+                    time_signed,
+                    fudge,
+                    mac,
+                    original_id,
+                    error,
+                    other,
+                })
+            }
+            QueryType::DOA => {
+                let enterprise = buffer.read_u32()?;
+                let doa_type = buffer.read_u32()?;
+                let location = buffer.read()?;
+                let media_type = buffer.read_character_string()?.to_vec();
+                let data = read_remaining(buffer, rdata_end)?;
+
+                Ok(DnsRecord::DOA {
+                    domain,
+                    class,
+                    ttl,
+                    enterprise,
+                    doa_type,
+                    location,
+                    media_type,
+                    data,
+                })
+            }
+            QueryType::AMTRELAY if len >= 2 => {
+                let precedence = buffer.read()?;
+                let flags = buffer.read()?;
+                match Gateway::read(buffer, flags & 0x7f, rdata_end)? {
+                    Some(relay) => Ok(DnsRecord::AMTRELAY {
+                        domain,
+                        class,
+                        ttl,
+                        precedence,
+                        discovery_optional: flags & 0x80 != 0,
+                        relay,
+                    }),
+                    None => {
+                        buffer.seek(rdata_start)?;
+                        Ok(DnsRecord::UNKNOWN {
+                            domain,
+                            qtype,
+                            class,
+                            ttl,
+                            data: read_remaining(buffer, rdata_end)?,
+                        })
+                    }
+                }
+            }
+            _ => {
+                let data = buffer.read_bytes(len as usize)?.to_vec();
+                Ok(DnsRecord::UNKNOWN {
+                    domain,
+                    qtype,
+                    class,
+                    ttl,
+                    data,
+                })
+            }
+        }?;
+
+        let actual = buffer.pos() - rdata_start;
+        if actual != len as usize {
+            return Err(ParseError::RdataLengthMismatch {
+                offset: rdata_start,
+                expected: len as usize,
+                actual,
+            });
+        }
+
+        Ok(record)
+    }
+
+    pub fn write<S: Storage>(
+        &self,
+        buffer: &mut BytePacketBuffer<S>,
+    ) -> Result<usize, EncodeError> {
+        let start_pos = buffer.pos();
+        match self {
+            DnsRecord::A {
+                domain,
+                class,
+                ttl,
+                addr: ip,
+            } => {
+                buffer.write_qname(domain)?;
+                buffer.write_u16(QueryType::A.to_num())?;
+                buffer.write_u16(class.to_num())?;
+                buffer.write_u32(*ttl)?;
+
+                let ip_octets = ip.octets();
+                buffer.write_u16(ip_octets.len() as u16)?;
+                buffer.write_bytes(&ip_octets)?;
+
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NS {
+                domain,
+                class,
+                host,
+                ttl,
+            } => {
+                buffer.write_qname(domain)?;
+                buffer.write_u16(QueryType::NS.to_num())?;
+                buffer.write_u16(class.to_num())?;
+                buffer.write_u32(*ttl)?;
+
+                let rdlength_pos = buffer.pos();
+                buffer.write_u16(0)?;
+
+                let rdata_start = buffer.pos();
+
+                buffer.write_qname(host)?;
+
+                let rdata_len = (buffer.pos() - rdata_start) as u16;
+
+                buffer.set_u16(rdlength_pos, rdata_len)?;
+
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::SOA {
+                domain,
+                class,
+                ttl,
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => {
+                buffer.write_qname(domain)?;
+                buffer.write_u16(QueryType::SOA.to_num())?;
+                buffer.write_u16(class.to_num())?;
+                buffer.write_u32(*ttl)?;
+
+                let rdlength_pos = buffer.pos();
+                buffer.write_u16(0)?;
+                let rdata_start = buffer.pos();
+
+                buffer.write_qname(mname)?;
+                buffer.write_qname(rname)?;
+                buffer.write_u32(*serial)?;
+                buffer.write_u32(*refresh)?;
+                buffer.write_u32(*retry)?;
+                buffer.write_u32(*expire)?;
+                buffer.write_u32(*minimum)?;
+
+                let rdata_len = (buffer.pos() - rdata_start) as u16;
+                buffer.set_u16(rdlength_pos, rdata_len)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::CNAME {
+                domain,
+                host,
+                class,
+                ttl,
+            } => {
+                buffer.write_qname(domain)?;
+                buffer.write_u16(QueryType::CNAME.to_num())?;
+                buffer.write_u16(class.to_num())?;
+                buffer.write_u32(*ttl)?;
+
+                let pos = buffer.pos();
+                buffer.write_u16(0)?;
+
+                buffer.write_qname(host)?;
+
+                let size = buffer.pos() - (pos + 2);
+                buffer.set_u16(pos, size as u16)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::MX {
+                domain,
+                class,
+                priority,
+                host,
+                ttl,
+            } => {
+                buffer.write_qname(domain)?;
+                buffer.write_u16(QueryType::MX.to_num())?;
+                buffer.write_u16(class.to_num())?;
+                buffer.write_u32(*ttl)?;
+
+                let pos = buffer.pos();
+                buffer.write_u16(0)?;
+
+                buffer.write_u16(*priority)?;
+                buffer.write_qname(host)?;
+
+                let size = buffer.pos() - (pos + 2);
+                buffer.set_u16(pos, size as u16)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::PTR {
+                domain,
+                class,
+                host,
+                ttl,
+            } => {
+                buffer.write_qname(domain)?;
+                buffer.write_u16(QueryType::PTR.to_num())?;
+                buffer.write_u16(class.to_num())?;
+                buffer.write_u32(*ttl)?;
+
+                let rdlength_pos = buffer.pos();
+                buffer.write_u16(0)?;
+                let rdata_start = buffer.pos();
+
+                buffer.write_qname(host)?;
+
+                let rdata_len = (buffer.pos() - rdata_start) as u16;
+                buffer.set_u16(rdlength_pos, rdata_len)?;
+
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::TXT {
+                domain,
+                class,
+                ttl,
+                data,
+            }
+            | DnsRecord::SPF {
+                domain,
+                class,
+                ttl,
+                data,
+            }
+            | DnsRecord::AVC {
+                domain,
+                class,
+                ttl,
+                data,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, self.qtype(), *class, *ttl)?;
+
+                for bytes in data.iter() {
+                    buffer.write_character_string(bytes)?;
+                }
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::HINFO {
+                domain,
+                class,
+                ttl,
+                cpu,
+                os,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::HINFO, *class, *ttl)?;
+
+                buffer.write_character_string(cpu)?;
+                buffer.write_character_string(os)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::MINFO {
+                domain,
+                class,
+                ttl,
+                rmailbx,
+                emailbx,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::MINFO, *class, *ttl)?;
+
+                // MINFO is an RFC 1035 type, so its names may be compressed.
+                buffer.write_qname(rmailbx)?;
+                buffer.write_qname(emailbx)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::RP {
+                domain,
+                class,
+                ttl,
+                mbox,
+                txt,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::RP, *class, *ttl)?;
+
+                // Types defined after RFC 1035 are sent uncompressed
+                // (RFC 3597 section 4).
+                buffer.write_qname_uncompressed(mbox)?;
+                buffer.write_qname_uncompressed(txt)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::AFSDB {
+                domain,
+                class,
+                ttl,
+                subtype,
+                host,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::AFSDB, *class, *ttl)?;
+
+                buffer.write_u16(*subtype)?;
+                buffer.write_qname_uncompressed(host)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::X25 {
+                domain,
+                class,
+                ttl,
+                address,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::X25, *class, *ttl)?;
+
+                buffer.write_character_string(address)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::ISDN {
+                domain,
+                class,
+                ttl,
+                address,
+                subaddress,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::ISDN, *class, *ttl)?;
+
+                buffer.write_character_string(address)?;
+                if let Some(subaddress) = subaddress {
+                    buffer.write_character_string(subaddress)?;
+                }
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::RT {
+                domain,
+                class,
+                ttl,
+                preference,
+                host,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::RT, *class, *ttl)?;
+
+                buffer.write_u16(*preference)?;
+                buffer.write_qname_uncompressed(host)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::PX {
+                domain,
+                class,
+                ttl,
+                preference,
+                map822,
+                mapx400,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::PX, *class, *ttl)?;
+
+                buffer.write_u16(*preference)?;
+                buffer.write_qname_uncompressed(map822)?;
+                buffer.write_qname_uncompressed(mapx400)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::KX {
+                domain,
+                class,
+                ttl,
+                preference,
+                exchanger,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::KX, *class, *ttl)?;
+
+                buffer.write_u16(*preference)?;
+                buffer.write_qname_uncompressed(exchanger)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::AAAA {
+                domain,
+                addr,
+                class,
+                ttl,
+            } => {
+                buffer.write_qname(domain)?;
+                buffer.write_u16(QueryType::AAAA.to_num())?;
+                buffer.write_u16(class.to_num())?;
+                buffer.write_u32(*ttl)?;
+                buffer.write_u16(16)?;
+                buffer.write_bytes(&addr.octets())?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::LOC {
+                domain,
+                class,
+                ttl,
+                location,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::LOC, *class, *ttl)?;
+
+                buffer.write_bytes(&location.to_wire())?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::SRV {
+                domain,
+                class,
+                ttl,
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::SRV, *class, *ttl)?;
+
+                buffer.write_u16(*priority)?;
+                buffer.write_u16(*weight)?;
+                buffer.write_u16(*port)?;
+                // RFC 2782 forbids compressing the target.
+                buffer.write_qname_uncompressed(target)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NAPTR {
+                domain,
+                class,
+                ttl,
+                order,
+                preference,
+                flags,
+                services,
+                regexp,
+                replacement,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::NAPTR, *class, *ttl)?;
+
+                buffer.write_u16(*order)?;
+                buffer.write_u16(*preference)?;
+                buffer.write_character_string(flags)?;
+                buffer.write_character_string(services)?;
+                buffer.write_character_string(regexp)?;
+                // RFC 3403 forbids compressing the replacement.
+                buffer.write_qname_uncompressed(replacement)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::DNAME {
+                domain,
+                class,
+                ttl,
+                host,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::DNAME, *class, *ttl)?;
+
+                // RFC 6672 forbids compressing the target.
+                buffer.write_qname_uncompressed(host)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::CERT {
+                domain,
+                class,
+                ttl,
+                cert_type,
+                key_tag,
+                algorithm,
+                certificate,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::CERT, *class, *ttl)?;
+
+                buffer.write_u16(cert_type.to_num())?;
+                buffer.write_u16(*key_tag)?;
+                buffer.write_u8(*algorithm)?;
+                buffer.write_bytes(certificate)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::SSHFP {
+                domain,
+                class,
+                ttl,
+                algorithm,
+                fingerprint_type,
+                fingerprint,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::SSHFP, *class, *ttl)?;
+
+                buffer.write_u8(algorithm.to_num())?;
+                buffer.write_u8(fingerprint_type.to_num())?;
+                buffer.write_bytes(fingerprint)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::TLSA {
+                domain,
+                class,
+                ttl,
+                usage,
+                selector,
+                matching_type,
+                association_data,
+            }
+            | DnsRecord::SMIMEA {
+                domain,
+                class,
+                ttl,
+                usage,
+                selector,
+                matching_type,
+                association_data,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, self.qtype(), *class, *ttl)?;
+
+                buffer.write_u8(usage.to_num())?;
+                buffer.write_u8(selector.to_num())?;
+                buffer.write_u8(matching_type.to_num())?;
+                buffer.write_bytes(association_data)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::OPENPGPKEY {
+                domain,
+                class,
+                ttl,
+                public_key,
+            } => {
+                let rdlength_pos =
+                    write_preamble(buffer, domain, QueryType::OPENPGPKEY, *class, *ttl)?;
+
+                buffer.write_bytes(public_key)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::DS {
+                domain,
+                class,
+                ttl,
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            }
+            | DnsRecord::CDS {
+                domain,
+                class,
+                ttl,
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            }
+            | DnsRecord::TA {
+                domain,
+                class,
+                ttl,
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            }
+            | DnsRecord::DLV {
+                domain,
+                class,
+                ttl,
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, self.qtype(), *class, *ttl)?;
+
+                buffer.write_u16(*key_tag)?;
+                buffer.write_u8(*algorithm)?;
+                buffer.write_u8(*digest_type)?;
+                buffer.write_bytes(digest)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::RRSIG {
+                domain,
+                class,
+                ttl,
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer_name,
+                signature,
+            }
+            | DnsRecord::SIG {
+                domain,
+                class,
+                ttl,
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer_name,
+                signature,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, self.qtype(), *class, *ttl)?;
+
+                buffer.write_u16(type_covered.to_num())?;
+                buffer.write_u8(*algorithm)?;
+                buffer.write_u8(*labels)?;
+                buffer.write_u32(*original_ttl)?;
+                buffer.write_u32(expiration.0)?;
+                buffer.write_u32(inception.0)?;
+                buffer.write_u16(*key_tag)?;
+                // RFC 4034 forbids compressing the signer's name.
+                buffer.write_qname_uncompressed(signer_name)?;
+                buffer.write_bytes(signature)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NSEC {
+                domain,
+                class,
+                ttl,
+                next_domain,
+                types,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::NSEC, *class, *ttl)?;
+
+                buffer.write_qname_uncompressed(next_domain)?;
+                types.write(buffer)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::DNSKEY {
+                domain,
+                class,
+                ttl,
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+            }
+            | DnsRecord::CDNSKEY {
+                domain,
+                class,
+                ttl,
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+            }
+            | DnsRecord::KEY {
+                domain,
+                class,
+                ttl,
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, self.qtype(), *class, *ttl)?;
+
+                buffer.write_u16(*flags)?;
+                buffer.write_u8(*protocol)?;
+                buffer.write_u8(*algorithm)?;
+                buffer.write_bytes(public_key)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NSEC3 {
+                domain,
+                class,
+                ttl,
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+                next_hashed_owner,
+                types,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::NSEC3, *class, *ttl)?;
+
+                buffer.write_u8(*hash_algorithm)?;
+                buffer.write_u8(*flags)?;
+                buffer.write_u16(*iterations)?;
+                buffer.write_character_string(salt)?;
+                if next_hashed_owner.is_empty() {
+                    return Err(EncodeError::InvalidRdata {
+                        offset: buffer.pos(),
+                        reason: "NSEC3 next hashed owner is empty",
+                    });
+                }
+                buffer.write_character_string(next_hashed_owner)?;
+                types.write(buffer)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NSEC3PARAM {
+                domain,
+                class,
+                ttl,
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+            } => {
+                let rdlength_pos =
+                    write_preamble(buffer, domain, QueryType::NSEC3PARAM, *class, *ttl)?;
+
+                buffer.write_u8(*hash_algorithm)?;
+                buffer.write_u8(*flags)?;
+                buffer.write_u16(*iterations)?;
+                buffer.write_character_string(salt)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::SVCB {
+                domain,
+                class,
+                ttl,
+                priority,
+                target,
+                params,
+            }
+            | DnsRecord::HTTPS {
+                domain,
+                class,
+                ttl,
+                priority,
+                target,
+                params,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, self.qtype(), *class, *ttl)?;
+
+                buffer.write_u16(*priority)?;
+                // RFC 9460 forbids compressing the target.
+                buffer.write_qname_uncompressed(target)?;
+                params.write(buffer)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::URI {
+                domain,
+                class,
+                ttl,
+                priority,
+                weight,
+                target,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::URI, *class, *ttl)?;
+
+                buffer.write_u16(*priority)?;
+                buffer.write_u16(*weight)?;
+                buffer.write_bytes(target)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::CAA {
+                domain,
+                class,
+                ttl,
+                flags,
+                tag,
+                value,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::CAA, *class, *ttl)?;
+
+                buffer.write_u8(*flags)?;
+                if !caa::is_valid_tag(tag.as_bytes()) {
+                    return Err(EncodeError::InvalidRdata {
+                        offset: buffer.pos(),
+                        reason: "CAA tag must be non-empty and alphanumeric",
+                    });
+                }
+                buffer.write_character_string(tag.as_bytes())?;
+                buffer.write_bytes(value)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NSAP {
+                domain,
+                class,
+                ttl,
+                address,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::NSAP, *class, *ttl)?;
+
+                buffer.write_bytes(address)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NsapPtr {
+                domain,
+                class,
+                ttl,
+                host,
+            } => {
+                let rdlength_pos =
+                    write_preamble(buffer, domain, QueryType::NsapPtr, *class, *ttl)?;
+
+                buffer.write_qname_uncompressed(host)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::APL {
+                domain,
+                class,
+                ttl,
+                items,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::APL, *class, *ttl)?;
+
+                for item in items {
+                    item.write(buffer)?;
+                }
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::IPSECKEY {
+                domain,
+                class,
+                ttl,
+                precedence,
+                algorithm,
+                gateway,
+                public_key,
+            } => {
+                let rdlength_pos =
+                    write_preamble(buffer, domain, QueryType::IPSECKEY, *class, *ttl)?;
+
+                buffer.write_u8(*precedence)?;
+                buffer.write_u8(gateway.type_num())?;
+                buffer.write_u8(*algorithm)?;
+                gateway.write(buffer)?;
+                buffer.write_bytes(public_key)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::DHCID {
+                domain,
+                class,
+                ttl,
+                data,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::DHCID, *class, *ttl)?;
+
+                buffer.write_bytes(data)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::HIP {
+                domain,
+                class,
+                ttl,
+                pk_algorithm,
+                hit,
+                public_key,
+                rendezvous_servers,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::HIP, *class, *ttl)?;
+
+                let hit_len = u8::try_from(hit.len())
+                    .ok()
+                    .filter(|_| !hit.is_empty() && !public_key.is_empty())
+                    .ok_or(EncodeError::InvalidRdata {
+                        offset: buffer.pos(),
+                        reason: "HIP HIT must be 1 to 255 bytes and the public key non-empty",
+                    })?;
+                buffer.write_u8(hit_len)?;
+                buffer.write_u8(*pk_algorithm)?;
+                write_u16_length(buffer, public_key.len())?;
+                buffer.write_bytes(hit)?;
+                buffer.write_bytes(public_key)?;
+                for server in rendezvous_servers {
+                    // RFC 8005 forbids compressing rendezvous servers.
+                    buffer.write_qname_uncompressed(server)?;
+                }
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::CSYNC {
+                domain,
+                class,
+                ttl,
+                serial,
+                flags,
+                types,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::CSYNC, *class, *ttl)?;
+
+                buffer.write_u32(*serial)?;
+                buffer.write_u16(*flags)?;
+                types.write(buffer)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::ZONEMD {
+                domain,
+                class,
+                ttl,
+                serial,
+                scheme,
+                hash_algorithm,
+                digest,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::ZONEMD, *class, *ttl)?;
+
+                buffer.write_u32(*serial)?;
+                buffer.write_u8(*scheme)?;
+                buffer.write_u8(*hash_algorithm)?;
+                buffer.write_bytes(digest)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::NID {
                 domain,
                 class,
                 ttl,
-                addr: ip,
+                preference,
+                node_id,
+            }
+            | DnsRecord::L64 {
+                domain,
+                class,
+                ttl,
+                preference,
+                locator: node_id,
             } => {
-                buffer.write_qname(domain.as_str())?;
-                buffer.write_u16(QueryType::A.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
+                let rdlength_pos = write_preamble(buffer, domain, self.qtype(), *class, *ttl)?;
 
-                let ip_octets = ip.octets();
-                buffer.write_u16(ip_octets.len() as u16)?;
-
-                for octet in ip_octets.iter() {
-                    buffer.write_u8(*octet)?;
-                }
+                buffer.write_u16(*preference)?;
+                buffer.write_bytes(&node_id.to_be_bytes())?;
 
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
-            DnsRecord::NS {
+            DnsRecord::L32 {
                 domain,
                 class,
-                host,
                 ttl,
+                preference,
+                locator,
             } => {
-                buffer.write_qname(domain.as_str())?;
-                buffer.write_u16(QueryType::NS.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::L32, *class, *ttl)?;
 
-                let rdlength_pos = buffer.pos();
-                buffer.write_u16(0)?;
-
-                let rdata_start = buffer.pos();
-
-                buffer.write_qname(host)?;
-
-                let rdata_len = (buffer.pos() - rdata_start) as u16;
-
-                buffer.set_u16(rdlength_pos, rdata_len)?;
+                buffer.write_u16(*preference)?;
+                buffer.write_bytes(&locator.octets())?;
 
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
-            DnsRecord::SOA {
+            DnsRecord::LP {
                 domain,
                 class,
                 ttl,
-                mname,
-                rname,
-                serial,
-                refresh,
-                retry,
-                expire,
-                minimum,
+                preference,
+                fqdn,
             } => {
-                buffer.write_qname(domain.as_str())?;
-                buffer.write_u16(QueryType::SOA.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
-
-                let rdlength_pos = buffer.pos();
-                buffer.write_u16(0)?;
-                let rdata_start = buffer.pos();
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::LP, *class, *ttl)?;
 
-                buffer.write_qname(mname)?;
-                buffer.write_qname(rname)?;
-                buffer.write_u32(*serial)?;
-                buffer.write_u32(*refresh)?;
-                buffer.write_u32(*retry)?;
-                buffer.write_u32(*expire)?;
-                buffer.write_u32(*minimum)?;
+                buffer.write_u16(*preference)?;
+                buffer.write_qname_uncompressed(fqdn)?;
 
-                let rdata_len = (buffer.pos() - rdata_start) as u16;
-                buffer.set_u16(rdlength_pos, rdata_len)?;
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
-            DnsRecord::CNAME {
+            DnsRecord::EUI48 {
                 domain,
-                host,
                 class,
                 ttl,
+                address,
             } => {
-                buffer.write_qname(domain)?;
-                buffer.write_u16(QueryType::CNAME.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
-
-                let pos = buffer.pos();
-                buffer.write_u16(0)?;
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::EUI48, *class, *ttl)?;
 
-                buffer.write_qname(host)?;
+                buffer.write_bytes(address)?;
 
-                let size = buffer.pos() - (pos + 2);
-                buffer.set_u16(pos, size as u16)?;
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
-            DnsRecord::MX {
+            DnsRecord::EUI64 {
                 domain,
                 class,
-                priority,
-                host,
                 ttl,
+                address,
             } => {
-                buffer.write_qname(domain)?;
-                buffer.write_u16(QueryType::MX.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
-
-                let pos = buffer.pos();
-                buffer.write_u16(0)?;
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::EUI64, *class, *ttl)?;
 
-                buffer.write_u16(*priority)?;
-                buffer.write_qname(host)?;
+                buffer.write_bytes(address)?;
 
-                let size = buffer.pos() - (pos + 2);
-                buffer.set_u16(pos, size as u16)?;
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
-            DnsRecord::PTR {
+            DnsRecord::TKEY {
                 domain,
                 class,
-                host,
                 ttl,
+                algorithm,
+                inception,
+                expiration,
+                mode,
+                error,
+                key,
+                other,
             } => {
-                buffer.write_qname(domain)?;
-                buffer.write_u16(QueryType::PTR.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::TKEY, *class, *ttl)?;
 
-                let rdlength_pos = buffer.pos();
-                buffer.write_u16(0)?;
-                let rdata_start = buffer.pos();
+                buffer.write_qname_uncompressed(algorithm)?;
+                buffer.write_u32(inception.0)?;
+                buffer.write_u32(expiration.0)?;
+                buffer.write_u16(*mode)?;
+                buffer.write_u16(*error)?;
+                write_u16_length(buffer, key.len())?;
+                buffer.write_bytes(key)?;
+                write_u16_length(buffer, other.len())?;
+                buffer.write_bytes(other)?;
 
-                buffer.write_qname(host)?;
+                finish_rdata(buffer, rdlength_pos)?;
+                Ok(buffer.pos() - start_pos)
+            }
+            DnsRecord::TSIG {
+                domain,
+                class,
+                ttl,
+                algorithm,
+                time_signed,
+                fudge,
+                mac,
+                original_id,
+                error,
+                other,
+            } => {
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::TSIG, *class, *ttl)?;
 
-                let rdata_len = (buffer.pos() - rdata_start) as u16;
-                buffer.set_u16(rdlength_pos, rdata_len)?;
+                if *time_signed >> 48 != 0 {
+                    return Err(EncodeError::InvalidRdata {
+                        offset: buffer.pos(),
+                        reason: "TSIG time does not fit in 48 bits",
+                    });
+                }
+                buffer.write_qname_uncompressed(algorithm)?;
+                buffer.write_bytes(&time_signed.to_be_bytes()[2..])?;
+                buffer.write_u16(*fudge)?;
+                write_u16_length(buffer, mac.len())?;
+                buffer.write_bytes(mac)?;
+                buffer.write_u16(*original_id)?;
+                buffer.write_u16(*error)?;
+                write_u16_length(buffer, other.len())?;
+                buffer.write_bytes(other)?;
 
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
-            DnsRecord::TXT {
+            DnsRecord::DOA {
                 domain,
                 class,
                 ttl,
+                enterprise,
+                doa_type,
+                location,
+                media_type,
                 data,
             } => {
-                buffer.write_qname(domain)?;
-                buffer.write_u16(QueryType::TXT.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
-
-                let rdlength_pos = buffer.pos();
-                buffer.write_u16(0)?;
-                let rdata_start = buffer.pos();
+                let rdlength_pos = write_preamble(buffer, domain, QueryType::DOA, *class, *ttl)?;
 
-                for txt in data.iter() {
-                    let bytes = txt.as_bytes();
-                    buffer.write_u8(bytes.len() as u8)?;
-                    for byte in bytes {
-                        buffer.write_u8(*byte)?;
-                    }
-                }
+                buffer.write_u32(*enterprise)?;
+                buffer.write_u32(*doa_type)?;
+                buffer.write_u8(*location)?;
+                buffer.write_character_string(media_type)?;
+                buffer.write_bytes(data)?;
 
-                let rdata_len = (buffer.pos() - rdata_start) as u16;
-                buffer.set_u16(rdlength_pos, rdata_len)?;
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
-            DnsRecord::AAAA {
+            DnsRecord::AMTRELAY {
                 domain,
-                addr,
                 class,
                 ttl,
+                precedence,
+                discovery_optional,
+                relay,
             } => {
-                buffer.write_qname(domain)?;
-                buffer.write_u16(QueryType::AAAA.to_num())?;
-                buffer.write_u16(*class)?;
-                buffer.write_u32(*ttl)?;
-                buffer.write_u16(16)?;
+                let rdlength_pos =
+                    write_preamble(buffer, domain, QueryType::AMTRELAY, *class, *ttl)?;
 
-                for octet in &addr.segments() {
-                    buffer.write_u16(*octet)?;
-                }
+                buffer.write_u8(*precedence)?;
+                buffer.write_u8((*discovery_optional as u8) << 7 | relay.type_num())?;
+                relay.write(buffer)?;
+
+                finish_rdata(buffer, rdlength_pos)?;
                 Ok(buffer.pos() - start_pos)
             }
             DnsRecord::UNKNOWN {
@@ -431,13 +2835,16 @@ impl DnsRecord {
             } => {
                 buffer.write_qname(domain)?;
                 buffer.write_u16(qtype.to_num())?;
-                buffer.write_u16(*class)?;
+                buffer.write_u16(class.to_num())?;
                 buffer.write_u32(*ttl)?;
-                buffer.write_u16(data.len() as u16)?;
-
-                for byte in data.iter() {
-                    buffer.write_u8(*byte)?;
+                if data.len() > u16::MAX as usize {
+                    return Err(EncodeError::RdataTooLong {
+                        offset: buffer.pos(),
+                        len: data.len(),
+                    });
                 }
+                buffer.write_u16(data.len() as u16)?;
+                buffer.write_bytes(data)?;
 
                 Ok(buffer.pos() - start_pos)
             }
@@ -445,37 +2852,157 @@ impl DnsRecord {
     }
 }
 
+/// Reads the rest of the RDATA as opaque bytes.
+fn read_remaining<S: Storage>(
+    buffer: &mut BytePacketBuffer<S>,
+    rdata_end: usize,
+) -> Result<Vec<u8>, ParseError> {
+    let len = rdata_end.saturating_sub(buffer.pos());
+    Ok(buffer.read_bytes(len)?.to_vec())
+}
+
+/// Writes the 16-bit length that precedes a variable-length field.
+fn write_u16_length<S: Storage>(
+    buffer: &mut BytePacketBuffer<S>,
+    len: usize,
+) -> Result<(), EncodeError> {
+    let len = u16::try_from(len).map_err(|_| EncodeError::RdataTooLong {
+        offset: buffer.pos(),
+        len,
+    })?;
+    buffer.write_u16(len)
+}
+
+/// Writes the owner, type, class and TTL followed by a placeholder RDLENGTH,
+/// returning the placeholder's position for `finish_rdata`.
+fn write_preamble<S: Storage>(
+    buffer: &mut BytePacketBuffer<S>,
+    domain: &Name,
+    qtype: QueryType,
+    class: DnsClass,
+    ttl: u32,
+) -> Result<usize, EncodeError> {
+    buffer.write_qname(domain)?;
+    buffer.write_u16(qtype.to_num())?;
+    buffer.write_u16(class.to_num())?;
+    buffer.write_u32(ttl)?;
+
+    let rdlength_pos = buffer.pos();
+    buffer.write_u16(0)?;
+    Ok(rdlength_pos)
+}
+
+/// Fills in the RDLENGTH placeholder with the size of the RDATA written
+/// since.
+fn finish_rdata<S: Storage>(
+    buffer: &mut BytePacketBuffer<S>,
+    rdlength_pos: usize,
+) -> Result<(), EncodeError> {
+    let rdata_start = rdlength_pos + 2;
+    let rdata_len = buffer.pos() - rdata_start;
+    if rdata_len > u16::MAX as usize {
+        return Err(EncodeError::RdataTooLong {
+            offset: rdata_start,
+            len: rdata_len,
+        });
+    }
+    buffer.set_u16(rdlength_pos, rdata_len as u16)
+}
+
 #[cfg(test)]
 mod tests {
     use super::DnsRecord;
-    use crate::{buffer::BytePacketBuffer, types::QueryType};
-    use std::net::Ipv6Addr;
+    use crate::{
+        buffer::BytePacketBuffer,
+        error::{EncodeError, ParseError},
+        name::Name,
+        rdata::{
+            apl::AplItem,
+            cert::CertType,
+            dnssec::{Timestamp, TypeBitmap},
+            gateway::Gateway,
+            sshfp::{SshfpAlgorithm, SshfpFingerprintType},
+            svcb::SvcParams,
+            tlsa::{TlsaMatchingType, TlsaSelector, TlsaUsage},
+        },
+        types::{DnsClass, QueryType},
+    };
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    /// Writes `record`, reads it back and writes the result again, checking
+    /// that neither the record nor its wire form changes.
+    fn assert_wire_roundtrip(record: &DnsRecord) {
+        let mut buffer = BytePacketBuffer::new();
+        let len = record.write(&mut buffer).unwrap();
+        buffer.seek(0).unwrap();
+        let parsed = DnsRecord::read(&mut buffer).unwrap();
+        assert_eq!(*record, parsed);
+        assert_eq!(len, buffer.pos());
+
+        let mut rewritten = BytePacketBuffer::new();
+        assert_eq!(len, parsed.write(&mut rewritten).unwrap());
+        assert_eq!(buffer.get_range(0, len), rewritten.get_range(0, len));
+    }
 
     #[test]
     fn aaaa_record_roundtrip() {
         let record = DnsRecord::AAAA {
-            domain: "ipv6.test".into(),
+            domain: "ipv6.test".parse().unwrap(),
             addr: Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
-            class: 1,
+            class: DnsClass::IN,
             ttl: 600,
         };
 
         let mut buffer = BytePacketBuffer::new();
         record.write(&mut buffer).unwrap();
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
 
         let parsed = DnsRecord::read(&mut buffer).unwrap();
         assert_eq!(record, parsed);
     }
 
+    #[test]
+    fn mdns_cache_flush_bit_is_split_from_the_class() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"a.local".parse().unwrap()).unwrap();
+        buffer.write_u16(QueryType::A.to_num()).unwrap();
+        buffer.write_u16(0x8001).unwrap();
+        buffer.write_u32(120).unwrap();
+        buffer.write_u16(4).unwrap();
+        buffer.write_bytes(&[10, 0, 0, 1]).unwrap();
+        let written = buffer.pos();
+
+        buffer.seek(0).unwrap();
+        let record = DnsRecord::read(&mut buffer).unwrap();
+        assert!(record.cache_flush());
+        assert_eq!(DnsClass::IN, record.base_class());
+
+        // The raw class is kept, so the bit survives writing the record again.
+        let mut rewritten = BytePacketBuffer::new();
+        record.write(&mut rewritten).unwrap();
+        assert_eq!(
+            buffer.get_range(0, written),
+            rewritten.get_range(0, written)
+        );
+
+        let plain = DnsRecord::A {
+            domain: "a.local".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 120,
+            addr: Ipv4Addr::new(10, 0, 0, 1),
+        };
+        assert!(!plain.cache_flush());
+        assert_eq!(DnsClass::IN, plain.base_class());
+    }
+
     #[test]
     fn soa_record_roundtrip() {
         let record = DnsRecord::SOA {
-            domain: "example.com".into(),
-            class: 1,
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
             ttl: 3600,
-            mname: "ns1.example.com".into(),
-            rname: "hostmaster.example.com".into(),
+            mname: "ns1.example.com".parse().unwrap(),
+            rname: "hostmaster.example.com".parse().unwrap(),
             serial: 20240101,
             refresh: 7200,
             retry: 600,
@@ -485,7 +3012,7 @@ mod tests {
 
         let mut buffer = BytePacketBuffer::new();
         record.write(&mut buffer).unwrap();
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
 
         let parsed = DnsRecord::read(&mut buffer).unwrap();
         assert_eq!(record, parsed);
@@ -494,15 +3021,15 @@ mod tests {
     #[test]
     fn txt_record_roundtrip() {
         let record = DnsRecord::TXT {
-            domain: "txt.example".into(),
-            class: 1,
+            domain: "txt.example".parse().unwrap(),
+            class: DnsClass::IN,
             ttl: 450,
             data: vec!["v=spf1 -all".into(), "hello world".into()],
         };
 
         let mut buffer = BytePacketBuffer::new();
         record.write(&mut buffer).unwrap();
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
 
         let parsed = DnsRecord::read(&mut buffer).unwrap();
         assert_eq!(record, parsed);
@@ -511,15 +3038,15 @@ mod tests {
     #[test]
     fn txt_record_supports_empty_segments() {
         let record = DnsRecord::TXT {
-            domain: "empty.txt".into(),
-            class: 1,
+            domain: "empty.txt".parse().unwrap(),
+            class: DnsClass::IN,
             ttl: 300,
             data: vec!["".into(), "segment".into()],
         };
 
         let mut buffer = BytePacketBuffer::new();
         record.write(&mut buffer).unwrap();
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
 
         let parsed = DnsRecord::read(&mut buffer).unwrap();
         assert_eq!(record, parsed);
@@ -528,24 +3055,839 @@ mod tests {
     #[test]
     fn ptr_record_roundtrip() {
         let record = DnsRecord::PTR {
-            domain: "4.3.2.1.in-addr.arpa".into(),
-            class: 1,
+            domain: "4.3.2.1.in-addr.arpa".parse().unwrap(),
+            class: DnsClass::IN,
             ttl: 86400,
-            host: "example.com".into(),
+            host: "example.com".parse().unwrap(),
         };
 
         let mut buffer = BytePacketBuffer::new();
         record.write(&mut buffer).unwrap();
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
+
+        let parsed = DnsRecord::read(&mut buffer).unwrap();
+        assert_eq!(record, parsed);
+    }
+
+    #[test]
+    fn srv_record_roundtrip_without_target_compression() {
+        let record = DnsRecord::SRV {
+            domain: "_sip._tcp.example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 300,
+            priority: 10,
+            weight: 60,
+            port: 5060,
+            target: "example.com".parse().unwrap(),
+        };
+
+        let mut buffer = BytePacketBuffer::new();
+        let written = record.write(&mut buffer).unwrap();
+        // owner (23) + fixed fields (10) + rdata (6 + 13): the target is
+        // written in full even though it is a suffix of the owner.
+        assert_eq!(52, written);
+        buffer.seek(0).unwrap();
 
         let parsed = DnsRecord::read(&mut buffer).unwrap();
         assert_eq!(record, parsed);
     }
 
+    #[test]
+    fn caa_record_roundtrip_and_tag_validation() {
+        let record = DnsRecord::CAA {
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 3600,
+            flags: 128,
+            tag: "issue".into(),
+            value: b"ca.example.net; account=230123".to_vec(),
+        };
+
+        let mut buffer = BytePacketBuffer::new();
+        record.write(&mut buffer).unwrap();
+        buffer.seek(0).unwrap();
+        assert_eq!(record, DnsRecord::read(&mut buffer).unwrap());
+
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"example.com".parse().unwrap()).unwrap();
+        buffer.write_u16(QueryType::CAA.to_num()).unwrap();
+        buffer.write_u16(1).unwrap();
+        buffer.write_u32(0).unwrap();
+        buffer.write_u16(4).unwrap();
+        buffer.write_bytes(&[0, 2, b'i', b'-']).unwrap();
+        buffer.seek(0).unwrap();
+        let opaque = DnsRecord::read(&mut buffer).unwrap();
+        assert_eq!(
+            DnsRecord::UNKNOWN {
+                domain: "example.com".parse().unwrap(),
+                qtype: QueryType::CAA,
+                class: DnsClass::IN,
+                ttl: 0,
+                data: vec![0, 2, b'i', b'-'],
+            },
+            opaque
+        );
+
+        let invalid = DnsRecord::CAA {
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 3600,
+            flags: 0,
+            tag: String::new(),
+            value: Vec::new(),
+        };
+        assert!(matches!(
+            invalid.write(&mut BytePacketBuffer::new()),
+            Err(EncodeError::InvalidRdata { offset: 24, .. })
+        ));
+    }
+
+    #[test]
+    fn https_record_roundtrip() {
+        let record = DnsRecord::HTTPS {
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 300,
+            priority: 1,
+            target: Name::root(),
+            params: "alpn=h3,h2 ipv4hint=192.0.2.1".parse().unwrap(),
+        };
+
+        let mut buffer = BytePacketBuffer::new();
+        record.write(&mut buffer).unwrap();
+        buffer.seek(0).unwrap();
+        assert_eq!(record, DnsRecord::read(&mut buffer).unwrap());
+
+        let alias = DnsRecord::SVCB {
+            domain: "_8443._foo.api.example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            ttl: 300,
+            priority: 0,
+            target: "svc4.example.net".parse().unwrap(),
+            params: SvcParams::new(),
+        };
+        let mut buffer = BytePacketBuffer::new();
+        alias.write(&mut buffer).unwrap();
+        buffer.seek(0).unwrap();
+        assert_eq!(alias, DnsRecord::read(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn dnssec_records_roundtrip() {
+        let domain: Name = "example.com".parse().unwrap();
+        let records = [
+            DnsRecord::DNSKEY {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                flags: 257,
+                protocol: 3,
+                algorithm: 13,
+                public_key: vec![0x5a; 64],
+            },
+            DnsRecord::CDS {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                key_tag: 2371,
+                algorithm: 13,
+                digest_type: 2,
+                digest: vec![0xc9; 32],
+            },
+            DnsRecord::RRSIG {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                type_covered: QueryType::A,
+                algorithm: 13,
+                labels: 2,
+                original_ttl: 3600,
+                expiration: "20240201000000".parse().unwrap(),
+                inception: Timestamp(1704067200),
+                key_tag: 2371,
+                signer_name: domain.clone(),
+                signature: vec![0xab; 64],
+            },
+            DnsRecord::NSEC {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                next_domain: "www.example.com".parse().unwrap(),
+                types: [QueryType::A, QueryType::RRSIG, QueryType::NSEC]
+                    .into_iter()
+                    .collect(),
+            },
+            DnsRecord::NSEC3 {
+                domain: "2t7b4g4vsa5smi47k61mv5bv1a22bojr.example.com"
+                    .parse()
+                    .unwrap(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                hash_algorithm: 1,
+                flags: 1,
+                iterations: 0,
+                salt: Vec::new(),
+                next_hashed_owner: vec![0x17; 20],
+                types: TypeBitmap::new(),
+            },
+            DnsRecord::NSEC3PARAM {
+                domain,
+                class: DnsClass::IN,
+                ttl: 0,
+                hash_algorithm: 1,
+                flags: 0,
+                iterations: 0,
+                salt: vec![0xaa, 0xbb],
+            },
+        ];
+
+        for record in &records {
+            assert_wire_roundtrip(record);
+        }
+    }
+
+    #[test]
+    fn certificate_records_roundtrip() {
+        let domain: Name = "_25._tcp.mail.example.com".parse().unwrap();
+        let records = [
+            DnsRecord::TLSA {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                usage: TlsaUsage::DANEEE,
+                selector: TlsaSelector::SPKI,
+                matching_type: TlsaMatchingType::SHA256,
+                association_data: vec![0x0c; 32],
+            },
+            DnsRecord::SMIMEA {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                usage: TlsaUsage::UNKNOWN(7),
+                selector: TlsaSelector::CERT,
+                matching_type: TlsaMatchingType::FULL,
+                association_data: vec![0x30, 0x82],
+            },
+            DnsRecord::SSHFP {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                algorithm: SshfpAlgorithm::ED25519,
+                fingerprint_type: SshfpFingerprintType::SHA256,
+                fingerprint: vec![0xd8; 32],
+            },
+            DnsRecord::OPENPGPKEY {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                public_key: vec![0x99, 0x01, 0x0d],
+            },
+            DnsRecord::CERT {
+                domain,
+                class: DnsClass::IN,
+                ttl: 3600,
+                cert_type: CertType::PGP,
+                key_tag: 0,
+                algorithm: 0,
+                certificate: vec![0x01, 0x02, 0x03],
+            },
+        ];
+
+        for record in &records {
+            assert_wire_roundtrip(record);
+        }
+    }
+
+    #[test]
+    fn naptr_uri_and_dname_records_roundtrip() {
+        let records = [
+            DnsRecord::NAPTR {
+                domain: "3.8.0.0.6.9.2.3.6.1.4.4.e164.arpa".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                order: 100,
+                preference: 10,
+                flags: "u".into(),
+                services: "E2U+sip".into(),
+                regexp: r"!^.*$!sip:info@example.com!".into(),
+                replacement: Name::root(),
+            },
+            DnsRecord::URI {
+                domain: "_ftp._tcp.example.com".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                priority: 10,
+                weight: 1,
+                target: "ftp://ftp1.example.com/public".into(),
+            },
+            DnsRecord::NAPTR {
+                domain: "example.com".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                order: 0,
+                preference: 0,
+                flags: vec![0xff],
+                services: b"E2U+\xc3(".to_vec(),
+                regexp: b"!a!\x80!".to_vec(),
+                replacement: Name::root(),
+            },
+            DnsRecord::URI {
+                domain: "_ftp._tcp.example.com".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                priority: 10,
+                weight: 1,
+                target: b"ftp://\xfe\xff/".to_vec(),
+            },
+            DnsRecord::DNAME {
+                domain: "example.com".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                host: "example.net".parse().unwrap(),
+            },
+        ];
+
+        for record in &records {
+            assert_wire_roundtrip(record);
+        }
+    }
+
+    #[test]
+    fn loc_record_roundtrip_is_byte_exact() {
+        let rdata = [
+            0x00, 0x00, 0x16, 0x13, 0x8b, 0x3c, 0xf0, 0x18, 0x81, 0x0c, 0xbc, 0xe0, 0x00, 0x98,
+            0x95, 0xb8,
+        ];
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"example.com".parse().unwrap()).unwrap();
+        buffer.write_u16(QueryType::LOC.to_num()).unwrap();
+        buffer.write_u16(1).unwrap();
+        buffer.write_u32(3600).unwrap();
+        buffer.write_u16(16).unwrap();
+        buffer.write_bytes(&rdata).unwrap();
+        let wire = buffer.buffer[..buffer.pos()].to_vec();
+
+        buffer.seek(0).unwrap();
+        let record = DnsRecord::read(&mut buffer).unwrap();
+        let DnsRecord::LOC { location, .. } = &record else {
+            panic!("expected LOC, got {record:?}");
+        };
+        assert_eq!(
+            "52 22 23.000 N 4 53 32.000 E -2.00m 0.00m 10000m 10m",
+            location.to_string()
+        );
+
+        let mut buffer = BytePacketBuffer::new();
+        record.write(&mut buffer).unwrap();
+        assert_eq!(wire, buffer.buffer[..buffer.pos()]);
+
+        // An unknown version is kept as opaque data.
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_bytes(&wire).unwrap();
+        buffer.set(wire.len() - 16, 1).unwrap();
+        buffer.seek(0).unwrap();
+        assert!(matches!(
+            DnsRecord::read(&mut buffer).unwrap(),
+            DnsRecord::UNKNOWN {
+                qtype: QueryType::LOC,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn legacy_records_roundtrip() {
+        let domain: Name = "example.com".parse().unwrap();
+        let host: Name = "host.example.com".parse().unwrap();
+        let records = [
+            DnsRecord::HINFO {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                cpu: "PDP-11/73".into(),
+                os: "UNIX".into(),
+            },
+            DnsRecord::MINFO {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                rmailbx: "owner.example.com".parse().unwrap(),
+                emailbx: "errors.example.com".parse().unwrap(),
+            },
+            DnsRecord::RP {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                mbox: "admin.example.com".parse().unwrap(),
+                txt: Name::root(),
+            },
+            DnsRecord::AFSDB {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                subtype: 1,
+                host: host.clone(),
+            },
+            DnsRecord::X25 {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: "311061700956".into(),
+            },
+            DnsRecord::ISDN {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: "150862028003217".into(),
+                subaddress: Some("004".into()),
+            },
+            DnsRecord::ISDN {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: "150862028003217".into(),
+                subaddress: None,
+            },
+            DnsRecord::HINFO {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                cpu: b"\xff\xfe".to_vec(),
+                os: vec![0xc3, 0x28],
+            },
+            DnsRecord::X25 {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: vec![0x80],
+            },
+            DnsRecord::ISDN {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: vec![0xed, 0xa0, 0x80],
+                subaddress: Some(vec![0xf8]),
+            },
+            DnsRecord::RT {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                preference: 10,
+                host: host.clone(),
+            },
+            DnsRecord::PX {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                preference: 10,
+                map822: "net2.it".parse().unwrap(),
+                mapx400: "PRMD-net2.ADMD-p400.C-it".parse().unwrap(),
+            },
+            DnsRecord::KX {
+                domain,
+                class: DnsClass::IN,
+                ttl: 3600,
+                preference: 10,
+                exchanger: host,
+            },
+        ];
+
+        for record in &records {
+            assert_wire_roundtrip(record);
+        }
+    }
+
+    #[test]
+    fn registry_records_roundtrip() {
+        let domain: Name = "example.com".parse().unwrap();
+        let host: Name = "host.example.com".parse().unwrap();
+        let records = [
+            DnsRecord::NSAP {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: vec![0x47, 0x00, 0x05, 0x80, 0x00, 0x5a],
+            },
+            DnsRecord::NsapPtr {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                host: host.clone(),
+            },
+            DnsRecord::APL {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                items: vec![
+                    AplItem::new(false, "192.168.32.0".parse().unwrap(), 21),
+                    AplItem::new(true, "192.168.38.0".parse().unwrap(), 28),
+                    AplItem::new(false, "ff00::".parse().unwrap(), 8),
+                ],
+            },
+            DnsRecord::IPSECKEY {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                precedence: 10,
+                algorithm: 2,
+                gateway: Gateway::Name(host.clone()),
+                public_key: vec![1, 2, 3, 4],
+            },
+            DnsRecord::IPSECKEY {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                precedence: 10,
+                algorithm: 0,
+                gateway: Gateway::None,
+                public_key: Vec::new(),
+            },
+            DnsRecord::DHCID {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                data: vec![0x00, 0x01, 0x01, 0xaa, 0xbb],
+            },
+            DnsRecord::HIP {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                pk_algorithm: 2,
+                hit: vec![0x20; 16],
+                public_key: vec![3, 1, 0, 1],
+                rendezvous_servers: vec![host.clone(), "rvs.example.net".parse().unwrap()],
+            },
+            DnsRecord::CSYNC {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                serial: 66,
+                flags: 3,
+                types: [QueryType::A, QueryType::NS, QueryType::AAAA]
+                    .into_iter()
+                    .collect(),
+            },
+            DnsRecord::ZONEMD {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                serial: 2018031900,
+                scheme: 1,
+                hash_algorithm: 1,
+                digest: vec![0xc6; 48],
+            },
+            DnsRecord::SPF {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                data: vec!["v=spf1 -all".into()],
+            },
+            DnsRecord::SPF {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                data: vec![vec![0xff], Vec::new()],
+            },
+            DnsRecord::NID {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                preference: 10,
+                node_id: 0x0014_4fff_ff20_ee64,
+            },
+            DnsRecord::L32 {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                preference: 10,
+                locator: Ipv4Addr::new(10, 1, 2, 0),
+            },
+            DnsRecord::L64 {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                preference: 10,
+                locator: 0x2001_0db8_1140_1000,
+            },
+            DnsRecord::LP {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                preference: 10,
+                fqdn: host.clone(),
+            },
+            DnsRecord::EUI48 {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: [0x00, 0x00, 0x5e, 0x00, 0x53, 0x2a],
+            },
+            DnsRecord::EUI64 {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                address: [0x00, 0x00, 0x5e, 0xef, 0x10, 0x00, 0x00, 0x2a],
+            },
+            DnsRecord::TKEY {
+                domain: domain.clone(),
+                class: DnsClass::ANY,
+                ttl: 0,
+                algorithm: "gss-tsig".parse().unwrap(),
+                inception: Timestamp(1_700_000_000),
+                expiration: Timestamp(1_700_003_600),
+                mode: 3,
+                error: 0,
+                key: vec![0xab; 20],
+                other: Vec::new(),
+            },
+            DnsRecord::TSIG {
+                domain: domain.clone(),
+                class: DnsClass::ANY,
+                ttl: 0,
+                algorithm: "hmac-sha256".parse().unwrap(),
+                time_signed: 0x0001_0000_0001,
+                fudge: 300,
+                mac: vec![0x5a; 32],
+                original_id: 0x1234,
+                error: 0,
+                other: Vec::new(),
+            },
+            DnsRecord::AVC {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                data: vec!["app-name=WebEx".into(), "app-class=OC".into()],
+            },
+            DnsRecord::DOA {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                enterprise: 0,
+                doa_type: 1,
+                location: 2,
+                media_type: "image/gif".into(),
+                data: vec![0x47, 0x49, 0x46],
+            },
+            DnsRecord::AVC {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                data: vec![b"app-name=\xc3(".to_vec()],
+            },
+            DnsRecord::DOA {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                enterprise: 0,
+                doa_type: 1,
+                location: 2,
+                media_type: vec![0x80, 0xbf],
+                data: Vec::new(),
+            },
+            DnsRecord::AMTRELAY {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                precedence: 10,
+                discovery_optional: true,
+                relay: Gateway::Ipv6("2001:db8::15".parse().unwrap()),
+            },
+            DnsRecord::AMTRELAY {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                precedence: 0,
+                discovery_optional: false,
+                relay: Gateway::Ipv4(Ipv4Addr::new(203, 0, 113, 15)),
+            },
+            DnsRecord::TA {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                key_tag: 20326,
+                algorithm: 8,
+                digest_type: 2,
+                digest: vec![0xe0; 32],
+            },
+            DnsRecord::DLV {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                key_tag: 20326,
+                algorithm: 8,
+                digest_type: 2,
+                digest: vec![0xe0; 32],
+            },
+            DnsRecord::KEY {
+                domain: domain.clone(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                flags: 0x0200,
+                protocol: 3,
+                algorithm: 15,
+                public_key: vec![9; 32],
+            },
+            DnsRecord::SIG {
+                domain: Name::root(),
+                class: DnsClass::ANY,
+                ttl: 0,
+                type_covered: QueryType::UNKNOWN(0),
+                algorithm: 15,
+                labels: 0,
+                original_ttl: 0,
+                expiration: Timestamp(1_700_000_300),
+                inception: Timestamp(1_700_000_000),
+                key_tag: 4242,
+                signer_name: domain,
+                signature: vec![7; 64],
+            },
+        ];
+
+        for record in &records {
+            assert_wire_roundtrip(record);
+        }
+    }
+
+    #[test]
+    fn unknown_gateway_types_are_kept_opaque() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"example.com".parse().unwrap()).unwrap();
+        buffer.write_u16(QueryType::AMTRELAY.to_num()).unwrap();
+        buffer.write_u16(1).unwrap();
+        buffer.write_u32(3600).unwrap();
+        buffer.write_u16(4).unwrap();
+        buffer.write_bytes(&[10, 0x84, 0xde, 0xad]).unwrap();
+
+        buffer.seek(0).unwrap();
+        assert_eq!(
+            DnsRecord::UNKNOWN {
+                domain: "example.com".parse().unwrap(),
+                qtype: QueryType::AMTRELAY,
+                class: DnsClass::IN,
+                ttl: 3600,
+                data: vec![10, 0x84, 0xde, 0xad],
+            },
+            DnsRecord::read(&mut buffer).unwrap()
+        );
+    }
+
+    #[test]
+    fn truncated_gateways_are_kept_opaque() {
+        // Each message ends part-way through a fixed field.
+        let cases: [(QueryType, &[u8]); 4] = [
+            (QueryType::AMTRELAY, &[0x0a]),
+            (QueryType::AMTRELAY, &[10, 0x02, 0x20, 0x01]),
+            (QueryType::IPSECKEY, &[10, 1]),
+            (QueryType::IPSECKEY, &[10, 1, 2, 192, 0]),
+        ];
+        for (qtype, rdata) in cases {
+            let mut buffer = BytePacketBuffer::new();
+            buffer.write_qname(&"a".parse().unwrap()).unwrap();
+            buffer.write_u16(qtype.to_num()).unwrap();
+            buffer.write_u16(1).unwrap();
+            buffer.write_u32(60).unwrap();
+            buffer.write_u16(rdata.len() as u16).unwrap();
+            buffer.write_bytes(rdata).unwrap();
+            let message = buffer.get_range(0, buffer.pos()).unwrap();
+
+            assert_eq!(
+                DnsRecord::UNKNOWN {
+                    domain: "a".parse().unwrap(),
+                    qtype,
+                    class: DnsClass::IN,
+                    ttl: 60,
+                    data: rdata.to_vec(),
+                },
+                DnsRecord::read(&mut BytePacketBuffer::from_bytes(message)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn empty_hashes_and_host_identities_stay_opaque() {
+        let domain: Name = "example.com".parse().unwrap();
+        for (qtype, data) in [
+            (QueryType::NSEC3, vec![1, 0, 0, 0, 0, 0]),
+            (QueryType::HIP, vec![0, 2, 0, 1, 0xaa]),
+            (QueryType::HIP, vec![1, 2, 0, 0, 0xaa]),
+        ] {
+            let raw = DnsRecord::UNKNOWN {
+                domain: domain.clone(),
+                qtype,
+                class: DnsClass::IN,
+                ttl: 3600,
+                data,
+            };
+            let mut buffer = BytePacketBuffer::new();
+            raw.write(&mut buffer).unwrap();
+            buffer.seek(0).unwrap();
+            assert_eq!(raw, DnsRecord::read(&mut buffer).unwrap());
+        }
+
+        let nsec3 = DnsRecord::NSEC3 {
+            domain: domain.clone(),
+            class: DnsClass::IN,
+            ttl: 3600,
+            hash_algorithm: 1,
+            flags: 0,
+            iterations: 0,
+            salt: Vec::new(),
+            next_hashed_owner: Vec::new(),
+            types: TypeBitmap::default(),
+        };
+        let hip = DnsRecord::HIP {
+            domain,
+            class: DnsClass::IN,
+            ttl: 3600,
+            pk_algorithm: 2,
+            hit: vec![0xaa],
+            public_key: Vec::new(),
+            rendezvous_servers: Vec::new(),
+        };
+        for record in [nsec3, hip] {
+            assert!(matches!(
+                record.write(&mut BytePacketBuffer::new()),
+                Err(EncodeError::InvalidRdata { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn rdata_names_are_decompressed() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"example.com".parse().unwrap()).unwrap();
+        buffer.write_u16(QueryType::RP.to_num()).unwrap();
+        buffer.write_u16(1).unwrap();
+        buffer.write_u32(3600).unwrap();
+        buffer.write_u16(9).unwrap();
+        // admin.<pointer to example.com>, then the root name.
+        buffer
+            .write_bytes(&[5, b'a', b'd', b'm', b'i', b'n', 0xc0, 0x00, 0x00])
+            .unwrap();
+        buffer.seek(0).unwrap();
+        let record = DnsRecord::read(&mut buffer).unwrap();
+        assert_eq!(
+            DnsRecord::RP {
+                domain: "example.com".parse().unwrap(),
+                class: DnsClass::IN,
+                ttl: 3600,
+                mbox: "admin.example.com".parse().unwrap(),
+                txt: Name::root(),
+            },
+            record
+        );
+
+        // Written back without the pointer, the record stands on its own.
+        let mut buffer = BytePacketBuffer::new();
+        record.write(&mut buffer).unwrap();
+        let rdata = &buffer.buffer[23..buffer.pos()];
+        assert_eq!(b"\x05admin\x07example\x03com\x00\x00", rdata);
+    }
+
     #[test]
     fn txt_record_errors_when_length_exceeds_rdata() {
         let mut buffer = BytePacketBuffer::new();
-        buffer.write_qname("txt.example").unwrap();
+        buffer.write_qname(&"txt.example".parse().unwrap()).unwrap();
         buffer.write_u16(QueryType::TXT.to_num()).unwrap();
         buffer.write_u16(1).unwrap();
         buffer.write_u32(0).unwrap();
@@ -553,7 +3895,37 @@ mod tests {
 
         buffer.write_u8(10).unwrap(); // txt length makes the record invalid
 
-        buffer.seek(0);
-        assert!(DnsRecord::read(&mut buffer).is_err());
+        buffer.seek(0).unwrap();
+        assert!(matches!(
+            DnsRecord::read(&mut buffer),
+            Err(ParseError::RdataLengthMismatch {
+                expected: 5,
+                actual: 11,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rdata_length_must_match_decoded_data() {
+        let mut buffer = BytePacketBuffer::new();
+        buffer.write_qname(&"a.example".parse().unwrap()).unwrap();
+        buffer.write_u16(QueryType::A.to_num()).unwrap();
+        buffer.write_u16(1).unwrap();
+        buffer.write_u32(0).unwrap();
+        buffer.write_u16(6).unwrap(); // an A record carries exactly 4 bytes
+        buffer.write_u32(0xC0000201).unwrap();
+        buffer.write_u16(0).unwrap();
+
+        buffer.seek(0).unwrap();
+        let err = DnsRecord::read(&mut buffer).unwrap_err();
+        assert_eq!(
+            ParseError::RdataLengthMismatch {
+                offset: 21,
+                expected: 6,
+                actual: 4,
+            },
+            err
+        );
     }
 }
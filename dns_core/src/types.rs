@@ -1,3 +1,7 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::PresentationError;
+
 #[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
 pub enum QueryType {
     UNKNOWN(u16),
@@ -215,18 +219,365 @@ impl QueryType {
     }
 }
 
+impl fmt::Display for QueryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryType::UNKNOWN(num) => write!(f, "TYPE{num}"),
+            QueryType::A => f.write_str("A"),
+            QueryType::NS => f.write_str("NS"),
+            QueryType::CNAME => f.write_str("CNAME"),
+            QueryType::SOA => f.write_str("SOA"),
+            QueryType::PTR => f.write_str("PTR"),
+            QueryType::HINFO => f.write_str("HINFO"),
+            QueryType::MINFO => f.write_str("MINFO"),
+            QueryType::MX => f.write_str("MX"),
+            QueryType::TXT => f.write_str("TXT"),
+            QueryType::RP => f.write_str("RP"),
+            QueryType::AFSDB => f.write_str("AFSDB"),
+            QueryType::X25 => f.write_str("X25"),
+            QueryType::ISDN => f.write_str("ISDN"),
+            QueryType::RT => f.write_str("RT"),
+            QueryType::NSAP => f.write_str("NSAP"),
+            QueryType::NsapPtr => f.write_str("NSAP-PTR"),
+            QueryType::SIG => f.write_str("SIG"),
+            QueryType::KEY => f.write_str("KEY"),
+            QueryType::PX => f.write_str("PX"),
+            QueryType::AAAA => f.write_str("AAAA"),
+            QueryType::LOC => f.write_str("LOC"),
+            QueryType::SRV => f.write_str("SRV"),
+            QueryType::NAPTR => f.write_str("NAPTR"),
+            QueryType::KX => f.write_str("KX"),
+            QueryType::CERT => f.write_str("CERT"),
+            QueryType::DNAME => f.write_str("DNAME"),
+            QueryType::OPT => f.write_str("OPT"),
+            QueryType::APL => f.write_str("APL"),
+            QueryType::DS => f.write_str("DS"),
+            QueryType::SSHFP => f.write_str("SSHFP"),
+            QueryType::IPSECKEY => f.write_str("IPSECKEY"),
+            QueryType::RRSIG => f.write_str("RRSIG"),
+            QueryType::NSEC => f.write_str("NSEC"),
+            QueryType::DNSKEY => f.write_str("DNSKEY"),
+            QueryType::DHCID => f.write_str("DHCID"),
+            QueryType::NSEC3 => f.write_str("NSEC3"),
+            QueryType::NSEC3PARAM => f.write_str("NSEC3PARAM"),
+            QueryType::TLSA => f.write_str("TLSA"),
+            QueryType::SMIMEA => f.write_str("SMIMEA"),
+            QueryType::HIP => f.write_str("HIP"),
+            QueryType::CDS => f.write_str("CDS"),
+            QueryType::CDNSKEY => f.write_str("CDNSKEY"),
+            QueryType::OPENPGPKEY => f.write_str("OPENPGPKEY"),
+            QueryType::CSYNC => f.write_str("CSYNC"),
+            QueryType::ZONEMD => f.write_str("ZONEMD"),
+            QueryType::SVCB => f.write_str("SVCB"),
+            QueryType::HTTPS => f.write_str("HTTPS"),
+            QueryType::SPF => f.write_str("SPF"),
+            QueryType::NID => f.write_str("NID"),
+            QueryType::L32 => f.write_str("L32"),
+            QueryType::L64 => f.write_str("L64"),
+            QueryType::LP => f.write_str("LP"),
+            QueryType::EUI48 => f.write_str("EUI48"),
+            QueryType::EUI64 => f.write_str("EUI64"),
+            QueryType::TKEY => f.write_str("TKEY"),
+            QueryType::TSIG => f.write_str("TSIG"),
+            QueryType::IXFR => f.write_str("IXFR"),
+            QueryType::AXFR => f.write_str("AXFR"),
+            QueryType::ANY => f.write_str("ANY"),
+            QueryType::URI => f.write_str("URI"),
+            QueryType::CAA => f.write_str("CAA"),
+            QueryType::AVC => f.write_str("AVC"),
+            QueryType::DOA => f.write_str("DOA"),
+            QueryType::AMTRELAY => f.write_str("AMTRELAY"),
+            QueryType::TA => f.write_str("TA"),
+            QueryType::DLV => f.write_str("DLV"),
+        }
+    }
+}
+
+impl FromStr for QueryType {
+    type Err = PresentationError;
+
+    /// Accepts mnemonics case-insensitively and the generic `TYPE<n>` form
+    /// from RFC 3597.
+    fn from_str(s: &str) -> Result<QueryType, PresentationError> {
+        let upper = s.to_ascii_uppercase();
+        let qtype = match upper.as_str() {
+            "A" => QueryType::A,
+            "NS" => QueryType::NS,
+            "CNAME" => QueryType::CNAME,
+            "SOA" => QueryType::SOA,
+            "PTR" => QueryType::PTR,
+            "HINFO" => QueryType::HINFO,
+            "MINFO" => QueryType::MINFO,
+            "MX" => QueryType::MX,
+            "TXT" => QueryType::TXT,
+            "RP" => QueryType::RP,
+            "AFSDB" => QueryType::AFSDB,
+            "X25" => QueryType::X25,
+            "ISDN" => QueryType::ISDN,
+            "RT" => QueryType::RT,
+            "NSAP" => QueryType::NSAP,
+            "NSAP-PTR" => QueryType::NsapPtr,
+            "SIG" => QueryType::SIG,
+            "KEY" => QueryType::KEY,
+            "PX" => QueryType::PX,
+            "AAAA" => QueryType::AAAA,
+            "LOC" => QueryType::LOC,
+            "SRV" => QueryType::SRV,
+            "NAPTR" => QueryType::NAPTR,
+            "KX" => QueryType::KX,
+            "CERT" => QueryType::CERT,
+            "DNAME" => QueryType::DNAME,
+            "OPT" => QueryType::OPT,
+            "APL" => QueryType::APL,
+            "DS" => QueryType::DS,
+            "SSHFP" => QueryType::SSHFP,
+            "IPSECKEY" => QueryType::IPSECKEY,
+            "RRSIG" => QueryType::RRSIG,
+            "NSEC" => QueryType::NSEC,
+            "DNSKEY" => QueryType::DNSKEY,
+            "DHCID" => QueryType::DHCID,
+            "NSEC3" => QueryType::NSEC3,
+            "NSEC3PARAM" => QueryType::NSEC3PARAM,
+            "TLSA" => QueryType::TLSA,
+            "SMIMEA" => QueryType::SMIMEA,
+            "HIP" => QueryType::HIP,
+            "CDS" => QueryType::CDS,
+            "CDNSKEY" => QueryType::CDNSKEY,
+            "OPENPGPKEY" => QueryType::OPENPGPKEY,
+            "CSYNC" => QueryType::CSYNC,
+            "ZONEMD" => QueryType::ZONEMD,
+            "SVCB" => QueryType::SVCB,
+            "HTTPS" => QueryType::HTTPS,
+            "SPF" => QueryType::SPF,
+            "NID" => QueryType::NID,
+            "L32" => QueryType::L32,
+            "L64" => QueryType::L64,
+            "LP" => QueryType::LP,
+            "EUI48" => QueryType::EUI48,
+            "EUI64" => QueryType::EUI64,
+            "TKEY" => QueryType::TKEY,
+            "TSIG" => QueryType::TSIG,
+            "IXFR" => QueryType::IXFR,
+            "AXFR" => QueryType::AXFR,
+            "ANY" => QueryType::ANY,
+            "URI" => QueryType::URI,
+            "CAA" => QueryType::CAA,
+            "AVC" => QueryType::AVC,
+            "DOA" => QueryType::DOA,
+            "AMTRELAY" => QueryType::AMTRELAY,
+            "TA" => QueryType::TA,
+            "DLV" => QueryType::DLV,
+            _ => upper
+                .strip_prefix("TYPE")
+                .and_then(|num| num.parse().ok())
+                .map(QueryType::from_num)
+                .ok_or_else(|| PresentationError::UnknownMnemonic {
+                    text: s.to_string(),
+                })?,
+        };
+        Ok(qtype)
+    }
+}
+
+pub const MDNS_CLASS_FLAG: u16 = 0x8000;
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Copy, PartialOrd, Ord)]
+pub enum DnsClass {
+    UNKNOWN(u16),
+    IN,   // 1
+    CS,   // 2
+    CH,   // 3
+    HS,   // 4
+    NONE, // 254
+    ANY,  // 255
+}
+
+impl DnsClass {
+    pub fn to_num(&self) -> u16 {
+        match *self {
+            DnsClass::UNKNOWN(x) => x,
+            DnsClass::IN => 1,
+            DnsClass::CS => 2,
+            DnsClass::CH => 3,
+            DnsClass::HS => 4,
+            DnsClass::NONE => 254,
+            DnsClass::ANY => 255,
+        }
+    }
+
+    pub fn from_num(num: u16) -> DnsClass {
+        match num {
+            1 => DnsClass::IN,
+            2 => DnsClass::CS,
+            3 => DnsClass::CH,
+            4 => DnsClass::HS,
+            254 => DnsClass::NONE,
+            255 => DnsClass::ANY,
+            _ => DnsClass::UNKNOWN(num),
+        }
+    }
+
+    /// Separates the mDNS top bit (RFC 6762): unicast-response in questions,
+    /// cache-flush in records. Returns the class proper and the flag.
+    pub fn split_mdns(&self) -> (DnsClass, bool) {
+        let num = self.to_num();
+        (
+            DnsClass::from_num(num & !MDNS_CLASS_FLAG),
+            num & MDNS_CLASS_FLAG != 0,
+        )
+    }
+
+    /// The class with the mDNS top bit set or cleared.
+    pub fn with_mdns_flag(&self, flag: bool) -> DnsClass {
+        let num = self.to_num() & !MDNS_CLASS_FLAG;
+        DnsClass::from_num(if flag { num | MDNS_CLASS_FLAG } else { num })
+    }
+}
+
+impl fmt::Display for DnsClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnsClass::UNKNOWN(num) => write!(f, "CLASS{num}"),
+            DnsClass::IN => f.write_str("IN"),
+            DnsClass::CS => f.write_str("CS"),
+            DnsClass::CH => f.write_str("CH"),
+            DnsClass::HS => f.write_str("HS"),
+            DnsClass::NONE => f.write_str("NONE"),
+            DnsClass::ANY => f.write_str("ANY"),
+        }
+    }
+}
+
+impl FromStr for DnsClass {
+    type Err = PresentationError;
+
+    /// Accepts mnemonics case-insensitively and the generic `CLASS<n>` form
+    /// from RFC 3597.
+    fn from_str(s: &str) -> Result<DnsClass, PresentationError> {
+        let upper = s.to_ascii_uppercase();
+        let class = match upper.as_str() {
+            "IN" => DnsClass::IN,
+            "CS" => DnsClass::CS,
+            "CH" => DnsClass::CH,
+            "HS" => DnsClass::HS,
+            "NONE" => DnsClass::NONE,
+            "ANY" => DnsClass::ANY,
+            _ => upper
+                .strip_prefix("CLASS")
+                .and_then(|num| num.parse().ok())
+                .map(DnsClass::from_num)
+                .ok_or_else(|| PresentationError::UnknownMnemonic {
+                    text: s.to_string(),
+                })?,
+        };
+        Ok(class)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    UNKNOWN(u8),
+    QUERY,  // 0
+    IQUERY, // 1
+    STATUS, // 2
+    NOTIFY, // 4
+    UPDATE, // 5
+    DSO,    // 6
+}
+
+impl Opcode {
+    pub fn to_num(&self) -> u8 {
+        match *self {
+            Opcode::UNKNOWN(x) => x,
+            Opcode::QUERY => 0,
+            Opcode::IQUERY => 1,
+            Opcode::STATUS => 2,
+            Opcode::NOTIFY => 4,
+            Opcode::UPDATE => 5,
+            Opcode::DSO => 6,
+        }
+    }
+
+    pub fn from_num(num: u8) -> Opcode {
+        match num {
+            0 => Opcode::QUERY,
+            1 => Opcode::IQUERY,
+            2 => Opcode::STATUS,
+            4 => Opcode::NOTIFY,
+            5 => Opcode::UPDATE,
+            6 => Opcode::DSO,
+            _ => Opcode::UNKNOWN(num),
+        }
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Opcode::UNKNOWN(num) => write!(f, "{num}"),
+            Opcode::QUERY => f.write_str("QUERY"),
+            Opcode::IQUERY => f.write_str("IQUERY"),
+            Opcode::STATUS => f.write_str("STATUS"),
+            Opcode::NOTIFY => f.write_str("NOTIFY"),
+            Opcode::UPDATE => f.write_str("UPDATE"),
+            Opcode::DSO => f.write_str("DSO"),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ResultCode {
-    NOERROR = 0,
-    FORMERR = 1,
-    SERVFAIL = 2,
-    NXDOMAIN = 3,
-    NOTIMP = 4,
-    REFUSED = 5,
+    UNKNOWN(u16),
+    NOERROR,   // 0
+    FORMERR,   // 1
+    SERVFAIL,  // 2
+    NXDOMAIN,  // 3
+    NOTIMP,    // 4
+    REFUSED,   // 5
+    YXDOMAIN,  // 6
+    YXRRSET,   // 7
+    NXRRSET,   // 8
+    NOTAUTH,   // 9
+    NOTZONE,   // 10
+    DSOTYPENI, // 11
+    BADVERS,   // 16, also BADSIG in TSIG records
+    BADKEY,    // 17
+    BADTIME,   // 18
+    BADMODE,   // 19
+    BADNAME,   // 20
+    BADALG,    // 21
+    BADTRUNC,  // 22
+    BADCOOKIE, // 23
 }
 
 impl ResultCode {
-    pub fn from_num(num: u8) -> ResultCode {
+    pub fn to_num(&self) -> u16 {
+        match *self {
+            ResultCode::UNKNOWN(x) => x,
+            ResultCode::NOERROR => 0,
+            ResultCode::FORMERR => 1,
+            ResultCode::SERVFAIL => 2,
+            ResultCode::NXDOMAIN => 3,
+            ResultCode::NOTIMP => 4,
+            ResultCode::REFUSED => 5,
+            ResultCode::YXDOMAIN => 6,
+            ResultCode::YXRRSET => 7,
+            ResultCode::NXRRSET => 8,
+            ResultCode::NOTAUTH => 9,
+            ResultCode::NOTZONE => 10,
+            ResultCode::DSOTYPENI => 11,
+            ResultCode::BADVERS => 16,
+            ResultCode::BADKEY => 17,
+            ResultCode::BADTIME => 18,
+            ResultCode::BADMODE => 19,
+            ResultCode::BADNAME => 20,
+            ResultCode::BADALG => 21,
+            ResultCode::BADTRUNC => 22,
+            ResultCode::BADCOOKIE => 23,
+        }
+    }
+
+    pub fn from_num(num: u16) -> ResultCode {
         match num {
             0 => ResultCode::NOERROR,
             1 => ResultCode::FORMERR,
@@ -234,14 +585,28 @@ impl ResultCode {
             3 => ResultCode::NXDOMAIN,
             4 => ResultCode::NOTIMP,
             5 => ResultCode::REFUSED,
-            _ => ResultCode::NOERROR,
+            6 => ResultCode::YXDOMAIN,
+            7 => ResultCode::YXRRSET,
+            8 => ResultCode::NXRRSET,
+            9 => ResultCode::NOTAUTH,
+            10 => ResultCode::NOTZONE,
+            11 => ResultCode::DSOTYPENI,
+            16 => ResultCode::BADVERS,
+            17 => ResultCode::BADKEY,
+            18 => ResultCode::BADTIME,
+            19 => ResultCode::BADMODE,
+            20 => ResultCode::BADNAME,
+            21 => ResultCode::BADALG,
+            22 => ResultCode::BADTRUNC,
+            23 => ResultCode::BADCOOKIE,
+            _ => ResultCode::UNKNOWN(num),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{QueryType, ResultCode};
+    use super::{DnsClass, Opcode, QueryType, ResultCode};
 
     #[test]
     fn query_type_to_and_from_num_are_inverse_for_known_type() {
@@ -259,11 +624,70 @@ mod tests {
         assert_eq!(unknown_value, QueryType::UNKNOWN(unknown_value).to_num());
     }
 
+    #[test]
+    fn query_type_presentation() {
+        assert_eq!("NSAP-PTR", QueryType::NsapPtr.to_string());
+        assert_eq!("TYPE65280", QueryType::UNKNOWN(65280).to_string());
+        assert_eq!(Ok(QueryType::NsapPtr), "nsap-ptr".parse());
+        assert_eq!(Ok(QueryType::MX), "TYPE15".parse());
+        assert_eq!(Ok(QueryType::UNKNOWN(65280)), "type65280".parse());
+        assert!("TYPE65536".parse::<QueryType>().is_err());
+        assert!("BOGUS".parse::<QueryType>().is_err());
+
+        for num in 0..=u16::MAX {
+            let qtype = QueryType::from_num(num);
+            assert_eq!(Ok(qtype), qtype.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn class_to_and_from_num_are_inverse() {
+        for num in [1, 2, 3, 4, 254, 255, 0x8001] {
+            assert_eq!(num, DnsClass::from_num(num).to_num());
+        }
+        assert_eq!(DnsClass::UNKNOWN(0x8001), DnsClass::from_num(0x8001));
+    }
+
+    #[test]
+    fn class_presentation_and_mdns_flag() {
+        for class in [
+            DnsClass::IN,
+            DnsClass::CH,
+            DnsClass::ANY,
+            DnsClass::UNKNOWN(77),
+        ] {
+            assert_eq!(class, class.to_string().parse().unwrap());
+        }
+        assert_eq!("CLASS77", DnsClass::UNKNOWN(77).to_string());
+        assert_eq!(DnsClass::CH, "ch".parse().unwrap());
+        assert_eq!(DnsClass::IN, "CLASS1".parse().unwrap());
+        assert!("CLASS".parse::<DnsClass>().is_err());
+        assert!("INTERNET".parse::<DnsClass>().is_err());
+
+        let flagged = DnsClass::IN.with_mdns_flag(true);
+        assert_eq!(DnsClass::UNKNOWN(0x8001), flagged);
+        assert_eq!((DnsClass::IN, true), flagged.split_mdns());
+        assert_eq!((DnsClass::IN, false), DnsClass::IN.split_mdns());
+        assert_eq!(DnsClass::IN, flagged.with_mdns_flag(false));
+    }
+
+    #[test]
+    fn opcode_to_and_from_num_are_inverse() {
+        for num in 0..16 {
+            assert_eq!(num, Opcode::from_num(num).to_num());
+        }
+        assert_eq!(Opcode::UPDATE, Opcode::from_num(5));
+        assert_eq!(Opcode::UNKNOWN(3), Opcode::from_num(3));
+    }
+
     #[test]
     fn result_code_from_num_handles_known_values() {
         assert_eq!(
             ResultCode::REFUSED,
-            ResultCode::from_num(ResultCode::REFUSED as u8)
+            ResultCode::from_num(ResultCode::REFUSED.to_num())
         );
+        assert_eq!(ResultCode::BADCOOKIE, ResultCode::from_num(23));
+        assert_eq!(ResultCode::UNKNOWN(12), ResultCode::from_num(12));
+        assert_eq!(3841, ResultCode::UNKNOWN(3841).to_num());
     }
 }
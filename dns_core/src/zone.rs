@@ -0,0 +1,563 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    encoding::unescape_bytes,
+    error::{PresentationError, ZoneError, ZoneErrorKind},
+    name::Name,
+    presentation::{Entry, Lexer, Token, Tokens, parse_rdata, parse_ttl, parse_ttl_class_type},
+    record::DnsRecord,
+    types::DnsClass,
+};
+
+// Limit on nested `$INCLUDE`s, which also stops include loops.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+// Limit on the records all `$GENERATE`s of a zone, including those in
+// `$INCLUDE`d files, may produce together.
+const MAX_GENERATE_COUNT: u64 = 65_536;
+
+/// Parses RFC 1035 master-file text. Relative names are completed with
+/// `origin` until `$ORIGIN` changes it, and relative `$INCLUDE` paths are
+/// taken from the current directory.
+pub fn parse_zone(text: &str, origin: &Name) -> Result<Vec<DnsRecord>, ZoneError> {
+    let mut loader = Loader::new(origin);
+    loader.load(text, None, 0)?;
+    Ok(loader.records)
+}
+
+/// Reads and parses a master file. Relative `$INCLUDE` paths are taken
+/// from the directory of the including file.
+pub fn parse_zone_file(path: impl AsRef<Path>, origin: &Name) -> Result<Vec<DnsRecord>, ZoneError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|err| ZoneError {
+        file: None,
+        line: 0,
+        column: 0,
+        kind: ZoneErrorKind::Io {
+            path: path.to_path_buf(),
+            kind: err.kind(),
+        },
+    })?;
+
+    let mut loader = Loader::new(origin);
+    loader.load(&text, Some(path), 0)?;
+    Ok(loader.records)
+}
+
+struct Loader {
+    origin: Name,
+    // Set by `$TTL`.
+    default_ttl: Option<u32>,
+    last_ttl: Option<u32>,
+    last_class: Option<DnsClass>,
+    last_owner: Option<Name>,
+    // Records produced by `$GENERATE` so far, checked against
+    // `MAX_GENERATE_COUNT`.
+    generated: u64,
+    records: Vec<DnsRecord>,
+}
+
+impl Loader {
+    fn new(origin: &Name) -> Loader {
+        Loader {
+            origin: origin.clone(),
+            default_ttl: None,
+            last_ttl: None,
+            last_class: None,
+            last_owner: None,
+            generated: 0,
+            records: Vec::new(),
+        }
+    }
+
+    fn load(&mut self, text: &str, file: Option<&Path>, depth: usize) -> Result<(), ZoneError> {
+        let error_at = |line, column, kind| ZoneError {
+            file: file.map(Path::to_path_buf),
+            line,
+            column,
+            kind,
+        };
+
+        let mut lexer = Lexer::new(text);
+        loop {
+            let entry = match lexer.next_entry() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    return Err(error_at(
+                        err.line,
+                        err.column,
+                        ZoneErrorKind::Syntax(err.error),
+                    ));
+                }
+            };
+
+            let first = &entry.tokens[0];
+            let result = if !entry.blank_owner && !first.quoted && first.text.starts_with('$') {
+                self.directive(&entry.tokens, file, depth)
+            } else {
+                self.record(&entry)
+            };
+            if let Err((token, kind)) = result {
+                return Err(match kind {
+                    Failure::Here(kind) => error_at(token.line, token.column, kind),
+                    Failure::Nested(err) => err,
+                });
+            }
+        }
+    }
+
+    fn record<'a>(&mut self, entry: &'a Entry) -> Result<(), (&'a Token, Failure)> {
+        let tokens = &entry.tokens;
+        let mut t = Tokens::new(tokens);
+        let domain = if entry.blank_owner {
+            self.last_owner
+                .clone()
+                .ok_or((&tokens[0], Failure::Here(ZoneErrorKind::MissingOwner)))?
+        } else {
+            t.name("owner", Some(&self.origin))
+                .map_err(|err| syntax(tokens, &t, err))?
+        };
+
+        let (ttl, class, qtype) =
+            parse_ttl_class_type(&mut t).map_err(|err| syntax(tokens, &t, err))?;
+        // RFC 2308 `$TTL`, or else the last TTL given as RFC 1035 has it.
+        let ttl = ttl
+            .or(self.default_ttl)
+            .or(self.last_ttl)
+            .ok_or((&tokens[0], Failure::Here(ZoneErrorKind::MissingTtl)))?;
+        let class = class.or(self.last_class).unwrap_or(DnsClass::IN);
+
+        let record = parse_rdata(
+            domain.clone(),
+            class,
+            ttl,
+            qtype,
+            &mut t,
+            Some(&self.origin),
+        )
+        .map_err(|err| syntax(tokens, &t, err))?;
+        if t.peek().is_some() {
+            let extra = &tokens[t.pos()];
+            let err = PresentationError::Invalid {
+                field: "trailing data",
+                text: extra.text.clone(),
+            };
+            return Err((extra, Failure::Here(ZoneErrorKind::Syntax(err))));
+        }
+
+        self.last_owner = Some(domain);
+        self.last_ttl = Some(ttl);
+        self.last_class = Some(class);
+        self.records.push(record);
+        Ok(())
+    }
+
+    fn directive<'a>(
+        &mut self,
+        tokens: &'a [Token],
+        file: Option<&Path>,
+        depth: usize,
+    ) -> Result<(), (&'a Token, Failure)> {
+        let mut t = Tokens::new(tokens);
+        let directive = &t.next("directive").expect("entries are not empty").text;
+
+        match directive.to_ascii_uppercase().as_str() {
+            "$ORIGIN" => {
+                self.origin = t
+                    .name("$ORIGIN", Some(&self.origin))
+                    .map_err(|err| syntax(tokens, &t, err))?;
+            }
+            "$TTL" => {
+                let token = t.next("$TTL").map_err(|err| syntax(tokens, &t, err))?;
+                let ttl = parse_ttl(&token.text)
+                    .ok_or_else(|| syntax(tokens, &t, invalid("TTL", &token.text)))?;
+                self.default_ttl = Some(ttl);
+            }
+            "$INCLUDE" => {
+                let token = t
+                    .next("$INCLUDE file")
+                    .map_err(|err| syntax(tokens, &t, err))?;
+                let path = unescape_bytes(&token.text)
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                    .ok_or_else(|| syntax(tokens, &t, invalid("$INCLUDE file", &token.text)))?;
+                let path = match file.and_then(Path::parent) {
+                    Some(dir) => dir.join(path),
+                    None => PathBuf::from(path),
+                };
+                let origin = match t.peek() {
+                    Some(_) => t
+                        .name("$INCLUDE origin", Some(&self.origin))
+                        .map_err(|err| syntax(tokens, &t, err))?,
+                    None => self.origin.clone(),
+                };
+                finish(tokens, &t)?;
+                return self
+                    .include(&path, origin, depth)
+                    .map_err(|kind| (token, kind));
+            }
+            "$GENERATE" => return self.generate(tokens),
+            _ => {
+                let err = PresentationError::UnknownMnemonic {
+                    text: directive.clone(),
+                };
+                return Err((&tokens[0], Failure::Here(ZoneErrorKind::Syntax(err))));
+            }
+        }
+        finish(tokens, &t)
+    }
+
+    /// Loads an included file. The origin and the owner to inherit revert
+    /// afterwards (RFC 1035 section 5.1).
+    fn include(&mut self, path: &Path, origin: Name, depth: usize) -> Result<(), Failure> {
+        if depth >= MAX_INCLUDE_DEPTH {
+            return Err(Failure::Here(ZoneErrorKind::IncludeTooDeep));
+        }
+        let text = fs::read_to_string(path).map_err(|err| {
+            Failure::Here(ZoneErrorKind::Io {
+                path: path.to_path_buf(),
+                kind: err.kind(),
+            })
+        })?;
+
+        let saved_origin = std::mem::replace(&mut self.origin, origin);
+        let saved_owner = self.last_owner.clone();
+        let result = self.load(&text, Some(path), depth + 1);
+        self.origin = saved_origin;
+        self.last_owner = saved_owner;
+        result.map_err(Failure::Nested)
+    }
+
+    /// Expands `$GENERATE start-stop[/step] lhs [ttl] [class] type rhs`,
+    /// substituting the iterator for `$` in every field after the range.
+    fn generate<'a>(&mut self, tokens: &'a [Token]) -> Result<(), (&'a Token, Failure)> {
+        let mut t = Tokens::new(tokens);
+        t.next("directive").expect("entries are not empty");
+        let range = t
+            .next("$GENERATE range")
+            .map_err(|err| syntax(tokens, &t, err))?;
+        let (start, stop, step) = parse_range(&range.text)
+            .ok_or_else(|| syntax(tokens, &t, invalid("$GENERATE range", &range.text)))?;
+        self.generated += u64::from((stop - start) / step) + 1;
+        if self.generated > MAX_GENERATE_COUNT {
+            return Err((range, Failure::Here(ZoneErrorKind::TooManyGenerated)));
+        }
+        let template = &tokens[t.pos()..];
+        if template.is_empty() {
+            let err = PresentationError::Missing {
+                field: "$GENERATE template",
+            };
+            return Err((range, Failure::Here(ZoneErrorKind::Syntax(err))));
+        }
+
+        for value in (start..=stop).step_by(step as usize) {
+            let mut entry = Entry::default();
+            for token in template {
+                let text = substitute(&token.text, value).ok_or_else(|| {
+                    let err = invalid("$GENERATE modifier", &token.text);
+                    (token, Failure::Here(ZoneErrorKind::Syntax(err)))
+                })?;
+                entry.tokens.push(Token {
+                    text,
+                    ..token.clone()
+                });
+            }
+            // Errors point into the generated entry, so report them at the
+            // template word instead.
+            self.record(&entry).map_err(|(failed, kind)| {
+                let index = entry
+                    .tokens
+                    .iter()
+                    .position(|token| std::ptr::eq(token, failed))
+                    .unwrap_or(0);
+                (&template[index], kind)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// Where a failure is reported: at a word of the current file, or already
+// located within an included one.
+enum Failure {
+    Here(ZoneErrorKind),
+    Nested(ZoneError),
+}
+
+// Blames the word the cursor stopped at: the one that failed to parse, or
+// the last one when a field is missing.
+fn syntax<'a>(tokens: &'a [Token], t: &Tokens, err: PresentationError) -> (&'a Token, Failure) {
+    let index = t.pos().saturating_sub(1).min(tokens.len() - 1);
+    (&tokens[index], Failure::Here(ZoneErrorKind::Syntax(err)))
+}
+
+fn finish<'a>(tokens: &'a [Token], t: &Tokens) -> Result<(), (&'a Token, Failure)> {
+    match tokens.get(t.pos()) {
+        Some(extra) => {
+            let err = invalid("trailing data", &extra.text);
+            Err((extra, Failure::Here(ZoneErrorKind::Syntax(err))))
+        }
+        None => Ok(()),
+    }
+}
+
+fn invalid(field: &'static str, text: &str) -> PresentationError {
+    PresentationError::Invalid {
+        field,
+        text: text.to_string(),
+    }
+}
+
+fn parse_range(text: &str) -> Option<(u32, u32, u32)> {
+    let (range, step) = match text.split_once('/') {
+        Some((range, step)) => (range, step.parse().ok()?),
+        None => (text, 1),
+    };
+    let (start, stop) = range.split_once('-')?;
+    let (start, stop): (u32, u32) = (start.parse().ok()?, stop.parse().ok()?);
+    let valid = start <= stop && step > 0;
+    valid.then_some((start, stop, step))
+}
+
+/// Replaces `$` with `value` and `${offset[,width[,base]]}` with `value`
+/// plus the offset, zero-padded to the width in base `d`, `o`, `x` or `X`.
+/// Escaped characters such as `\$` are left alone.
+fn substitute(template: &str, value: u32) -> Option<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                out.push(c);
+                out.extend(chars.next());
+            }
+            '$' if chars.as_str().starts_with('{') => {
+                let (spec, rest) = chars.as_str()[1..].split_once('}')?;
+                chars = rest.chars();
+
+                let mut parts = spec.split(',');
+                let offset: i64 = parts.next()?.parse().ok()?;
+                let width: usize = match parts.next() {
+                    Some(width) => width.parse().ok()?,
+                    None => 0,
+                };
+                let base = parts.next().unwrap_or("d");
+                if parts.next().is_some() || width > 255 {
+                    return None;
+                }
+
+                let n = u32::try_from(value as i64 + offset).ok()?;
+                let digits = match base {
+                    "d" => format!("{n:0width$}"),
+                    "o" => format!("{n:0width$o}"),
+                    "x" => format!("{n:0width$x}"),
+                    "X" => format!("{n:0width$X}"),
+                    _ => return None,
+                };
+                out.push_str(&digits);
+            }
+            '$' => out.push_str(&value.to_string()),
+            _ => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_zone, parse_zone_file, substitute};
+    use crate::{
+        error::{PresentationError, ZoneError, ZoneErrorKind},
+        name::Name,
+        record::DnsRecord,
+        types::DnsClass,
+    };
+    use std::{fs, net::Ipv4Addr};
+
+    fn origin() -> Name {
+        "example.com".parse().unwrap()
+    }
+
+    #[test]
+    fn parses_a_zone_with_relative_names_and_inheritance() {
+        let zone = r#"
+$TTL 1h
+@   IN  SOA ns1 hostmaster (
+            2024010101 ; serial
+            2h 1h 2w 5m )
+        NS  ns1
+        NS  ns2.example.net.
+ns1 300 A   192.0.2.1
+        AAAA 2001:db8::1
+www     CNAME @
+$ORIGIN sub
+txt 1d30m TXT "semi;colon" "quote\"d" ( "multi"
+                                         "line" )
+"#;
+        let records = parse_zone(zone, &origin()).unwrap();
+        let text: Vec<String> = records.iter().map(DnsRecord::to_string).collect();
+        assert_eq!(
+            vec![
+                "example.com.\t3600\tIN\tSOA\tns1.example.com. hostmaster.example.com. 2024010101 7200 3600 1209600 300",
+                "example.com.\t3600\tIN\tNS\tns1.example.com.",
+                "example.com.\t3600\tIN\tNS\tns2.example.net.",
+                "ns1.example.com.\t300\tIN\tA\t192.0.2.1",
+                "ns1.example.com.\t3600\tIN\tAAAA\t2001:db8::1",
+                "www.example.com.\t3600\tIN\tCNAME\texample.com.",
+                "txt.sub.example.com.\t88200\tIN\tTXT\t\"semi;colon\" \"quote\\\"d\" \"multi\" \"line\"",
+            ],
+            text
+        );
+    }
+
+    #[test]
+    fn ttl_falls_back_to_the_last_one_given() {
+        let records = parse_zone("a 60 CH A 10.0.0.1\nb A 10.0.0.2\n", &origin()).unwrap();
+        assert_eq!(
+            DnsRecord::A {
+                domain: "b.example.com".parse().unwrap(),
+                class: DnsClass::CH,
+                ttl: 60,
+                addr: Ipv4Addr::new(10, 0, 0, 2),
+            },
+            records[1]
+        );
+
+        let err = parse_zone("a A 10.0.0.1\n", &origin()).unwrap_err();
+        assert_eq!(
+            (1, 1, ZoneErrorKind::MissingTtl),
+            (err.line, err.column, err.kind)
+        );
+        let err = parse_zone("$TTL 60\n  A 10.0.0.1\n", &origin()).unwrap_err();
+        assert_eq!(
+            (2, 3, ZoneErrorKind::MissingOwner),
+            (err.line, err.column, err.kind)
+        );
+    }
+
+    #[test]
+    fn generate_expands_templates() {
+        let zone = "$TTL 60\n$GENERATE 1-10/4 host-$ A 10.0.0.${10}\n\
+                    $GENERATE 254-255 ${0,2,x} PTR dhcp-$.example.com.\n";
+        let text: Vec<String> = parse_zone(zone, &origin())
+            .unwrap()
+            .iter()
+            .map(DnsRecord::to_string)
+            .collect();
+        assert_eq!(
+            vec![
+                "host-1.example.com.\t60\tIN\tA\t10.0.0.11",
+                "host-5.example.com.\t60\tIN\tA\t10.0.0.15",
+                "host-9.example.com.\t60\tIN\tA\t10.0.0.19",
+                "fe.example.com.\t60\tIN\tPTR\tdhcp-254.example.com.",
+                "ff.example.com.\t60\tIN\tPTR\tdhcp-255.example.com.",
+            ],
+            text
+        );
+
+        assert_eq!(Some("a-007-\\$".to_string()), substitute("a-${2,3}-\\$", 5));
+        assert_eq!(None, substitute("${1,2,q}", 5));
+        assert_eq!(None, substitute("${-6}", 5));
+        assert_eq!(None, substitute("${1", 5));
+
+        let err =
+            parse_zone("$TTL 60\n$GENERATE 1-2 a-$ A 10.0.0.${255}\n", &origin()).unwrap_err();
+        assert_eq!((2, 21), (err.line, err.column));
+        let err = parse_zone("$GENERATE 5-1 a A 10.0.0.1\n", &origin()).unwrap_err();
+        assert_eq!((1, 11), (err.line, err.column));
+        let err = parse_zone("$GENERATE 0-4294967295 h$ A 10.0.0.1\n", &origin()).unwrap_err();
+        assert_eq!((1, 11), (err.line, err.column));
+        assert_eq!(ZoneErrorKind::TooManyGenerated, err.kind);
+        let zone = "$TTL 60\n$GENERATE 1-65536 h$ A 10.0.0.1\n";
+        assert_eq!(65_536, parse_zone(zone, &origin()).unwrap().len());
+
+        // The limit covers the whole zone, not each directive.
+        let zone = "$TTL 60\n$GENERATE 1-40000 a$ A 10.0.0.1\n\
+                    $GENERATE 1-40000 b$ A 10.0.0.1\n";
+        let err = parse_zone(zone, &origin()).unwrap_err();
+        assert_eq!((3, 11), (err.line, err.column));
+        assert_eq!(ZoneErrorKind::TooManyGenerated, err.kind);
+    }
+
+    #[test]
+    fn include_restores_the_origin() {
+        let dir = std::env::temp_dir().join(format!("dns_core_zone_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("hosts.zone"), "$TTL 60\nhost A 10.0.0.1\n").unwrap();
+        fs::write(dir.join("loop.zone"), "$INCLUDE loop.zone\n").unwrap();
+        fs::write(dir.join("hosts.gen"), "$GENERATE 1-40000 a$ A 10.0.0.1\n").unwrap();
+        fs::write(
+            dir.join("generate.zone"),
+            "$TTL 60\n$INCLUDE hosts.gen\n$GENERATE 1-40000 b$ A 10.0.0.1\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("main.zone"),
+            "$INCLUDE hosts.zone lab\nafter 60 A 10.0.0.2\n",
+        )
+        .unwrap();
+
+        let records = parse_zone_file(dir.join("main.zone"), &origin());
+        let loops = parse_zone_file(dir.join("loop.zone"), &origin());
+        let generated = parse_zone_file(dir.join("generate.zone"), &origin());
+        let missing = parse_zone("$INCLUDE /nonexistent/zone\n", &origin());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<String> = records
+            .unwrap()
+            .iter()
+            .map(|record| record.to_string().split('\t').next().unwrap().to_string())
+            .collect();
+        assert_eq!(vec!["host.lab.example.com.", "after.example.com."], names);
+
+        let err = loops.unwrap_err();
+        assert_eq!(ZoneErrorKind::IncludeTooDeep, err.kind);
+        assert_eq!(Some(dir.join("loop.zone")), err.file);
+        // `$GENERATE`s in included files count towards the same limit.
+        let err = generated.unwrap_err();
+        assert_eq!(ZoneErrorKind::TooManyGenerated, err.kind);
+        assert_eq!(Some(dir.join("generate.zone")), err.file);
+        assert_eq!((3, 11), (err.line, err.column));
+        assert!(matches!(
+            missing,
+            Err(ZoneError {
+                line: 1,
+                column: 10,
+                kind: ZoneErrorKind::Io { .. },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn errors_carry_line_and_column() {
+        let cases = [
+            ("$TTL 60\nwww A 10.0.0\n", 2, 7),
+            ("$TTL 60\nwww MX 10\n", 2, 8),
+            ("$TTL 60\nwww A 10.0.0.1 extra\n", 2, 16),
+            ("$TTL 60\nwww TXT \"open\n", 2, 9),
+            ("$TTL 60\nwww A ( 10.0.0.1\n", 2, 7),
+            ("$TTL 60\nwww A 10.0.0.1 )\n", 2, 16),
+            ("$TTL 1x\n", 1, 6),
+            ("$BOGUS\n", 1, 1),
+            ("$TTL 60\nwww BOGUS x\n", 2, 5),
+        ];
+        for (zone, line, column) in cases {
+            let err = parse_zone(zone, &origin()).unwrap_err();
+            assert_eq!((line, column), (err.line, err.column), "{zone:?}: {err}");
+            assert!(matches!(err.kind, ZoneErrorKind::Syntax(_)), "{zone:?}");
+        }
+
+        let err = parse_zone("$TTL 60\nwww MX 10\n", &origin()).unwrap_err();
+        assert_eq!(
+            ZoneErrorKind::Syntax(PresentationError::Missing {
+                field: "MX exchange"
+            }),
+            err.kind
+        );
+        assert_eq!("2:8: missing MX exchange", err.to_string());
+    }
+}
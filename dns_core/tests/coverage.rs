@@ -4,10 +4,11 @@ use dns_core::{
     buffer::BytePacketBuffer,
     buffer::MAX_PACKET_SIZE,
     header::DnsHeader,
+    name::Name,
     packet::DnsPacket,
     question::DnsQuestion,
     record::DnsRecord,
-    types::{QueryType, ResultCode},
+    types::{DnsClass, Opcode, QueryType, ResultCode},
 };
 
 fn build_test_packet() -> DnsPacket {
@@ -18,47 +19,48 @@ fn build_test_packet() -> DnsPacket {
     packet.header.recursion_available = true;
     packet.header.authoritative_answer = true;
     packet.header.truncated_message = true;
-    packet.header.opcode = 3;
+    packet.header.opcode = Opcode::UNKNOWN(3);
     packet.header.response = true;
     packet.header.rescode = ResultCode::NXDOMAIN;
 
-    packet
-        .questions
-        .push(DnsQuestion::new("example.com".into(), QueryType::A));
+    packet.questions.push(DnsQuestion::new(
+        "example.com".parse().unwrap(),
+        QueryType::A,
+    ));
 
     packet.answers.push(DnsRecord::A {
-        domain: "example.com".into(),
-        class: 1,
+        domain: "example.com".parse().unwrap(),
+        class: DnsClass::IN,
         ttl: 60,
         addr: Ipv4Addr::new(192, 0, 2, 1),
     });
 
     packet.authorities.push(DnsRecord::NS {
-        domain: "example.com".into(),
-        class: 1,
-        host: "ns1.example.com".into(),
+        domain: "example.com".parse().unwrap(),
+        class: DnsClass::IN,
+        host: "ns1.example.com".parse().unwrap(),
         ttl: 60,
     });
 
     packet.resources.push(DnsRecord::AAAA {
-        domain: "example.com".into(),
+        domain: "example.com".parse().unwrap(),
         addr: Ipv6Addr::new(0x2606, 0x4700, 0, 0, 0, 0, 0, 0x1111),
-        class: 1,
+        class: DnsClass::IN,
         ttl: 60,
     });
 
     packet.resources.push(DnsRecord::MX {
-        domain: "example.com".into(),
+        domain: "example.com".parse().unwrap(),
         priority: 10,
-        class: 1,
-        host: "mail.example.com".into(),
+        class: DnsClass::IN,
+        host: "mail.example.com".parse().unwrap(),
         ttl: 60,
     });
 
     packet.resources.push(DnsRecord::CNAME {
-        domain: "alias.example.com".into(),
-        host: "example.com".into(),
-        class: 1,
+        domain: "alias.example.com".parse().unwrap(),
+        host: "example.com".parse().unwrap(),
+        class: DnsClass::IN,
         ttl: 30,
     });
 
@@ -72,20 +74,24 @@ fn byte_packet_buffer_boundary_conditions() {
     buffer.write_u8(0x12).unwrap();
     buffer.write_u16(0x3456).unwrap();
     buffer.write_u32(0x789ABCDE).unwrap();
-    buffer.seek(0);
+    buffer.seek(0).unwrap();
     assert_eq!(0x12, buffer.read().unwrap());
     assert_eq!(0x3456, buffer.read_u16().unwrap());
     assert_eq!(0x789ABCDE, buffer.read_u32().unwrap());
 
-    buffer.step(10);
+    buffer.step(10).unwrap();
     assert_eq!(17, buffer.pos());
     buffer.set(5, 0xAA).unwrap();
     assert_eq!(0xAA, buffer.get(5).unwrap());
 
-    buffer.seek(MAX_PACKET_SIZE - 1);
+    buffer.seek(MAX_PACKET_SIZE - 1).unwrap();
     assert!(buffer.write_u8(0xFF).is_ok());
     assert!(buffer.write_u8(0xEE).is_err());
-    assert!(buffer.get_range(MAX_PACKET_SIZE - 2, 5).is_err());
+    assert!(
+        buffer
+            .get_range(MAX_PACKET_SIZE - 2, MAX_PACKET_SIZE + 3)
+            .is_err()
+    );
 }
 
 #[test]
@@ -93,7 +99,7 @@ fn get_reads_from_requested_position_even_after_seek() {
     let mut buffer = BytePacketBuffer::new();
     buffer.buffer[0] = 0xAB;
     buffer.set_size(1);
-    buffer.seek(1);
+    buffer.seek(1).unwrap();
 
     assert_eq!(0xAB, buffer.get(0).unwrap());
 }
@@ -101,26 +107,28 @@ fn get_reads_from_requested_position_even_after_seek() {
 #[test]
 fn qname_roundtrip_and_pointer_behaviour() {
     let mut buffer = BytePacketBuffer::new();
-    buffer.write_qname("MiXeD.Case.Test").unwrap();
+    buffer
+        .write_qname(&"MiXeD.Case.Test".parse().unwrap())
+        .unwrap();
     let pointer_location = buffer.pos();
     buffer.write_u8(0xC0).unwrap();
     buffer.write_u8(0x00).unwrap();
 
-    buffer.seek(0);
-    let mut out = String::new();
+    buffer.seek(0).unwrap();
+    let mut out = Name::root();
     buffer.read_qname(&mut out).unwrap();
-    assert_eq!("mixed.case.test", out);
+    assert_eq!("MiXeD.Case.Test.", out.to_string());
 
-    buffer.seek(pointer_location);
-    let mut pointer_out = String::new();
+    buffer.seek(pointer_location).unwrap();
+    let mut pointer_out = Name::root();
     buffer.read_qname(&mut pointer_out).unwrap();
-    assert_eq!("mixed.case.test", pointer_out);
+    assert_eq!("MiXeD.Case.Test.", pointer_out.to_string());
 
     let mut loop_buffer = BytePacketBuffer::new();
     loop_buffer.buffer[0] = 0xC0;
     loop_buffer.buffer[1] = 0x00;
-    loop_buffer.seek(0);
-    let mut loop_out = String::new();
+    loop_buffer.seek(0).unwrap();
+    let mut loop_out = Name::root();
     assert!(loop_buffer.read_qname(&mut loop_out).is_err());
 }
 
@@ -131,7 +139,7 @@ fn header_and_question_roundtrip() {
     header.recursion_desired = true;
     header.truncated_message = true;
     header.authoritative_answer = true;
-    header.opcode = 2;
+    header.opcode = Opcode::STATUS;
     header.response = true;
     header.rescode = ResultCode::REFUSED;
     header.checking_disabled = true;
@@ -146,7 +154,7 @@ fn header_and_question_roundtrip() {
 
     let mut buffer = BytePacketBuffer::new();
     header.write(&mut buffer).unwrap();
-    buffer.seek(0);
+    buffer.seek(0).unwrap();
 
     let mut parsed = DnsHeader::new();
     parsed.read(&mut buffer).unwrap();
@@ -166,12 +174,12 @@ fn header_and_question_roundtrip() {
     assert_eq!(header.authoritative_entries, parsed.authoritative_entries);
     assert_eq!(header.resource_entries, parsed.resource_entries);
 
-    let question = DnsQuestion::new("example.com".into(), QueryType::AAAA);
+    let question = DnsQuestion::new("example.com".parse().unwrap(), QueryType::AAAA);
     let mut qbuffer = BytePacketBuffer::new();
     question.write(&mut qbuffer).unwrap();
-    qbuffer.seek(0);
+    qbuffer.seek(0).unwrap();
 
-    let mut parsed_question = DnsQuestion::new(String::new(), QueryType::UNKNOWN(0));
+    let mut parsed_question = DnsQuestion::new(Name::root(), QueryType::UNKNOWN(0));
     parsed_question.read(&mut qbuffer).unwrap();
     assert_eq!(question, parsed_question);
 }
@@ -180,40 +188,40 @@ fn header_and_question_roundtrip() {
 fn records_read_and_write_roundtrip() {
     let records = vec![
         DnsRecord::A {
-            domain: "example.com".into(),
-            class: 1,
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
             ttl: 120,
             addr: Ipv4Addr::new(203, 0, 113, 5),
         },
         DnsRecord::NS {
-            domain: "example.com".into(),
-            class: 1,
-            host: "ns.example.com".into(),
+            domain: "example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            host: "ns.example.com".parse().unwrap(),
             ttl: 240,
         },
         DnsRecord::CNAME {
-            domain: "alias.example.com".into(),
-            class: 1,
-            host: "example.com".into(),
+            domain: "alias.example.com".parse().unwrap(),
+            class: DnsClass::IN,
+            host: "example.com".parse().unwrap(),
             ttl: 360,
         },
         DnsRecord::MX {
-            domain: "example.com".into(),
+            domain: "example.com".parse().unwrap(),
             priority: 5,
-            class: 1,
-            host: "mail.example.com".into(),
+            class: DnsClass::IN,
+            host: "mail.example.com".parse().unwrap(),
             ttl: 180,
         },
         DnsRecord::AAAA {
-            domain: "ipv6.example.com".into(),
+            domain: "ipv6.example.com".parse().unwrap(),
             addr: Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
-            class: 1,
+            class: DnsClass::IN,
             ttl: 60,
         },
         DnsRecord::UNKNOWN {
-            domain: "weird.example.com".into(),
+            domain: "weird.example.com".parse().unwrap(),
             qtype: QueryType::UNKNOWN(65000),
-            class: 1,
+            class: DnsClass::IN,
             ttl: 0,
             data: vec![1, 2, 3, 4],
         },
@@ -222,7 +230,7 @@ fn records_read_and_write_roundtrip() {
     for record in records {
         let mut buffer = BytePacketBuffer::new();
         let _ = record.write(&mut buffer);
-        buffer.seek(0);
+        buffer.seek(0).unwrap();
         let parsed = DnsRecord::read(&mut buffer).unwrap();
         match (&record, &parsed) {
             (
@@ -258,7 +266,7 @@ fn full_packet_write_and_parse() {
     let mut buffer = BytePacketBuffer::new();
     packet.write(&mut buffer).unwrap();
 
-    buffer.seek(0);
+    buffer.seek(0).unwrap();
     let parsed = DnsPacket::from_buffer(&mut buffer).unwrap();
 
     assert_eq!(packet.header.id, parsed.header.id);
@@ -282,7 +290,6 @@ fn full_packet_write_and_parse() {
 
 #[test]
 fn invalid_qname_rejected() {
-    let mut buffer = BytePacketBuffer::new();
     let oversized_label = "a".repeat(64) + ".com";
-    assert!(buffer.write_qname(&oversized_label).is_err());
+    assert!(oversized_label.parse::<Name>().is_err());
 }
@@ -0,0 +1,134 @@
+use std::net::Ipv4Addr;
+
+use dns_core::{
+    packet::DnsPacket,
+    packet_ref::DnsPacketRef,
+    question::DnsQuestion,
+    record::DnsRecord,
+    types::{DnsClass, QueryType},
+};
+
+fn sample_message() -> Vec<u8> {
+    let mut packet = DnsPacket::new();
+    packet.header.id = 0xBEEF;
+    packet.header.response = true;
+    packet.questions.push(DnsQuestion::new(
+        "www.example.com".parse().unwrap(),
+        QueryType::A,
+    ));
+    packet.answers.push(DnsRecord::CNAME {
+        domain: "www.example.com".parse().unwrap(),
+        host: "cdn.example.com".parse().unwrap(),
+        class: DnsClass::IN,
+        ttl: 300,
+    });
+    packet.answers.push(DnsRecord::A {
+        domain: "cdn.example.com".parse().unwrap(),
+        addr: Ipv4Addr::new(192, 0, 2, 7),
+        class: DnsClass::IN,
+        ttl: 300,
+    });
+    packet.resources.push(DnsRecord::TXT {
+        domain: "example.com".parse().unwrap(),
+        data: vec![b"v=spf1 -all".to_vec()],
+        class: DnsClass::IN,
+        ttl: 300,
+    });
+    packet.to_bytes().unwrap()
+}
+
+// Both decoders must return, never panic, and agree on what they accept.
+fn decode(message: &[u8]) {
+    let owned = DnsPacket::from_bytes(message);
+    let view = DnsPacketRef::parse(message);
+    assert_eq!(owned.is_ok(), view.is_ok());
+
+    if let Ok(view) = view {
+        for record in view
+            .answers()
+            .chain(view.authorities())
+            .chain(view.resources())
+        {
+            record.to_record().unwrap();
+        }
+        view.to_packet().unwrap();
+    }
+}
+
+fn header(qdcount: u16, ancount: u16, nscount: u16, arcount: u16) -> Vec<u8> {
+    let mut message = vec![0x12, 0x34, 0x81, 0x80];
+    for count in [qdcount, ancount, nscount, arcount] {
+        message.extend_from_slice(&count.to_be_bytes());
+    }
+    message
+}
+
+#[test]
+fn every_truncation_is_rejected() {
+    let message = sample_message();
+    assert!(DnsPacket::from_bytes(&message).is_ok());
+
+    for len in 0..message.len() {
+        assert!(DnsPacket::from_bytes(&message[..len]).is_err(), "len {len}");
+        assert!(DnsPacketRef::parse(&message[..len]).is_err(), "len {len}");
+    }
+}
+
+#[test]
+fn random_mutations_never_panic() {
+    let message = sample_message();
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..20_000 {
+        let mut mutated = message.clone();
+        for _ in 0..1 + next() % 4 {
+            let index = (next() % mutated.len() as u64) as usize;
+            mutated[index] = next() as u8;
+        }
+        decode(&mutated);
+    }
+}
+
+#[test]
+fn crafted_packets_never_panic() {
+    // Question name pointing at itself.
+    let mut self_pointer = header(1, 0, 0, 0);
+    self_pointer.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1]);
+    assert!(DnsPacket::from_bytes(&self_pointer).is_err());
+    decode(&self_pointer);
+
+    // Pointer past the end of the message.
+    let mut forward = header(1, 0, 0, 0);
+    forward.extend_from_slice(&[0xC0, 0xFF, 0, 1, 0, 1]);
+    assert!(DnsPacket::from_bytes(&forward).is_err());
+    decode(&forward);
+
+    // Maximum counts with no body at all.
+    let counts = header(u16::MAX, u16::MAX, u16::MAX, u16::MAX);
+    assert!(DnsPacket::from_bytes(&counts).is_err());
+    decode(&counts);
+
+    // Record claiming far more rdata than the message holds.
+    let mut rdlength = header(0, 1, 0, 0);
+    rdlength.extend_from_slice(&[0, 0, 16, 0, 1, 0, 0, 0, 60, 0xFF, 0xFF, 1, b'a']);
+    assert!(DnsPacket::from_bytes(&rdlength).is_err());
+    decode(&rdlength);
+
+    // TXT string length running past its rdata.
+    let mut txt = header(0, 1, 0, 0);
+    txt.extend_from_slice(&[0, 0, 16, 0, 1, 0, 0, 0, 60, 0, 2, 0xFF, b'a']);
+    assert!(DnsPacket::from_bytes(&txt).is_err());
+    decode(&txt);
+
+    // Reserved label type.
+    let mut label = header(1, 0, 0, 0);
+    label.extend_from_slice(&[0x80, 0, 0, 1, 0, 1]);
+    assert!(DnsPacket::from_bytes(&label).is_err());
+    decode(&label);
+}
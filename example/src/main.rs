@@ -1,13 +1,14 @@
 use std::env;
-use std::fmt::Write as _;
 use std::io::{Read, Write};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpStream, UdpSocket};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use dns_core::buffer::BytePacketBuffer;
+use dns_core::buffer::{BytePacketBuffer, MAX_PACKET_SIZE};
+use dns_core::edns::{Edns, EdnsOption};
+use dns_core::error::Section;
+use dns_core::name::Name;
 use dns_core::packet::DnsPacket;
 use dns_core::question::DnsQuestion;
-use dns_core::record::DnsRecord;
 use dns_core::types::QueryType;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -21,7 +22,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn lookup(
-    qname: &str,
+    qname: &Name,
     qtype: QueryType,
     server: IpAddr,
 ) -> Result<DnsPacket, Box<dyn std::error::Error>> {
@@ -30,18 +31,20 @@ fn lookup(
     request.header.recursion_desired = true;
     request
         .questions
-        .push(DnsQuestion::new(qname.to_string(), qtype));
+        .push(DnsQuestion::new(qname.clone(), qtype));
+    let mut edns = Edns::new();
+    edns.payload_size = MAX_PACKET_SIZE as u16;
+    edns.options.push(EdnsOption::NSID(Vec::new()));
+    request.edns = Some(edns);
 
-    let mut req_buffer = BytePacketBuffer::new();
-    request.write(&mut req_buffer)?;
-    let request_size = req_buffer.pos();
+    let request_bytes = request.to_bytes()?;
 
     let socket = match server {
         IpAddr::V4(_) => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?,
         IpAddr::V6(_) => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?,
     };
     socket.set_read_timeout(Some(Duration::from_secs(5)))?;
-    socket.send_to(&req_buffer.buffer[..req_buffer.pos()], (server, 53))?;
+    socket.send_to(&request_bytes, (server, 53))?;
 
     let mut resp_buffer = BytePacketBuffer::new();
     let (response_size, _) = socket.recv_from(&mut resp_buffer.buffer)?;
@@ -49,49 +52,39 @@ fn lookup(
 
     let packet = DnsPacket::from_buffer(&mut resp_buffer)?;
     if packet.header.truncated_message {
-        return tcp_lookup(&req_buffer, request_size, server);
+        return tcp_lookup(&request_bytes, server);
     }
 
     Ok(packet)
 }
 
-fn tcp_lookup(
-    req_buffer: &BytePacketBuffer,
-    request_size: usize,
-    server: IpAddr,
-) -> Result<DnsPacket, Box<dyn std::error::Error>> {
+fn tcp_lookup(request: &[u8], server: IpAddr) -> Result<DnsPacket, Box<dyn std::error::Error>> {
     let mut stream = TcpStream::connect((server, 53))?;
     stream.set_read_timeout(Some(Duration::from_secs(5)))?;
 
-    let length_prefix = (request_size as u16).to_be_bytes();
+    let length_prefix = (request.len() as u16).to_be_bytes();
     stream.write_all(&length_prefix)?;
-    stream.write_all(&req_buffer.buffer[..request_size])?;
+    stream.write_all(request)?;
 
     let mut response_len_bytes = [0u8; 2];
     stream.read_exact(&mut response_len_bytes)?;
     let response_size = u16::from_be_bytes(response_len_bytes) as usize;
 
-    if response_size > dns_core::buffer::MAX_PACKET_SIZE {
-        return Err(format!("TCP response exceeds buffer: {response_size} bytes").into());
-    }
+    let mut response = vec![0; response_size];
+    stream.read_exact(&mut response)?;
 
-    let mut resp_buffer = BytePacketBuffer::new();
-    stream.read_exact(&mut resp_buffer.buffer[..response_size])?;
-    resp_buffer.set_size(response_size);
-
-    DnsPacket::from_buffer(&mut resp_buffer)
+    Ok(DnsPacket::from_bytes(&response)?)
 }
 
-fn print_packet(domain: &str, qtype: QueryType, server: IpAddr, packet: &DnsPacket) {
-    let qtype_display = display_query_type(qtype);
-    let domain_display = display_domain(domain);
-
-    println!("; <<>> DiG <<>> @{server} {domain_display} {qtype_display}");
+fn print_packet(domain: &Name, qtype: QueryType, server: IpAddr, packet: &DnsPacket) {
+    println!("; <<>> DiG <<>> @{server} {domain} {qtype}");
     println!(";; global options: +cmd");
     println!(";; Got answer:\n");
     println!(
         ";; ->>HEADER<<- opcode: {}, status: {:?}, id: {}",
-        packet.header.opcode, packet.header.rescode, packet.header.id
+        packet.header.opcode,
+        packet.rescode(),
+        packet.header.id
     );
     println!(
         ";; flags:{}{}{}{}; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}",
@@ -105,41 +98,58 @@ fn print_packet(domain: &str, qtype: QueryType, server: IpAddr, packet: &DnsPack
         packet.header.resource_entries
     );
 
-    println!("\n;; QUESTION SECTION:");
-    for question in &packet.questions {
+    if let Some(edns) = &packet.edns {
+        println!("\n;; OPT PSEUDOSECTION:");
         println!(
-            ";{}\t{}\t{}",
-            display_domain(&question.name),
-            display_class(1),
-            display_query_type(question.qtype)
+            "; EDNS: version: {}, flags:{}; udp: {}",
+            edns.version,
+            display_flag(" do", edns.dnssec_ok),
+            edns.payload_size
         );
+        for option in &edns.options {
+            println!("; {option}");
+        }
+    }
+
+    println!(
+        "\n;; {} SECTION:",
+        section_heading(packet, Section::Question)
+    );
+    for question in &packet.questions {
+        println!(";{question}");
     }
 
     if !packet.answers.is_empty() {
-        println!("\n;; ANSWER SECTION:");
+        println!("\n;; {} SECTION:", section_heading(packet, Section::Answer));
         for record in &packet.answers {
-            println!("{}", display_record(record));
+            println!("{record}");
         }
     }
 
     if !packet.authorities.is_empty() {
-        println!("\n;; AUTHORITY SECTION:");
+        println!(
+            "\n;; {} SECTION:",
+            section_heading(packet, Section::Authority)
+        );
         for record in &packet.authorities {
-            println!("{}", display_record(record));
+            println!("{record}");
         }
     }
 
     if !packet.resources.is_empty() {
-        println!("\n;; ADDITIONAL SECTION:");
+        println!(
+            "\n;; {} SECTION:",
+            section_heading(packet, Section::Additional)
+        );
         for record in &packet.resources {
-            println!("{}", display_record(record));
+            println!("{record}");
         }
     }
 }
 
-fn parse_args() -> Result<(IpAddr, String, QueryType), Box<dyn std::error::Error>> {
+fn parse_args() -> Result<(IpAddr, Name, QueryType), Box<dyn std::error::Error>> {
     let mut server = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
-    let mut domain: Option<String> = None;
+    let mut domain: Option<Name> = None;
     let mut qtype: Option<QueryType> = None;
 
     for raw_arg in env::args().skip(1) {
@@ -149,7 +159,11 @@ fn parse_args() -> Result<(IpAddr, String, QueryType), Box<dyn std::error::Error
         }
 
         if domain.is_none() {
-            domain = Some(raw_arg);
+            domain = Some(
+                raw_arg
+                    .parse()
+                    .map_err(|err| format!("Invalid domain name '{raw_arg}': {err}"))?,
+            );
             continue;
         }
 
@@ -170,310 +184,13 @@ fn parse_args() -> Result<(IpAddr, String, QueryType), Box<dyn std::error::Error
 }
 
 fn parse_query_type(name: &str) -> Option<QueryType> {
-    let normalized = name.to_uppercase().replace(['-', '_'], "");
-
-    let parsed = match normalized.as_str() {
-        "A" => Some(QueryType::A),
-        "NS" => Some(QueryType::NS),
-        "CNAME" => Some(QueryType::CNAME),
-        "SOA" => Some(QueryType::SOA),
-        "PTR" => Some(QueryType::PTR),
-        "HINFO" => Some(QueryType::HINFO),
-        "MINFO" => Some(QueryType::MINFO),
-        "MX" => Some(QueryType::MX),
-        "TXT" => Some(QueryType::TXT),
-        "RP" => Some(QueryType::RP),
-        "AFSDB" => Some(QueryType::AFSDB),
-        "X25" => Some(QueryType::X25),
-        "ISDN" => Some(QueryType::ISDN),
-        "RT" => Some(QueryType::RT),
-        "NSAP" => Some(QueryType::NSAP),
-        "NSAPPTR" => Some(QueryType::NsapPtr),
-        "SIG" => Some(QueryType::SIG),
-        "KEY" => Some(QueryType::KEY),
-        "PX" => Some(QueryType::PX),
-        "AAAA" => Some(QueryType::AAAA),
-        "LOC" => Some(QueryType::LOC),
-        "SRV" => Some(QueryType::SRV),
-        "NAPTR" => Some(QueryType::NAPTR),
-        "KX" => Some(QueryType::KX),
-        "CERT" => Some(QueryType::CERT),
-        "DNAME" => Some(QueryType::DNAME),
-        "OPT" => Some(QueryType::OPT),
-        "APL" => Some(QueryType::APL),
-        "DS" => Some(QueryType::DS),
-        "SSHFP" => Some(QueryType::SSHFP),
-        "IPSECKEY" => Some(QueryType::IPSECKEY),
-        "RRSIG" => Some(QueryType::RRSIG),
-        "NSEC" => Some(QueryType::NSEC),
-        "DNSKEY" => Some(QueryType::DNSKEY),
-        "DHCID" => Some(QueryType::DHCID),
-        "NSEC3" => Some(QueryType::NSEC3),
-        "NSEC3PARAM" => Some(QueryType::NSEC3PARAM),
-        "TLSA" => Some(QueryType::TLSA),
-        "SMIMEA" => Some(QueryType::SMIMEA),
-        "HIP" => Some(QueryType::HIP),
-        "CDS" => Some(QueryType::CDS),
-        "CDNSKEY" => Some(QueryType::CDNSKEY),
-        "OPENPGPKEY" => Some(QueryType::OPENPGPKEY),
-        "CSYNC" => Some(QueryType::CSYNC),
-        "ZONEMD" => Some(QueryType::ZONEMD),
-        "SVCB" => Some(QueryType::SVCB),
-        "HTTPS" => Some(QueryType::HTTPS),
-        "SPF" => Some(QueryType::SPF),
-        "NID" => Some(QueryType::NID),
-        "L32" => Some(QueryType::L32),
-        "L64" => Some(QueryType::L64),
-        "LP" => Some(QueryType::LP),
-        "EUI48" => Some(QueryType::EUI48),
-        "EUI64" => Some(QueryType::EUI64),
-        "TKEY" => Some(QueryType::TKEY),
-        "TSIG" => Some(QueryType::TSIG),
-        "IXFR" => Some(QueryType::IXFR),
-        "AXFR" => Some(QueryType::AXFR),
-        "ANY" => Some(QueryType::ANY),
-        "URI" => Some(QueryType::URI),
-        "CAA" => Some(QueryType::CAA),
-        "AVC" => Some(QueryType::AVC),
-        "DOA" => Some(QueryType::DOA),
-        "AMTRELAY" => Some(QueryType::AMTRELAY),
-        "TA" => Some(QueryType::TA),
-        "DLV" => Some(QueryType::DLV),
-        _ => None,
-    };
-
-    parsed.or_else(|| name.parse::<u16>().ok().map(QueryType::from_num))
+    name.parse()
+        .ok()
+        .or_else(|| name.parse::<u16>().ok().map(QueryType::from_num))
 }
 
-fn display_class(class: u16) -> String {
-    match class {
-        1 => "IN".to_string(),
-        2 => "CS".to_string(),
-        3 => "CH".to_string(),
-        4 => "HS".to_string(),
-        value => format!("CLASS{value}"),
-    }
-}
-
-fn display_query_type(qtype: QueryType) -> String {
-    match qtype {
-        QueryType::UNKNOWN(value) => format!("TYPE{value}"),
-        QueryType::A => "A".to_string(),
-        QueryType::NS => "NS".to_string(),
-        QueryType::CNAME => "CNAME".to_string(),
-        QueryType::SOA => "SOA".to_string(),
-        QueryType::PTR => "PTR".to_string(),
-        QueryType::HINFO => "HINFO".to_string(),
-        QueryType::MINFO => "MINFO".to_string(),
-        QueryType::MX => "MX".to_string(),
-        QueryType::TXT => "TXT".to_string(),
-        QueryType::RP => "RP".to_string(),
-        QueryType::AFSDB => "AFSDB".to_string(),
-        QueryType::X25 => "X25".to_string(),
-        QueryType::ISDN => "ISDN".to_string(),
-        QueryType::RT => "RT".to_string(),
-        QueryType::NSAP => "NSAP".to_string(),
-        QueryType::NsapPtr => "NSAP-PTR".to_string(),
-        QueryType::SIG => "SIG".to_string(),
-        QueryType::KEY => "KEY".to_string(),
-        QueryType::PX => "PX".to_string(),
-        QueryType::AAAA => "AAAA".to_string(),
-        QueryType::LOC => "LOC".to_string(),
-        QueryType::SRV => "SRV".to_string(),
-        QueryType::NAPTR => "NAPTR".to_string(),
-        QueryType::KX => "KX".to_string(),
-        QueryType::CERT => "CERT".to_string(),
-        QueryType::DNAME => "DNAME".to_string(),
-        QueryType::OPT => "OPT".to_string(),
-        QueryType::APL => "APL".to_string(),
-        QueryType::DS => "DS".to_string(),
-        QueryType::SSHFP => "SSHFP".to_string(),
-        QueryType::IPSECKEY => "IPSECKEY".to_string(),
-        QueryType::RRSIG => "RRSIG".to_string(),
-        QueryType::NSEC => "NSEC".to_string(),
-        QueryType::DNSKEY => "DNSKEY".to_string(),
-        QueryType::DHCID => "DHCID".to_string(),
-        QueryType::NSEC3 => "NSEC3".to_string(),
-        QueryType::NSEC3PARAM => "NSEC3PARAM".to_string(),
-        QueryType::TLSA => "TLSA".to_string(),
-        QueryType::SMIMEA => "SMIMEA".to_string(),
-        QueryType::HIP => "HIP".to_string(),
-        QueryType::CDS => "CDS".to_string(),
-        QueryType::CDNSKEY => "CDNSKEY".to_string(),
-        QueryType::OPENPGPKEY => "OPENPGPKEY".to_string(),
-        QueryType::CSYNC => "CSYNC".to_string(),
-        QueryType::ZONEMD => "ZONEMD".to_string(),
-        QueryType::SVCB => "SVCB".to_string(),
-        QueryType::HTTPS => "HTTPS".to_string(),
-        QueryType::SPF => "SPF".to_string(),
-        QueryType::NID => "NID".to_string(),
-        QueryType::L32 => "L32".to_string(),
-        QueryType::L64 => "L64".to_string(),
-        QueryType::LP => "LP".to_string(),
-        QueryType::EUI48 => "EUI48".to_string(),
-        QueryType::EUI64 => "EUI64".to_string(),
-        QueryType::TKEY => "TKEY".to_string(),
-        QueryType::TSIG => "TSIG".to_string(),
-        QueryType::IXFR => "IXFR".to_string(),
-        QueryType::AXFR => "AXFR".to_string(),
-        QueryType::ANY => "ANY".to_string(),
-        QueryType::URI => "URI".to_string(),
-        QueryType::CAA => "CAA".to_string(),
-        QueryType::AVC => "AVC".to_string(),
-        QueryType::DOA => "DOA".to_string(),
-        QueryType::AMTRELAY => "AMTRELAY".to_string(),
-        QueryType::TA => "TA".to_string(),
-        QueryType::DLV => "DLV".to_string(),
-    }
-}
-
-fn display_record(record: &DnsRecord) -> String {
-    match record {
-        DnsRecord::A {
-            domain,
-            class,
-            ttl,
-            addr,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tA\t{}",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            addr
-        ),
-        DnsRecord::AAAA {
-            domain,
-            addr,
-            class,
-            ttl,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tAAAA\t{}",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            addr
-        ),
-        DnsRecord::MX {
-            domain,
-            ttl,
-            priority,
-            host,
-            class,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tMX\t{}\t{}",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            priority,
-            display_domain(host)
-        ),
-        DnsRecord::NS {
-            domain,
-            ttl,
-            host,
-            class,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tNS\t{}",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            display_domain(host)
-        ),
-        DnsRecord::CNAME {
-            domain,
-            ttl,
-            host,
-            class,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tCNAME\t{}",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            display_domain(host)
-        ),
-        DnsRecord::UNKNOWN {
-            domain,
-            qtype,
-            ttl,
-            class,
-            ..
-        } => {
-            let mut line = String::new();
-            let _ = write!(
-                &mut line,
-                "{}\t{}\t{}\t{}",
-                display_domain(domain),
-                ttl,
-                display_class(*class),
-                display_query_type(*qtype)
-            );
-            line
-        }
-        DnsRecord::TXT {
-            domain,
-            ttl,
-            class,
-            data,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tTXT\t{}",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            data.join(" ")
-        ),
-        DnsRecord::SOA {
-            domain,
-            ttl,
-            class,
-            mname,
-            rname,
-            serial,
-            refresh,
-            retry,
-            expire,
-            minimum,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tSOA\t{} {} ( {} {} {} {} {} )",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            display_domain(mname),
-            display_domain(rname),
-            serial,
-            refresh,
-            retry,
-            expire,
-            minimum
-        ),
-        DnsRecord::PTR {
-            domain,
-            ttl,
-            class,
-            host,
-            ..
-        } => format!(
-            "{}\t{}\t{}\tPTR\t{}",
-            display_domain(domain),
-            ttl,
-            display_class(*class),
-            display_domain(host)
-        ),
-    }
-}
-
-fn display_domain(domain: &str) -> String {
-    if domain.ends_with('.') {
-        domain.to_string()
-    } else {
-        format!("{domain}.")
-    }
+fn section_heading(packet: &DnsPacket, section: Section) -> String {
+    packet.section_name(section).to_uppercase()
 }
 
 fn display_flag(label: &str, present: bool) -> String {
@@ -649,7 +366,7 @@ mod tests {
     #[test]
     fn queries_google_for_each_supported_type() {
         for &qtype in ALL_QUERY_TYPES {
-            let response = lookup("google.com", qtype, GOOGLE_DNS);
+            let response = lookup(&"google.com".parse().unwrap(), qtype, GOOGLE_DNS);
             if let Err(err) = response {
                 if err
                     .downcast_ref::<std::io::Error>()